@@ -0,0 +1,10 @@
+// FOURMEME_ANVIL_TESTS=1 时启用 anvil_tests cfg：本地链集成测试（tests/anvil.rs）默认标记为ignored，
+// 设置后必须真正启动anvil运行，缺少anvil或无法取得solc时测试失败而不是跳过。
+fn main() {
+    println!("cargo::rustc-check-cfg=cfg(anvil_tests)");
+    println!("cargo::rerun-if-changed=build.rs");
+    println!("cargo::rerun-if-env-changed=FOURMEME_ANVIL_TESTS");
+    if std::env::var("FOURMEME_ANVIL_TESTS").is_ok_and(|value| value.trim() == "1") {
+        println!("cargo::rustc-cfg=anvil_tests");
+    }
+}
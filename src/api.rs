@@ -0,0 +1,971 @@
+// Four.meme Web API
+// 所有接口都返回 {code, msg, data} 信封：code可能是数字、字符串或null，出错时data通常为null。
+// 统一用ApiEnvelope<T>宽松解析，失败时保留HTTP状态码和原始响应体，按类型归类为ApiError。
+// 绑定钱包和会话缓存后，需要登录的接口遇到登录失效会自动重新登录并重试一次。
+
+use chrono::Utc;
+use ethers::signers::{LocalWallet, Signer};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+use crate::logging;
+use crate::metadata::TokenLabel;
+use crate::session::SessionStore;
+use crate::token_image::{prepare_image, ImageLimits, ImageSource};
+use crate::traffic::{self, Exchange, Traffic};
+use crate::config::{CREATE_TOKEN_RAISED_AMOUNT, CREATE_TOKEN_RESERVE_RATE, CREATE_TOKEN_SALE_RATE, CREATE_TOKEN_TOTAL_SUPPLY, DEFAULT_NETWORK_CODE, DEFAULT_WALLET_NAME, FOURMEME_API_BASE_URL, TOKEN_IMAGE_DOWNLOAD_MAX_BYTES};
+
+// 通用响应信封
+#[derive(Debug, Deserialize)]
+pub struct ApiEnvelope<T> {
+    #[serde(default, deserialize_with = "deserialize_code")]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub msg: Option<String>,
+    #[serde(default = "Option::default")]
+    pub data: Option<T>,
+}
+
+impl<T> ApiEnvelope<T> {
+    // code缺失、为0或"0"都视为成功
+    pub fn is_success(&self) -> bool {
+        match self.code.as_deref() {
+            None => true,
+            Some(code) => code.trim() == "0",
+        }
+    }
+}
+
+// code兼容数字、字符串和null
+fn deserialize_code<'de, D>(deserializer: D) -> std::result::Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(match value {
+        None | Some(serde_json::Value::Null) => None,
+        Some(serde_json::Value::String(s)) => Some(s),
+        Some(other) => Some(other.to_string()),
+    })
+}
+
+// 一次失败请求的上下文，body为原始响应体，用于日志排查
+#[derive(Debug, Clone)]
+pub struct ApiFailure {
+    pub endpoint: String,
+    pub status: u16,
+    pub code: Option<String>,
+    pub msg: String,
+    pub body: String,
+}
+
+impl fmt::Display for ApiFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (HTTP {}, code: {}): {} | 原始响应: {}",
+            self.endpoint,
+            self.status,
+            self.code.as_deref().unwrap_or("-"),
+            self.msg,
+            self.body
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum ApiError {
+    AuthExpired(ApiFailure), // 未登录或access_token过期，需要重新登录
+    Validation(ApiFailure),  // 参数被服务端拒绝
+    RateLimited(ApiFailure), // 请求过于频繁
+    Server(ApiFailure),      // 5xx或无法解析的响应
+    Transport(String),       // 网络错误、本地参数错误等，请求未得到响应
+}
+
+impl ApiError {
+    // 根据HTTP状态码和信封中的code/msg归类
+    fn classify(failure: ApiFailure) -> Self {
+        let text = format!("{} {}", failure.code.as_deref().unwrap_or(""), failure.msg).to_lowercase();
+        let mentions = |keys: &[&str]| keys.iter().any(|k| text.contains(k));
+
+        if failure.status == 401
+            || failure.status == 403
+            || mentions(&[
+                "token expired",
+                "not login",
+                "unauthorized",
+                "login expired",
+                "access denied",
+                "未登录",
+                "请先登录",
+                "请重新登录",
+                "登录已过期",
+                "登录过期",
+                "登录已失效",
+                "登录失效",
+            ])
+        {
+            ApiError::AuthExpired(failure)
+        } else if failure.status == 429 || mentions(&["too many", "rate limit", "frequent", "频繁"]) {
+            ApiError::RateLimited(failure)
+        } else if failure.status >= 500 {
+            ApiError::Server(failure)
+        } else {
+            ApiError::Validation(failure)
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::AuthExpired(failure) => write!(f, "登录已失效: {}", failure),
+            ApiError::Validation(failure) => write!(f, "请求参数被拒绝: {}", failure),
+            ApiError::RateLimited(failure) => write!(f, "请求过于频繁: {}", failure),
+            ApiError::Server(failure) => write!(f, "服务端错误: {}", failure),
+            ApiError::Transport(e) => write!(f, "请求失败: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(e: reqwest::Error) -> Self {
+        ApiError::Transport(e.to_string())
+    }
+}
+
+// Four.meme API 相关结构体定义
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NonceRequest {
+    #[serde(rename = "accountAddress")]
+    pub account_address: String,
+    #[serde(rename = "verifyType")]
+    pub verify_type: String,
+    #[serde(rename = "networkCode")]
+    pub network_code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyInfo {
+    pub address: String,
+    #[serde(rename = "networkCode")]
+    pub network_code: String,
+    pub signature: String,
+    #[serde(rename = "verifyType")]
+    pub verify_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoginRequest {
+    pub region: String,
+    #[serde(rename = "langType")]
+    pub lang_type: String,
+    #[serde(rename = "loginIp")]
+    pub login_ip: String,
+    #[serde(rename = "inviteCode")]
+    pub invite_code: String,
+    #[serde(rename = "verifyInfo")]
+    pub verify_info: VerifyInfo,
+    #[serde(rename = "walletName")]
+    pub wallet_name: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RaisedToken {
+    pub symbol: String,
+    #[serde(rename = "nativeSymbol")]
+    pub native_symbol: String,
+    #[serde(rename = "symbolAddress")]
+    pub symbol_address: String,
+    #[serde(rename = "deployCost")]
+    pub deploy_cost: String,
+    #[serde(rename = "buyFee")]
+    pub buy_fee: String,
+    #[serde(rename = "sellFee")]
+    pub sell_fee: String,
+    #[serde(rename = "minTradeFee")]
+    pub min_trade_fee: String,
+    #[serde(rename = "b0Amount")]
+    pub b0_amount: String,
+    #[serde(rename = "totalBAmount")]
+    pub total_b_amount: String,
+    #[serde(rename = "totalAmount")]
+    pub total_amount: String,
+    #[serde(rename = "logoUrl")]
+    pub logo_url: String,
+    #[serde(rename = "tradeLevel")]
+    pub trade_level: Vec<String>,
+    pub status: String,
+    #[serde(rename = "buyTokenLink")]
+    pub buy_token_link: String,
+    #[serde(rename = "reservedNumber")]
+    pub reserved_number: u32,
+    #[serde(rename = "saleRate")]
+    pub sale_rate: String,
+    #[serde(rename = "networkCode")]
+    pub network_code: String,
+    pub platform: String,
+}
+
+impl RaisedToken {
+    // 以链上原生币（BNB）募集时，symbol与nativeSymbol相同
+    pub fn is_native(&self) -> bool {
+        self.symbol.eq_ignore_ascii_case(&self.native_symbol)
+    }
+
+    // 平台是否开放该募集代币
+    pub fn is_published(&self) -> bool {
+        self.status.eq_ignore_ascii_case("PUBLISH")
+    }
+
+    // 募集上限（totalBAmount，整数个募集代币）；平台返回无法解析的值时报错，不退回内置值
+    pub fn raised_amount(&self) -> std::result::Result<u32, String> {
+        self.total_b_amount
+            .trim()
+            .parse()
+            .map_err(|_| format!("平台配置中{}的totalBAmount不是有效的整数: '{}'", self.symbol, self.total_b_amount))
+    }
+}
+
+// 内置的raisedToken配置（BNB募集），仅在无法获取平台配置时使用
+pub fn default_raised_token() -> RaisedToken {
+    RaisedToken {
+        symbol: "BNB".to_string(),
+        native_symbol: "BNB".to_string(),
+        symbol_address: "0xbb4cdb9cbd36b01bd1cbaebf2de08d9173bc095c".to_string(),
+        deploy_cost: "0".to_string(),
+        buy_fee: "0.01".to_string(),
+        sell_fee: "0.01".to_string(),
+        min_trade_fee: "0".to_string(),
+        b0_amount: "8".to_string(),
+        total_b_amount: CREATE_TOKEN_RAISED_AMOUNT.to_string(),
+        total_amount: "1000000000".to_string(),
+        logo_url: "https://static.four.meme/market/fc6c4c92-63a3-4034-bc27-355ea380a6795959172881106751506.png".to_string(),
+        trade_level: vec!["0.1".to_string(), "0.5".to_string(), "1".to_string()],
+        status: "PUBLISH".to_string(),
+        buy_token_link: "https://pancakeswap.finance/swap".to_string(),
+        reserved_number: 10,
+        sale_rate: "0.8".to_string(),
+        network_code: "BSC".to_string(),
+        platform: "MEME".to_string(),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateTokenRequest {
+    pub name: String,
+    #[serde(rename = "shortName")]
+    pub short_name: String,
+    pub desc: String,
+    #[serde(rename = "imgUrl")]
+    pub img_url: String,
+    #[serde(rename = "launchTime")]
+    pub launch_time: u64,
+    pub label: TokenLabel,
+    #[serde(rename = "lpTradingFee")]
+    pub lp_trading_fee: f64,
+    #[serde(rename = "webUrl")]
+    pub web_url: String,
+    #[serde(rename = "twitterUrl")]
+    pub twitter_url: String,
+    #[serde(rename = "telegramUrl")]
+    pub telegram_url: String,
+    #[serde(rename = "preSale")]
+    pub pre_sale: String,
+    // 固定参数
+    #[serde(rename = "totalSupply")]
+    pub total_supply: u64,
+    #[serde(rename = "raisedAmount")]
+    pub raised_amount: u32,
+    #[serde(rename = "saleRate")]
+    pub sale_rate: f64,
+    #[serde(rename = "reserveRate")]
+    pub reserve_rate: f64,
+    #[serde(rename = "funGroup")]
+    pub fun_group: bool,
+    #[serde(rename = "clickFun")]
+    pub click_fun: bool,
+    pub symbol: String,
+    #[serde(rename = "raisedToken")]
+    pub raised_token: RaisedToken,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTokenData {
+    #[serde(rename = "tokenId")]
+    pub token_id: u64,
+    #[serde(rename = "totalAmount")]
+    pub total_amount: String,
+    #[serde(rename = "saleAmount")]
+    pub sale_amount: String,
+    pub template: u32,
+    #[serde(rename = "launchTime")]
+    pub launch_time: u64,
+    #[serde(rename = "serverTime")]
+    pub server_time: u64,
+    #[serde(rename = "createArg")]
+    pub create_arg: String,
+    pub signature: String,
+    pub bamount: String,  // 需要的BNB数量
+    pub tamount: String,  // 代币数量
+    #[serde(rename = "contractAddress")]
+    pub contract_address: Option<String>,  // 合约地址（可选，API可能不返回）
+}
+
+// 创建代币参数结构体 - 更新为Four.meme API格式
+#[derive(Debug, Clone)]
+pub struct CreateTokenParams {
+    pub name: String,
+    pub short_name: String, // symbol
+    pub desc: String,
+    pub img_url: String,
+    pub launch_time: Option<u64>, // 毫秒时间戳，不填为立即发布
+    pub label: TokenLabel,
+    pub web_url: Option<String>,
+    pub twitter_url: Option<String>,
+    pub telegram_url: Option<String>,
+    pub pre_sale: String, // 预购买的数量（以募集代币计），"0"表示不预购买
+    pub raise_symbol: String, // 募集代币符号，如BNB
+}
+
+// Four.meme API客户端结构体
+pub struct FourMemeApiClient {
+    client: reqwest::Client,
+    base_url: String,
+    access_token: Option<String>,
+    wallet: Option<LocalWallet>,     // 用于自动重新登录
+    sessions: Option<SessionStore>, // 登录会话的磁盘缓存
+    traffic: Option<Arc<Traffic>>,  // 录制/回放（见traffic模块）
+}
+
+impl Default for FourMemeApiClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FourMemeApiClient {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: FOURMEME_API_BASE_URL.to_string(),
+            access_token: None,
+            wallet: None,
+            sessions: None,
+            traffic: traffic::current(),
+        }
+    }
+
+    // 替换API地址，例如指向测试环境或本地mock服务
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    // 录制请求和响应，或者从录制文件回放而不访问网络
+    pub fn with_traffic(mut self, traffic: Arc<Traffic>) -> Self {
+        self.traffic = Some(traffic);
+        self
+    }
+
+    // 绑定钱包和会话缓存：优先复用缓存的access_token，登录失效时自动重新登录
+    pub fn with_session(mut self, wallet: LocalWallet, sessions: SessionStore) -> Self {
+        self.wallet = Some(wallet);
+        self.sessions = Some(sessions);
+        self
+    }
+
+    // 只绑定钱包、不缓存会话：每个客户端首次请求时登录，登录失效时自动重新登录
+    pub fn with_wallet(mut self, wallet: LocalWallet) -> Self {
+        self.wallet = Some(wallet);
+        self
+    }
+
+    // 确保已登录：内存中已有token直接返回，否则读取未过期的缓存，都没有时重新登录
+    pub async fn ensure_session(&mut self) -> std::result::Result<(), ApiError> {
+        if self.access_token.is_some() {
+            return Ok(());
+        }
+        if let Some(session) = self.sessions.as_ref().and_then(|s| s.load()) {
+            logging::register_secret(&session.access_token);
+            tracing::info!(expires_at = %format_timestamp(session.expires_at), "复用已缓存的登录会话");
+            self.access_token = Some(session.access_token);
+            return Ok(());
+        }
+        self.relogin().await
+    }
+
+    // 通过get_nonce + login重新获取access_token，并写入会话缓存
+    #[tracing::instrument(name = "api.login", skip_all, fields(wallet = tracing::field::Empty))]
+    pub async fn relogin(&mut self) -> std::result::Result<(), ApiError> {
+        let wallet = self
+            .wallet
+            .clone()
+            .ok_or_else(|| ApiError::Transport("未绑定钱包，无法自动登录".to_string()))?;
+        tracing::Span::current().record("wallet", tracing::field::debug(wallet.address()));
+
+        let nonce = self.get_nonce(&format!("{:?}", wallet.address())).await?;
+        let access_token = self.login(&wallet, &nonce).await?;
+        tracing::info!("登录成功，获得access_token");
+
+        if let Some(sessions) = &self.sessions {
+            if let Err(e) = sessions.save(&access_token) {
+                tracing::warn!(error = %e, "保存登录会话失败");
+            }
+        }
+        Ok(())
+    }
+
+    // 发送需要登录的请求：build根据access_token构造请求，登录失效时重新登录后重试一次
+    async fn send_authed<T, F>(&mut self, endpoint: &str, build: F) -> std::result::Result<T, ApiError>
+    where
+        T: DeserializeOwned,
+        F: Fn(&str) -> std::result::Result<reqwest::RequestBuilder, ApiError>,
+    {
+        self.ensure_session().await?;
+        let request = build(self.access_token()?)?;
+        match self.send(endpoint, request).await {
+            Err(ApiError::AuthExpired(failure)) if self.wallet.is_some() => {
+                tracing::warn!(endpoint, error = %failure, "登录已失效，重新登录后重试");
+                self.access_token = None;
+                if let Some(sessions) = &self.sessions {
+                    sessions.clear();
+                }
+                self.relogin().await?;
+                let request = build(self.access_token()?)?;
+                self.send(endpoint, request).await
+            }
+            result => result,
+        }
+    }
+
+    // 发送请求并解析信封：code非0、data为null或响应无法解析时返回带原始响应体的ApiError
+    async fn send<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        request: reqwest::RequestBuilder,
+    ) -> std::result::Result<T, ApiError> {
+        let (status, body) = self.exchange(endpoint, request).await?;
+
+        let failure = |code: Option<String>, msg: String| ApiFailure {
+            endpoint: endpoint.to_string(),
+            status,
+            code,
+            msg,
+            body: body.clone(),
+        };
+
+        let envelope: ApiEnvelope<T> = match serde_json::from_str(&body) {
+            Ok(envelope) => envelope,
+            Err(e) => {
+                let failure = failure(None, format!("响应解析失败: {}", e));
+                // 非2xx的非JSON响应（网关错误页等）仍按状态码归类
+                return Err(if (200..300).contains(&status) {
+                    ApiError::Server(failure)
+                } else {
+                    ApiError::classify(failure)
+                });
+            }
+        };
+
+        let msg = envelope.msg.clone().unwrap_or_default();
+        if !(200..300).contains(&status) || !envelope.is_success() {
+            return Err(ApiError::classify(failure(envelope.code, msg)));
+        }
+
+        envelope
+            .data
+            .ok_or_else(|| ApiError::Server(failure(envelope.code, format!("响应缺少data字段 {}", msg).trim_end().to_string())))
+    }
+
+    // 发送请求并返回(状态码, 响应体)；录制模式下记录本次交互，回放模式下直接返回记录的响应
+    async fn exchange(&self, endpoint: &str, request: reqwest::RequestBuilder) -> std::result::Result<(u16, String), ApiError> {
+        let request = request.build()?;
+        let method = request.method().to_string();
+        if let Some(traffic) = self.traffic.as_ref().filter(|t| t.is_replay()) {
+            return traffic
+                .replay_http(&method, endpoint)
+                .ok_or_else(|| ApiError::Transport(format!("回放记录中没有 {} {} 的响应", method, endpoint)));
+        }
+
+        // multipart上传的请求体是流，不记录
+        let request_body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| serde_json::from_slice(bytes).unwrap_or_else(|_| String::from_utf8_lossy(bytes).into()))
+            .unwrap_or_default();
+        let response = self.client.execute(request).await?;
+        let status = response.status().as_u16();
+        let body = response.text().await?;
+        let exchange = Exchange::Http {
+            method,
+            endpoint: endpoint.to_string(),
+            request: request_body,
+            status,
+            response: body.clone(),
+        };
+        // 请求体和响应按录制的脱敏规则处理后再进日志（登录签名、access_token等）
+        if tracing::enabled!(tracing::Level::DEBUG) {
+            if let Exchange::Http { method, request, response, .. } = traffic::redact(exchange.clone()) {
+                tracing::debug!(method, endpoint, status, request = %request, response = %response, "Four.meme API请求");
+            }
+        }
+        if let Some(traffic) = &self.traffic {
+            traffic.log(exchange);
+        }
+        Ok((status, body))
+    }
+
+    fn access_token(&self) -> std::result::Result<&str, ApiError> {
+        self.access_token
+            .as_deref()
+            .ok_or_else(|| ApiError::Transport("需要先登录获取access_token".to_string()))
+    }
+
+    // 1. 获取nonce
+    pub async fn get_nonce(&self, account_address: &str) -> std::result::Result<String, ApiError> {
+        let nonce_request = NonceRequest {
+            account_address: account_address.to_string(),
+            verify_type: "LOGIN".to_string(),
+            network_code: DEFAULT_NETWORK_CODE.to_string(),
+        };
+
+        let request = self
+            .client
+            .post(format!("{}/v1/private/user/nonce/generate", self.base_url))
+            .json(&nonce_request);
+
+        self.send("nonce/generate", request).await
+    }
+
+    // 2. 用户登录
+    pub async fn login(&mut self, wallet: &LocalWallet, nonce: &str) -> std::result::Result<String, ApiError> {
+        let account_address = format!("{:?}", wallet.address());
+
+        // 签名消息
+        let message = format!("You are sign in Meme {}", nonce);
+        let signature = wallet
+            .sign_message(message.as_bytes())
+            .await
+            .map_err(|e| ApiError::Transport(format!("签名登录消息失败: {}", e)))?;
+        let signature_hex = format!("0x{}", hex::encode(signature.to_vec()));
+
+        let verify_info = VerifyInfo {
+            address: account_address.clone(),
+            network_code: DEFAULT_NETWORK_CODE.to_string(),
+            signature: signature_hex,
+            verify_type: "LOGIN".to_string(),
+        };
+
+        let login_request = LoginRequest {
+            region: "WEB".to_string(),
+            lang_type: "EN".to_string(),
+            login_ip: "".to_string(),
+            invite_code: "".to_string(),
+            verify_info,
+            wallet_name: DEFAULT_WALLET_NAME.to_string(),
+        };
+
+        let request = self
+            .client
+            .post(format!("{}/v1/private/user/login/dex", self.base_url))
+            .json(&login_request);
+
+        let access_token: String = self.send("login/dex", request).await?;
+        logging::register_secret(&access_token);
+        self.access_token = Some(access_token.clone());
+        Ok(access_token)
+    }
+
+    // 3. 上传代币图片：识别真实格式并校验/转换后上传，返回图片URL
+    pub async fn upload_image(&mut self, source: &ImageSource) -> std::result::Result<String, ApiError> {
+        let raw = self.read_image(source).await?;
+        let image = prepare_image(&raw, &ImageLimits::default())
+            .map_err(|e| ApiError::Transport(format!("图片不符合要求 {}: {}", source.describe(), e)))?;
+        tracing::info!(
+            source = %source.describe(),
+            width = image.width,
+            height = image.height,
+            mime = %image.mime,
+            bytes = image.data.len(),
+            reencoded = image.reencoded,
+            "图片已校验"
+        );
+
+        // 生成随机文件名
+        let random_filename = format!("{:x}.{}", 
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos() % 0xffffffffffffffff, 
+            image.extension
+        );
+
+        let url = format!("{}/v1/private/token/upload", self.base_url);
+        let client = self.client.clone();
+        self.send_authed("token/upload", |access_token| {
+            // multipart表单不能复用，每次请求重新构造
+            let form = reqwest::multipart::Form::new()
+                .part("file", reqwest::multipart::Part::bytes(image.data.clone())
+                    .file_name(random_filename.clone())
+                    .mime_str(image.mime)?);
+
+            Ok(client.post(&url).header("meme-web-access", access_token).multipart(form))
+        })
+        .await
+    }
+
+    async fn read_image(&self, source: &ImageSource) -> std::result::Result<Vec<u8>, ApiError> {
+        match source {
+            ImageSource::Path(path) => std::fs::read(path)
+                .map_err(|e| ApiError::Transport(format!("读取图片失败 {}: {}", path.display(), e))),
+            ImageSource::Bytes { data, .. } => Ok(data.clone()),
+            ImageSource::Url(url) => {
+                let response = self.client.get(url).send().await?;
+                if !response.status().is_success() {
+                    return Err(ApiError::Transport(format!("下载图片失败 {}: HTTP {}", url, response.status())));
+                }
+                read_limited(response, url, TOKEN_IMAGE_DOWNLOAD_MAX_BYTES).await
+            }
+        }
+    }
+
+    // 平台公开配置中的募集代币列表，不需要登录
+    pub async fn get_raised_tokens(&self) -> std::result::Result<Vec<RaisedToken>, ApiError> {
+        let request = self.client.get(format!("{}/v1/public/config", self.base_url));
+        let data: serde_json::Value = self.send("public/config", request).await?;
+
+        // data可能直接是列表，也可能是包含列表的对象
+        let items = match data {
+            serde_json::Value::Array(items) => items,
+            serde_json::Value::Object(map) => map
+                .into_iter()
+                .find_map(|(_, v)| match v {
+                    serde_json::Value::Array(items) if items.iter().any(|i| i.get("symbolAddress").is_some()) => Some(items),
+                    _ => None,
+                })
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+
+        items
+            .into_iter()
+            .map(|item| {
+                serde_json::from_value::<RaisedToken>(stringify_numbers(item))
+                    .map_err(|e| ApiError::Transport(format!("平台配置格式错误: {}", e)))
+            })
+            .collect()
+    }
+
+    // 4. 创建代币，raised_token为平台配置中选定的募集代币
+    pub async fn create_token(
+        &mut self,
+        params: &CreateTokenParams,
+        raised_token: &RaisedToken,
+    ) -> std::result::Result<CreateTokenData, ApiError> {
+        let raised_amount = raised_token.raised_amount().map_err(ApiError::Transport)?;
+
+        // 未指定发布时间时立即发布
+        let launch_time = params.launch_time.unwrap_or_else(|| Utc::now().timestamp_millis() as u64);
+
+        let create_request = CreateTokenRequest {
+            name: params.name.clone(),
+            short_name: params.short_name.clone(),
+            desc: params.desc.clone(),
+            img_url: params.img_url.clone(),
+            launch_time,
+            label: params.label,
+            lp_trading_fee: 0.0025,
+            web_url: params.web_url.clone().unwrap_or_default(),
+            twitter_url: params.twitter_url.clone().unwrap_or_default(),
+            telegram_url: params.telegram_url.clone().unwrap_or_default(),
+            pre_sale: params.pre_sale.clone(),
+            // 固定参数
+            total_supply: CREATE_TOKEN_TOTAL_SUPPLY,
+            raised_amount,
+            sale_rate: CREATE_TOKEN_SALE_RATE,
+            reserve_rate: CREATE_TOKEN_RESERVE_RATE,
+            fun_group: false,
+            click_fun: false,
+            symbol: raised_token.symbol.clone(),
+            raised_token: raised_token.clone(),
+        };
+
+        let url = format!("{}/v1/private/token/create", self.base_url);
+        let client = self.client.clone();
+        self.send_authed("token/create", |access_token| {
+            Ok(client.post(&url).header("meme-web-access", access_token).json(&create_request))
+        })
+        .await
+    }
+}
+
+// 平台配置中的数值字段有时是数字有时是字符串，统一转成字符串（reservedNumber除外）
+fn stringify_numbers(mut item: serde_json::Value) -> serde_json::Value {
+    if let serde_json::Value::Object(map) = &mut item {
+        for (key, value) in map.iter_mut() {
+            if key != "reservedNumber" && value.is_number() {
+                *value = serde_json::Value::String(value.to_string());
+            }
+        }
+    }
+    item
+}
+
+fn format_timestamp(ts: i64) -> String {
+    chrono::DateTime::from_timestamp(ts, 0)
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| ts.to_string())
+}
+
+// 读取响应体，Content-Length或已读取的字节数超过max_bytes时立即放弃，不把整个响应读进内存
+async fn read_limited(mut response: reqwest::Response, url: &str, max_bytes: usize) -> std::result::Result<Vec<u8>, ApiError> {
+    let too_large = || ApiError::Transport(format!("下载图片失败 {}: 超过{}字节上限", url, max_bytes));
+    if response.content_length().is_some_and(|len| len > max_bytes as u64) {
+        return Err(too_large());
+    }
+    let mut data = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if data.len() + chunk.len() > max_bytes {
+            return Err(too_large());
+        }
+        data.extend_from_slice(&chunk);
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::{MockFourMeme, MOCK_IMAGE_URL};
+    use chrono::Duration;
+    use ethers::types::Signature;
+    use std::str::FromStr;
+
+    const TEST_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+    fn wallet() -> LocalWallet {
+        LocalWallet::from_str(TEST_KEY).unwrap()
+    }
+
+    fn params() -> CreateTokenParams {
+        CreateTokenParams {
+            name: "MockMeme".to_string(),
+            short_name: "MOCK".to_string(),
+            desc: "mock token".to_string(),
+            img_url: MOCK_IMAGE_URL.to_string(),
+            launch_time: Some(1_700_000_000_000),
+            label: TokenLabel::Meme,
+            web_url: None,
+            twitter_url: None,
+            telegram_url: None,
+            pre_sale: "0.2".to_string(),
+            raise_symbol: "BNB".to_string(),
+        }
+    }
+
+    fn client(mock: &MockFourMeme, dir: &tempfile::TempDir) -> FourMemeApiClient {
+        let sessions = SessionStore::new(dir.path(), &wallet(), Duration::hours(1));
+        FourMemeApiClient::new()
+            .with_base_url(&mock.base_url)
+            .with_session(wallet(), sessions)
+    }
+
+    fn api_failure(status: u16, code: Option<&str>, msg: &str) -> ApiFailure {
+        ApiFailure {
+            endpoint: "/v1/private/token/create".to_string(),
+            status,
+            code: code.map(str::to_string),
+            msg: msg.to_string(),
+            body: String::new(),
+        }
+    }
+
+    #[test]
+    fn classify_auth_only_on_status_or_expiry_phrases() {
+        let auth = |status, code, msg| matches!(ApiError::classify(api_failure(status, code, msg)), ApiError::AuthExpired(_));
+        assert!(auth(401, None, ""));
+        assert!(auth(403, Some("-1"), "forbidden"));
+        assert!(auth(200, Some("-1"), "Token expired"));
+        assert!(auth(200, Some("-1"), "用户未登录"));
+        assert!(auth(200, Some("-1"), "登录已过期，请重新登录"));
+
+        // 只是提到"登录"的校验错误不能被当成登录失效而触发重新登录
+        let err = ApiError::classify(api_failure(200, Some("-1"), "登录地址与钱包地址不一致"));
+        assert!(matches!(err, ApiError::Validation(_)), "{}", err);
+        let err = ApiError::classify(api_failure(200, Some("-1"), "登录过于频繁"));
+        assert!(matches!(err, ApiError::RateLimited(_)), "{}", err);
+    }
+
+    #[test]
+    fn envelope_accepts_numeric_string_and_null_codes() {
+        let numeric: ApiEnvelope<String> = serde_json::from_str(r#"{"code":0,"msg":"success","data":"x"}"#).unwrap();
+        assert!(numeric.is_success());
+        assert_eq!(numeric.data.as_deref(), Some("x"));
+
+        let failed: ApiEnvelope<CreateTokenData> =
+            serde_json::from_str(r#"{"code":"-1","msg":"name is required","data":null}"#).unwrap();
+        assert!(!failed.is_success());
+        assert_eq!(failed.code.as_deref(), Some("-1"));
+        assert!(failed.data.is_none());
+
+        let bare: ApiEnvelope<String> = serde_json::from_str(r#"{"code":null}"#).unwrap();
+        assert!(bare.is_success());
+        assert!(bare.msg.is_none());
+    }
+
+    #[tokio::test]
+    async fn full_create_flow_against_mock() {
+        let mock = MockFourMeme::start().await;
+        let dir = tempfile::tempdir().unwrap();
+        let mut api = client(&mock, &dir);
+
+        api.ensure_session().await.unwrap();
+        let img_url = api.upload_image(&ImageSource::Path(crate::config::CREATE_TOKEN_IMAGE_PATH.into())).await.unwrap();
+        assert_eq!(img_url, MOCK_IMAGE_URL);
+
+        let data = api.create_token(&params(), &default_raised_token()).await.unwrap();
+        assert_eq!(data.bamount, "0.2");
+        assert!(data.create_arg.starts_with("0x"));
+
+        let state = mock.state();
+        assert_eq!(state.logins.len(), 1);
+        assert_eq!(state.uploads.len(), 1);
+        let request = &state.create_requests[0];
+        assert_eq!(request["shortName"], "MOCK");
+        assert_eq!(request["preSale"], "0.2");
+        assert_eq!(request["raisedToken"]["symbol"], "BNB");
+    }
+
+    #[tokio::test]
+    async fn login_signs_the_sign_in_message() {
+        let mock = MockFourMeme::start().await;
+        let mut api = FourMemeApiClient::new().with_base_url(&mock.base_url);
+        let wallet = wallet();
+
+        let nonce = api.get_nonce(&format!("{:?}", wallet.address())).await.unwrap();
+        api.login(&wallet, &nonce).await.unwrap();
+
+        let login = mock.state().logins[0].clone();
+        assert_eq!(login.nonce, nonce);
+        assert_eq!(login.address, format!("{:?}", wallet.address()));
+        let signature = Signature::from_str(login.signature.trim_start_matches("0x")).unwrap();
+        let message = format!("You are sign in Meme {}", nonce);
+        assert_eq!(signature.recover(message.as_str()).unwrap(), wallet.address());
+        assert!(signature.verify("You are sign in Meme", wallet.address()).is_err());
+    }
+
+    #[tokio::test]
+    async fn cached_session_is_reused_and_refreshed_on_auth_error() {
+        let mock = MockFourMeme::start().await;
+        let dir = tempfile::tempdir().unwrap();
+
+        client(&mock, &dir).create_token(&params(), &default_raised_token()).await.unwrap();
+        // 新客户端读取磁盘缓存，不再登录
+        let mut api = client(&mock, &dir);
+        api.create_token(&params(), &default_raised_token()).await.unwrap();
+        assert_eq!(mock.state().logins.len(), 1);
+
+        // 服务端使会话失效后自动重新登录并重试
+        mock.expire_sessions();
+        api.create_token(&params(), &default_raised_token()).await.unwrap();
+        assert_eq!(mock.state().logins.len(), 2);
+        assert_eq!(mock.state().create_requests.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn public_config_lists_raised_tokens() {
+        let mock = MockFourMeme::start().await;
+        let api = FourMemeApiClient::new().with_base_url(&mock.base_url);
+
+        let tokens = api.get_raised_tokens().await.unwrap();
+        let symbols: Vec<&str> = tokens.iter().map(|t| t.symbol.as_str()).collect();
+        assert_eq!(symbols, ["BNB", "CAKE"]);
+        assert!(tokens[0].is_native());
+        assert!(!tokens[1].is_native());
+        // 数字形式的字段被转成字符串
+        assert_eq!(tokens[1].deploy_cost, "0.005");
+        assert_eq!(tokens[1].reserved_number, 10);
+    }
+
+    #[tokio::test]
+    async fn unparseable_raised_amount_is_rejected_before_create() {
+        let mock = MockFourMeme::start().await;
+        let dir = tempfile::tempdir().unwrap();
+        let mut api = client(&mock, &dir);
+
+        let mut raised_token = default_raised_token();
+        raised_token.total_b_amount = "24.5".to_string();
+        let err = api.create_token(&params(), &raised_token).await.unwrap_err().to_string();
+        assert!(err.contains("totalBAmount") && err.contains("'24.5'"), "{}", err);
+        assert!(mock.state().create_requests.is_empty());
+
+        raised_token.total_b_amount = " 18 ".to_string();
+        assert_eq!(raised_token.raised_amount().unwrap(), 18);
+    }
+
+    #[tokio::test]
+    async fn rejected_request_keeps_raw_body() {
+        let mock = MockFourMeme::start().await;
+        let dir = tempfile::tempdir().unwrap();
+        let mut api = client(&mock, &dir);
+
+        let mut invalid = params();
+        invalid.name = String::new();
+        match api.create_token(&invalid, &default_raised_token()).await {
+            Err(ApiError::Validation(failure)) => {
+                assert_eq!(failure.code.as_deref(), Some("-1"));
+                assert_eq!(failure.msg, "name is required");
+                assert!(failure.body.contains("\"data\":null"));
+            }
+            other => panic!("expected validation error, got {:?}", other.map(|d| d.token_id)),
+        }
+    }
+
+    // 返回固定原始HTTP响应的一次性服务，响应头和响应体由测试给出
+    async fn raw_http_server(head: String, body_len: usize) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = socket.read(&mut request).await;
+            socket.write_all(head.as_bytes()).await.unwrap();
+            // 客户端提前断开时写入失败，忽略
+            for _ in 0..body_len / 1024 {
+                if socket.write_all(&[0u8; 1024]).await.is_err() {
+                    return;
+                }
+            }
+        });
+        format!("http://{}/image.png", addr)
+    }
+
+    #[tokio::test]
+    async fn image_download_stops_at_size_limit() {
+        let client = reqwest::Client::new();
+
+        // Content-Length超限：不读取响应体
+        let url = raw_http_server("HTTP/1.1 200 OK\r\nContent-Length: 1000000000\r\n\r\n".to_string(), 0).await;
+        let response = client.get(&url).send().await.unwrap();
+        let err = read_limited(response, &url, 4096).await.unwrap_err();
+        assert!(err.to_string().contains("4096字节上限"), "{}", err);
+
+        // 没有Content-Length：读到超过上限为止
+        let url = raw_http_server("HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n".to_string(), 64 * 1024).await;
+        let response = client.get(&url).send().await.unwrap();
+        let err = read_limited(response, &url, 4096).await.unwrap_err();
+        assert!(err.to_string().contains("4096字节上限"), "{}", err);
+
+        // 未超限时完整读取
+        let url = raw_http_server("HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n".to_string(), 2048).await;
+        let response = client.get(&url).send().await.unwrap();
+        assert_eq!(read_limited(response, &url, 4096).await.unwrap().len(), 2048);
+    }
+}
@@ -0,0 +1,450 @@
+// K线（OHLCV）构建
+// 扫描指定代币在TokenManager2上的TokenPurchase/TokenSale事件，
+// 以事件中的price为成交价，按1s/1m/5m/1h聚合成带成交量和成交笔数的K线。
+// 原始成交和K线都保存在 data/candles/<代币地址>/ 下，增量索引。
+// CANDLES模式用它盯盘（最新K线、动量和成交量）；卖出流程目前不读取K线，仍按单次报价执行。
+
+use ethers::{
+    prelude::*,
+    types::{Address, U256},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use crate::history;
+use crate::journal::TradeSide;
+use crate::contracts::{TokenPurchaseFilter, TokenSaleFilter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CandleInterval {
+    OneSecond,
+    OneMinute,
+    FiveMinutes,
+    OneHour,
+}
+
+impl CandleInterval {
+    pub const ALL: [CandleInterval; 4] = [
+        CandleInterval::OneSecond,
+        CandleInterval::OneMinute,
+        CandleInterval::FiveMinutes,
+        CandleInterval::OneHour,
+    ];
+
+    pub fn seconds(&self) -> u64 {
+        match self {
+            CandleInterval::OneSecond => 1,
+            CandleInterval::OneMinute => 60,
+            CandleInterval::FiveMinutes => 300,
+            CandleInterval::OneHour => 3600,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CandleInterval::OneSecond => "1s",
+            CandleInterval::OneMinute => "1m",
+            CandleInterval::FiveMinutes => "5m",
+            CandleInterval::OneHour => "1h",
+        }
+    }
+}
+
+impl std::str::FromStr for CandleInterval {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        CandleInterval::ALL
+            .into_iter()
+            .find(|interval| interval.label() == s)
+            .ok_or_else(|| format!("不支持的K线周期: {} (可选 1s/1m/5m/1h)", s))
+    }
+}
+
+// 曲线上的单笔成交
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurveTrade {
+    pub block_number: u64,
+    pub log_index: u64,
+    pub timestamp: u64,
+    pub side: TradeSide,
+    pub price: U256,  // 事件中的成交价
+    pub amount: U256, // 代币数量
+    pub cost: U256,   // BNB数量
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candle {
+    pub open_time: u64, // 周期起始时间（秒）
+    pub open: U256,
+    pub high: U256,
+    pub low: U256,
+    pub close: U256,
+    pub volume_token: U256,
+    pub volume_bnb: U256,
+    pub trades: u32,
+    pub buys: u32,
+    pub sells: u32,
+}
+
+// 按周期聚合成交，成交需按时间排序；没有成交的周期不生成K线
+pub fn build_candles(trades: &[CurveTrade], interval: CandleInterval) -> Vec<Candle> {
+    let mut candles: BTreeMap<u64, Candle> = BTreeMap::new();
+    let step = interval.seconds();
+
+    for trade in trades {
+        let open_time = trade.timestamp - trade.timestamp % step;
+        let candle = candles.entry(open_time).or_insert_with(|| Candle {
+            open_time,
+            open: trade.price,
+            high: trade.price,
+            low: trade.price,
+            close: trade.price,
+            volume_token: U256::zero(),
+            volume_bnb: U256::zero(),
+            trades: 0,
+            buys: 0,
+            sells: 0,
+        });
+
+        candle.high = candle.high.max(trade.price);
+        candle.low = candle.low.min(trade.price);
+        candle.close = trade.price;
+        candle.volume_token += trade.amount;
+        candle.volume_bnb += trade.cost;
+        candle.trades += 1;
+        match trade.side {
+            TradeSide::Buy => candle.buys += 1,
+            TradeSide::Sell => candle.sells += 1,
+        }
+    }
+
+    candles.into_values().collect()
+}
+
+// 最近若干根K线的动量和成交量概况
+#[derive(Debug, Clone, Serialize)]
+pub struct Momentum {
+    pub candles: usize,
+    pub change_pct: f64, // 区间涨跌幅（首根开盘到末根收盘）
+    pub volume_bnb: U256,
+    pub trades: u32,
+    pub buy_ratio: f64, // 买入笔数占比
+}
+
+fn to_f64(value: U256) -> f64 {
+    value.to_string().parse().unwrap_or(0.0)
+}
+
+pub fn momentum(candles: &[Candle], lookback: usize) -> Option<Momentum> {
+    let window = &candles[candles.len().saturating_sub(lookback)..];
+    let first = window.first()?;
+    let last = window.last()?;
+
+    let open = to_f64(first.open);
+    let change_pct = if open > 0.0 {
+        (to_f64(last.close) - open) / open * 100.0
+    } else {
+        0.0
+    };
+    let trades: u32 = window.iter().map(|c| c.trades).sum();
+    let buys: u32 = window.iter().map(|c| c.buys).sum();
+
+    Some(Momentum {
+        candles: window.len(),
+        change_pct,
+        volume_bnb: window.iter().fold(U256::zero(), |acc, c| acc + c.volume_bnb),
+        trades,
+        buy_ratio: if trades > 0 { buys as f64 / trades as f64 } else { 0.0 },
+    })
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexState {
+    last_block: u64,
+}
+
+// 单个代币的K线存储目录
+pub struct CandleStore {
+    dir: PathBuf,
+}
+
+impl CandleStore {
+    pub fn new(root: impl AsRef<Path>, token: Address) -> Self {
+        Self {
+            dir: root.as_ref().join(format!("{:?}", token)),
+        }
+    }
+
+    fn state_path(&self) -> PathBuf {
+        self.dir.join("state.json")
+    }
+
+    fn trades_path(&self) -> PathBuf {
+        self.dir.join("trades.jsonl")
+    }
+
+    fn candles_path(&self, interval: CandleInterval) -> PathBuf {
+        self.dir.join(format!("{}.json", interval.label()))
+    }
+
+    // 已索引到的区块，从未索引过时返回None
+    pub fn last_block(&self) -> std::result::Result<Option<u64>, Box<dyn std::error::Error>> {
+        let path = self.state_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let state: IndexState = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        Ok(Some(state.last_block))
+    }
+
+    fn save_last_block(&self, last_block: u64) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.state_path(), serde_json::to_string(&IndexState { last_block })?)?;
+        Ok(())
+    }
+
+    // 读取全部成交，按时间排序并去重（中断重跑可能重复写入同一页）
+    pub fn load_trades(&self) -> std::result::Result<Vec<CurveTrade>, Box<dyn std::error::Error>> {
+        let path = self.trades_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let mut trades: Vec<CurveTrade> = Vec::new();
+        for line in BufReader::new(std::fs::File::open(path)?).lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                trades.push(serde_json::from_str(&line)?);
+            }
+        }
+        trades.sort_by_key(|t| (t.block_number, t.log_index));
+        trades.dedup_by_key(|t| (t.block_number, t.log_index));
+        Ok(trades)
+    }
+
+    fn append_trades(&self, trades: &[CurveTrade]) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(&self.dir)?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.trades_path())?;
+        for trade in trades {
+            writeln!(file, "{}", serde_json::to_string(trade)?)?;
+        }
+        Ok(())
+    }
+
+    // 读取某个周期的K线
+    pub fn load_candles(&self, interval: CandleInterval) -> std::result::Result<Vec<Candle>, Box<dyn std::error::Error>> {
+        let path = self.candles_path(interval);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    // 根据全部成交重建所有周期的K线
+    pub fn rebuild_candles(&self) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let trades = self.load_trades()?;
+        std::fs::create_dir_all(&self.dir)?;
+        for interval in CandleInterval::ALL {
+            let candles = build_candles(&trades, interval);
+            std::fs::write(self.candles_path(interval), serde_json::to_string(&candles)?)?;
+        }
+        Ok(())
+    }
+}
+
+// 增量索引代币成交并更新K线，返回新增成交数
+pub async fn index_token<M: Middleware + 'static>(
+    client: &M,
+    token_manager_address: Address,
+    token: Address,
+    from_block: Option<u64>,
+    block_chunk: u64,
+    store: &CandleStore,
+) -> std::result::Result<usize, Box<dyn std::error::Error>> {
+    let start_block = match (store.last_block()?, from_block) {
+        (Some(last), _) => last + 1,
+        (None, Some(block)) => block,
+        (None, None) => return Err("首次索引需要指定起始区块".into()),
+    };
+    let latest_block = client.get_block_number().await?.as_u64();
+    if start_block > latest_block {
+        return Ok(0);
+    }
+
+    let topics = [TokenPurchaseFilter::signature(), TokenSaleFilter::signature()];
+    let mut block_times: HashMap<u64, u64> = HashMap::new();
+    let mut total = 0;
+    let chunk = block_chunk.max(1);
+
+    let mut page_start = start_block;
+    while page_start <= latest_block {
+        let page_end = (page_start + chunk - 1).min(latest_block);
+        let logs = history::get_logs_split(client, &[token_manager_address], &topics, page_start, page_end).await?;
+
+        let mut trades = Vec::new();
+        for log in &logs {
+            let raw_log = ethers::abi::RawLog::from(log.clone());
+            let parsed = if let Ok(event) = <TokenPurchaseFilter as EthEvent>::decode_log(&raw_log) {
+                (event.token == token).then_some((TradeSide::Buy, event.price, event.amount, event.cost))
+            } else if let Ok(event) = <TokenSaleFilter as EthEvent>::decode_log(&raw_log) {
+                (event.token == token).then_some((TradeSide::Sell, event.price, event.amount, event.cost))
+            } else {
+                None
+            };
+            let (Some((side, price, amount, cost)), Some(block_number)) = (parsed, log.block_number) else {
+                continue;
+            };
+            let block_number = block_number.as_u64();
+
+            let timestamp = match block_times.get(&block_number) {
+                Some(ts) => *ts,
+                None => {
+                    let ts = client
+                        .get_block(block_number)
+                        .await?
+                        .map(|block| block.timestamp.as_u64())
+                        .unwrap_or_default();
+                    block_times.insert(block_number, ts);
+                    ts
+                }
+            };
+
+            trades.push(CurveTrade {
+                block_number,
+                log_index: log.log_index.map(|i| i.as_u64()).unwrap_or_default(),
+                timestamp,
+                side,
+                price,
+                amount,
+                cost,
+            });
+        }
+
+        // 先写成交再推进区块进度，中断后不会漏掉成交
+        trades.sort_by_key(|t| (t.block_number, t.log_index));
+        store.append_trades(&trades)?;
+        store.save_last_block(page_end)?;
+        total += trades.len();
+        logln!("  已索引区块 {}-{}: 成交 {} 笔", page_start, page_end, trades.len());
+
+        page_start = page_end + 1;
+    }
+
+    store.rebuild_candles()?;
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(block: u64, timestamp: u64, side: TradeSide, price: u64, amount: u64, cost: u64) -> CurveTrade {
+        CurveTrade {
+            block_number: block,
+            log_index: 0,
+            timestamp,
+            side,
+            price: U256::from(price),
+            amount: U256::from(amount),
+            cost: U256::from(cost),
+        }
+    }
+
+    // 1m周期：59秒属于第一根，60秒开始第二根，125秒跳过一个没有成交的周期
+    fn trades() -> Vec<CurveTrade> {
+        vec![
+            trade(1, 0, TradeSide::Buy, 100, 10, 1000),
+            trade(2, 30, TradeSide::Buy, 130, 5, 650),
+            trade(3, 45, TradeSide::Sell, 90, 4, 360),
+            trade(4, 59, TradeSide::Buy, 110, 2, 220),
+            trade(5, 60, TradeSide::Sell, 105, 3, 315),
+            trade(6, 125, TradeSide::Buy, 120, 1, 120),
+        ]
+    }
+
+    #[test]
+    fn buckets_by_interval_start() {
+        let candles = build_candles(&trades(), CandleInterval::OneMinute);
+        assert_eq!(candles.iter().map(|c| c.open_time).collect::<Vec<_>>(), vec![0, 60, 120]);
+
+        let five_minutes = build_candles(&trades(), CandleInterval::FiveMinutes);
+        assert_eq!(five_minutes.len(), 1);
+        assert_eq!(five_minutes[0].trades, 6);
+
+        let one_second = build_candles(&trades(), CandleInterval::OneSecond);
+        assert_eq!(one_second.len(), 6);
+    }
+
+    #[test]
+    fn open_close_follow_trade_order_within_bucket() {
+        let first = &build_candles(&trades(), CandleInterval::OneMinute)[0];
+        // 开盘为第一笔，收盘为最后一笔，高低点在中间
+        assert_eq!(first.open, U256::from(100));
+        assert_eq!(first.close, U256::from(110));
+        assert_eq!(first.high, U256::from(130));
+        assert_eq!(first.low, U256::from(90));
+    }
+
+    #[test]
+    fn sums_volume_and_counts_trades() {
+        let candles = build_candles(&trades(), CandleInterval::OneMinute);
+        let first = &candles[0];
+        assert_eq!(first.volume_token, U256::from(21));
+        assert_eq!(first.volume_bnb, U256::from(2230));
+        assert_eq!((first.trades, first.buys, first.sells), (4, 3, 1));
+
+        let second = &candles[1];
+        assert_eq!((second.trades, second.buys, second.sells), (1, 0, 1));
+        assert_eq!(second.open, second.close);
+    }
+
+    #[test]
+    fn momentum_uses_last_candles_only() {
+        let candles = build_candles(&trades(), CandleInterval::OneMinute);
+
+        let last_two = momentum(&candles, 2).unwrap();
+        assert_eq!(last_two.candles, 2);
+        // 105 -> 120
+        assert!((last_two.change_pct - 100.0 * 15.0 / 105.0).abs() < 1e-9);
+        assert_eq!(last_two.volume_bnb, U256::from(435));
+        assert_eq!(last_two.trades, 2);
+        assert!((last_two.buy_ratio - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn momentum_lookback_longer_than_series_uses_all() {
+        let candles = build_candles(&trades(), CandleInterval::OneMinute);
+
+        let all = momentum(&candles, 100).unwrap();
+        assert_eq!(all.candles, 3);
+        assert!((all.change_pct - 20.0).abs() < 1e-9);
+        assert_eq!(all.volume_bnb, U256::from(2665));
+        assert_eq!(all.trades, 6);
+        assert!((all.buy_ratio - 4.0 / 6.0).abs() < 1e-9);
+
+        assert!(momentum(&candles, 0).is_none());
+        assert!(momentum(&[], 12).is_none());
+    }
+
+    #[test]
+    fn store_dedups_reindexed_trades() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CandleStore::new(dir.path(), Address::repeat_byte(0x44));
+        assert_eq!(store.last_block().unwrap(), None);
+
+        // 中断后重跑同一页会重复写入
+        store.append_trades(&trades()[..4]).unwrap();
+        store.append_trades(&trades()[2..]).unwrap();
+        store.save_last_block(6).unwrap();
+        assert_eq!(store.load_trades().unwrap().len(), 6);
+        assert_eq!(store.last_block().unwrap(), Some(6));
+
+        store.rebuild_candles().unwrap();
+        assert_eq!(store.load_candles(CandleInterval::OneMinute).unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,402 @@
+// 代币创建断点
+// 创建流程的每个阶段完成后写入 data/creations/<钱包>_<名称符号哈希>.json：
+// 登录 -> 上传图片 -> 获得API签名 -> 交易已发送 -> 交易已确认。
+// 进程中断后重新运行会从最后一个阶段继续：复用已上传的图片和未过期的签名，
+// 已发送的交易先查询结果，并通过链上TokenCreate事件确认代币是否已经创建，避免重复创建。
+
+use chrono::Utc;
+use ethers::{
+    prelude::*,
+    types::{Address, H256, U256},
+    utils::keccak256,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::api::{CreateTokenData, CreateTokenParams};
+use crate::history::get_logs_split;
+use crate::contracts::TokenCreateFilter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CreationStage {
+    Started,
+    LoggedIn,
+    ImageUploaded,
+    Signed,
+    TxSent,
+    Confirmed,
+}
+
+impl fmt::Display for CreationStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            CreationStage::Started => "已开始",
+            CreationStage::LoggedIn => "已登录",
+            CreationStage::ImageUploaded => "图片已上传",
+            CreationStage::Signed => "已获得API签名",
+            CreationStage::TxSent => "交易已发送",
+            CreationStage::Confirmed => "交易已确认",
+        };
+        f.write_str(label)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreationCheckpoint {
+    pub wallet: Address,
+    pub name: String,
+    pub symbol: String,
+    pub stage: CreationStage,
+    pub img_url: Option<String>,
+    pub create_data: Option<CreateTokenData>,
+    pub signed_at: Option<i64>,          // 获得签名的时间（秒）
+    pub signed_block: Option<u64>,       // 获得签名时的区块，查找TokenCreate事件的起点
+    pub clock_offset_ms: Option<i64>,    // 服务器时间 - 本地时间
+    pub tx_hash: Option<H256>,
+    pub tx_nonce: Option<U256>,
+    pub token_address: Option<Address>,
+    pub block_number: Option<u64>,
+    pub updated_at: i64,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl CreationCheckpoint {
+    // 同一钱包下按名称+符号区分不同的创建
+    pub fn path_for(dir: impl AsRef<Path>, wallet: Address, params: &CreateTokenParams) -> PathBuf {
+        let digest = keccak256(format!("{}\n{}", params.name, params.short_name));
+        dir.as_ref()
+            .join(format!("{:?}_{}.json", wallet, hex::encode(&digest[..8])))
+    }
+
+    pub fn load_or_new(
+        dir: impl AsRef<Path>,
+        wallet: Address,
+        params: &CreateTokenParams,
+    ) -> std::result::Result<Self, Box<dyn std::error::Error>> {
+        let path = Self::path_for(dir, wallet, params);
+        let mut checkpoint = if path.exists() {
+            let content = std::fs::read_to_string(&path)?;
+            serde_json::from_str::<CreationCheckpoint>(&content)
+                .map_err(|e| format!("创建断点文件损坏 {}: {}", path.display(), e))?
+        } else {
+            CreationCheckpoint {
+                wallet,
+                name: params.name.clone(),
+                symbol: params.short_name.clone(),
+                stage: CreationStage::Started,
+                img_url: None,
+                create_data: None,
+                signed_at: None,
+                signed_block: None,
+                clock_offset_ms: None,
+                tx_hash: None,
+                tx_nonce: None,
+                token_address: None,
+                block_number: None,
+                updated_at: Utc::now().timestamp(),
+                path: PathBuf::new(),
+            }
+        };
+        checkpoint.path = path;
+        Ok(checkpoint)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // 先写临时文件再重命名，避免中断时留下半个文件
+    fn save(&mut self) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.updated_at = Utc::now().timestamp();
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    pub fn logged_in(&mut self) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.stage = self.stage.max(CreationStage::LoggedIn);
+        self.save()
+    }
+
+    pub fn image_uploaded(&mut self, img_url: &str) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.img_url = Some(img_url.to_string());
+        self.stage = CreationStage::ImageUploaded;
+        self.save()
+    }
+
+    pub fn signed(
+        &mut self,
+        create_data: &CreateTokenData,
+        signed_block: u64,
+        clock_offset_ms: i64,
+    ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.create_data = Some(create_data.clone());
+        self.signed_at = Some(Utc::now().timestamp());
+        self.signed_block = Some(signed_block);
+        self.clock_offset_ms = Some(clock_offset_ms);
+        self.stage = CreationStage::Signed;
+        self.save()
+    }
+
+    pub fn tx_sent(&mut self, tx_hash: H256, tx_nonce: U256) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.tx_hash = Some(tx_hash);
+        self.tx_nonce = Some(tx_nonce);
+        self.stage = CreationStage::TxSent;
+        self.save()
+    }
+
+    pub fn confirmed(&mut self, tx_hash: H256, block_number: Option<u64>) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.tx_hash = Some(tx_hash);
+        self.block_number = block_number;
+        self.stage = CreationStage::Confirmed;
+        self.save()
+    }
+
+    pub fn token_found(&mut self, token_address: Address) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.token_address = Some(token_address);
+        self.save()
+    }
+
+    // 上次的交易已回滚或其nonce已被其他交易占用，回到已签名阶段，下次发送使用新的nonce
+    pub fn clear_tx(&mut self) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.tx_hash = None;
+        self.tx_nonce = None;
+        if self.stage > CreationStage::Signed {
+            self.stage = CreationStage::Signed;
+        }
+        self.save()
+    }
+
+    // 节点上查不到上次的交易但其nonce尚未使用：回到已签名阶段并保留nonce，
+    // 重发时沿用该nonce，新交易会替换可能仍在内存池中的旧交易，而不是与之并存
+    pub fn tx_dropped(&mut self) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.tx_hash = None;
+        if self.stage > CreationStage::Signed {
+            self.stage = CreationStage::Signed;
+        }
+        self.save()
+    }
+
+    // 签名失效，需要重新调用API（保留已上传的图片；未使用的nonce同样保留，理由同tx_dropped）
+    pub fn clear_signature(&mut self) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.create_data = None;
+        self.signed_at = None;
+        self.signed_block = None;
+        self.clock_offset_ms = None;
+        self.tx_hash = None;
+        self.stage = if self.img_url.is_some() {
+            CreationStage::ImageUploaded
+        } else {
+            CreationStage::LoggedIn
+        };
+        self.save()
+    }
+
+    // 未超过有效期的签名可以直接复用
+    pub fn reusable_signature(&self, ttl_secs: i64) -> Option<&CreateTokenData> {
+        let signed_at = self.signed_at?;
+        if Utc::now().timestamp() - signed_at > ttl_secs {
+            return None;
+        }
+        self.create_data.as_ref()
+    }
+}
+
+// 上次发送的创建交易的状态
+// NotFound只表示当前节点查不到该交易，不代表已被丢弃（可能已被其他节点打包或仍在别处的内存池），
+// 是否可以重发要看nonce是否已被使用（见nonce_consumed）
+pub enum SentTx {
+    Mined(Box<TransactionReceipt>),
+    Pending,
+    NotFound,
+}
+
+pub async fn sent_tx_status<M: Middleware + 'static>(
+    client: &M,
+    tx_hash: H256,
+) -> std::result::Result<SentTx, Box<dyn std::error::Error>> {
+    if let Some(receipt) = client.get_transaction_receipt(tx_hash).await? {
+        return Ok(SentTx::Mined(Box::new(receipt)));
+    }
+    Ok(match client.get_transaction(tx_hash).await? {
+        Some(_) => SentTx::Pending,
+        None => SentTx::NotFound,
+    })
+}
+
+// 账户已确认的交易数（latest nonce）大于发送时的nonce，说明该nonce已被某笔交易使用
+pub fn nonce_consumed(latest_nonce: U256, tx_nonce: U256) -> bool {
+    latest_nonce > tx_nonce
+}
+
+// 在链上查找该钱包用这次API签名（requestId = tokenId）创建的代币，返回代币地址和交易哈希
+pub async fn find_created_token<M: Middleware + 'static>(
+    client: &M,
+    token_manager: Address,
+    creator: Address,
+    request_id: u64,
+    from_block: u64,
+) -> std::result::Result<Option<(Address, H256)>, Box<dyn std::error::Error>> {
+    let latest = client.get_block_number().await?.as_u64();
+    if from_block > latest {
+        return Ok(None);
+    }
+    let logs = get_logs_split(client, &[token_manager], &[TokenCreateFilter::signature()], from_block, latest).await?;
+
+    for log in logs {
+        if let Some(token) = created_token_from_log(&log, token_manager, creator, request_id) {
+            if let Some(tx_hash) = log.transaction_hash {
+                return Ok(Some((token, tx_hash)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+// 解析TokenManager发出的TokenCreate事件，creator和requestId都匹配时返回新代币地址
+pub fn created_token_from_log(log: &Log, token_manager: Address, creator: Address, request_id: u64) -> Option<Address> {
+    if log.address != token_manager || log.topics.first() != Some(&TokenCreateFilter::signature()) {
+        return None;
+    }
+    let raw = ethers::abi::RawLog {
+        topics: log.topics.clone(),
+        data: log.data.to_vec(),
+    };
+    let event = <TokenCreateFilter as EthEvent>::decode_log(&raw).ok()?;
+    (event.creator == creator && event.request_id == U256::from(request_id)).then_some(event.token)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::metadata::TokenLabel;
+
+    pub(crate) fn params() -> CreateTokenParams {
+        CreateTokenParams {
+            name: "狐链fox".to_string(),
+            short_name: "FOX".to_string(),
+            desc: "a fox on chain".to_string(),
+            img_url: String::new(),
+            launch_time: None,
+            label: TokenLabel::Meme,
+            web_url: None,
+            twitter_url: None,
+            telegram_url: None,
+            pre_sale: "0".to_string(),
+            raise_symbol: "BNB".to_string(),
+        }
+    }
+
+    pub(crate) fn create_data() -> CreateTokenData {
+        serde_json::from_value(serde_json::json!({
+            "tokenId": 100001,
+            "totalAmount": "1000000000",
+            "saleAmount": "800000000",
+            "template": 0,
+            "launchTime": 0,
+            "serverTime": 0,
+            "createArg": "0xab",
+            "signature": "0xcd",
+            "bamount": "0",
+            "tamount": "0",
+        }))
+        .unwrap()
+    }
+
+    // TokenManager发出的TokenCreate事件日志
+    pub(crate) fn token_create_log(token_manager: Address, creator: Address, token: Address, request_id: u64) -> Log {
+        let data = ethers::abi::encode(&[
+            ethers::abi::Token::Address(creator),
+            ethers::abi::Token::Address(token),
+            ethers::abi::Token::Uint(U256::from(request_id)),
+            ethers::abi::Token::String("狐链fox".to_string()),
+            ethers::abi::Token::String("FOX".to_string()),
+            ethers::abi::Token::Uint(U256::exp10(27)),
+            ethers::abi::Token::Uint(U256::zero()),
+            ethers::abi::Token::Uint(U256::zero()),
+        ]);
+        Log {
+            address: token_manager,
+            topics: vec![TokenCreateFilter::signature()],
+            data: data.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stages_survive_reload_and_rollback() {
+        let dir = tempfile::tempdir().unwrap();
+        let wallet = Address::repeat_byte(0x11);
+
+        let mut checkpoint = CreationCheckpoint::load_or_new(dir.path(), wallet, &params()).unwrap();
+        checkpoint.logged_in().unwrap();
+        checkpoint.image_uploaded("https://static.four.meme/fox.png").unwrap();
+        checkpoint.signed(&create_data(), 100, 42).unwrap();
+        checkpoint.tx_sent(H256::repeat_byte(0x22), U256::from(7)).unwrap();
+
+        let mut reloaded = CreationCheckpoint::load_or_new(dir.path(), wallet, &params()).unwrap();
+        assert_eq!(reloaded.stage, CreationStage::TxSent);
+        assert_eq!(reloaded.tx_nonce, Some(U256::from(7)));
+        assert_eq!(reloaded.reusable_signature(600).map(|d| d.token_id), Some(100001));
+        assert!(reloaded.reusable_signature(-1).is_none());
+
+        // 节点查不到交易且nonce未使用：回到已签名阶段，保留nonce用于替换重发
+        reloaded.tx_dropped().unwrap();
+        let mut reloaded = CreationCheckpoint::load_or_new(dir.path(), wallet, &params()).unwrap();
+        assert_eq!(reloaded.stage, CreationStage::Signed);
+        assert_eq!(reloaded.tx_hash, None);
+        assert_eq!(reloaded.tx_nonce, Some(U256::from(7)));
+
+        // 签名失效：保留已上传的图片和未使用的nonce
+        reloaded.clear_signature().unwrap();
+        assert_eq!(reloaded.stage, CreationStage::ImageUploaded);
+        assert!(reloaded.img_url.is_some() && reloaded.create_data.is_none());
+        assert_eq!(reloaded.tx_nonce, Some(U256::from(7)));
+
+        // 交易回滚或nonce已被占用：丢弃nonce，下次用新的nonce发送
+        reloaded.signed(&create_data(), 100, 42).unwrap();
+        reloaded.tx_sent(H256::repeat_byte(0x33), U256::from(7)).unwrap();
+        reloaded.clear_tx().unwrap();
+        assert_eq!(reloaded.stage, CreationStage::Signed);
+        assert_eq!(reloaded.tx_nonce, None);
+
+        // 不同的代币使用不同的断点文件
+        let mut other = params();
+        other.short_name = "WOLF".to_string();
+        let fresh = CreationCheckpoint::load_or_new(dir.path(), wallet, &other).unwrap();
+        assert_eq!(fresh.stage, CreationStage::Started);
+    }
+
+    #[test]
+    fn nonce_consumed_only_after_it_is_mined() {
+        // 发送时nonce为7：latest为7表示还没有nonce为7的交易上链
+        assert!(!nonce_consumed(U256::from(7), U256::from(7)));
+        assert!(!nonce_consumed(U256::from(6), U256::from(7)));
+        assert!(nonce_consumed(U256::from(8), U256::from(7)));
+    }
+
+    #[test]
+    fn token_create_log_must_match_manager_creator_and_request_id() {
+        let manager = Address::repeat_byte(0x5c);
+        let creator = Address::repeat_byte(0x11);
+        let token = Address::repeat_byte(0x44);
+        let log = token_create_log(manager, creator, token, 100001);
+        assert_eq!(created_token_from_log(&log, manager, creator, 100001), Some(token));
+
+        assert_eq!(created_token_from_log(&log, Address::repeat_byte(0x5d), creator, 100001), None);
+        assert_eq!(created_token_from_log(&log, manager, Address::repeat_byte(0x12), 100001), None);
+        assert_eq!(created_token_from_log(&log, manager, creator, 100002), None);
+
+        // 同一合约的其他事件不被当成TokenCreate
+        let mut other = log.clone();
+        other.topics = vec![H256::repeat_byte(0xdd)];
+        assert_eq!(created_token_from_log(&other, manager, creator, 100001), None);
+    }
+}
@@ -0,0 +1,160 @@
+// 命令行参数
+// 未指定子命令时沿用环境变量OPERATION_MODE选择模式（默认SELL），
+// 兼容之前 `OPERATION_MODE=CREATE_AND_BUY cargo run` 的用法。
+
+use clap::{Args, Parser, Subcommand};
+use fourmeme_auto_sell::logging::LogFormat;
+use fourmeme_auto_sell::output::OutputFormat;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+#[command(name = "fourmeme_auto_sell", about = "Four.meme 自动交易工具")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// 录制RPC和Four.meme API流量（已脱敏），未指定文件时写入data/traffic/<时间>.jsonl
+    #[arg(long, value_name = "FILE", global = true, num_args = 0..=1, conflicts_with = "replay")]
+    pub record: Option<Option<PathBuf>>,
+    /// 从录制文件回放RPC和API响应，不访问网络
+    #[arg(long, value_name = "FILE", global = true)]
+    pub replay: Option<PathBuf>,
+    /// 输出格式：text（默认）或json（结果逐行输出JSON到stdout，日志改写到stderr）
+    #[arg(long, value_name = "FORMAT", global = true, default_value_t = OutputFormat::Text)]
+    pub output: OutputFormat,
+    /// 日志级别，如info、debug或"warn,fourmeme_auto_sell::api=debug"，默认读取环境变量LOG_LEVEL
+    #[arg(long, value_name = "LEVEL", global = true)]
+    pub log_level: Option<String>,
+    /// 日志格式：text或json，日志始终写到stderr，默认读取环境变量LOG_FORMAT
+    #[arg(long, value_name = "FORMAT", global = true)]
+    pub log_format: Option<LogFormat>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// 卖出现有代币（默认模式）
+    Sell(SellArgs),
+    /// 通过Four.meme API创建代币并预购
+    Create(CreateArgs),
+    /// 盈亏统计
+    Pnl,
+    /// 导入链上历史成交
+    ImportHistory,
+    /// 构建K线
+    Candles,
+    /// 本地联合曲线报价
+    Quote,
+    /// 通过TokenTradeWithFee路由买卖和管理手续费
+    Proxy(ProxyArgs),
+    /// 部署TokenTradeWithFee路由
+    Router(RouterArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct ProxyArgs {
+    /// 路由合约地址，默认读取环境变量PROXY_ROUTER_ADDRESS或配置常量
+    #[arg(long, value_name = "ADDRESS", global = true)]
+    pub router: Option<String>,
+    #[command(subcommand)]
+    pub action: ProxyAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ProxyAction {
+    /// 通过路由买入TOKEN_ADDRESS，默认金额为BUY_AMOUNT_BNB
+    Buy {
+        #[arg(value_name = "BNB")]
+        amount: Option<String>,
+    },
+    /// 通过路由卖出TOKEN_ADDRESS的全部持仓
+    Sell,
+    /// 查看路由的owner、手续费率和接收地址
+    Fee,
+    /// 增量索引路由的ProxyTokenPurchase/ProxyTokenSold/FeeSet事件
+    Index {
+        /// 首次索引的起始区块（路由部署区块），之后从上次的位置继续
+        #[arg(long, value_name = "BLOCK")]
+        from_block: Option<u64>,
+    },
+    /// 按天、代币、用户输出收入报告，并与feeRecipient到账对账
+    Report,
+    /// 设置手续费（仅owner）
+    SetFee {
+        /// 手续费率（百分比，0-5）
+        #[arg(value_name = "PERCENT")]
+        rate: u64,
+        /// 手续费接收地址
+        #[arg(value_name = "ADDRESS")]
+        recipient: String,
+    },
+}
+
+#[derive(Debug, Default, Args)]
+pub struct SellArgs {
+    /// sellToken的origin参数
+    #[arg(long)]
+    pub origin: Option<u64>,
+    /// 第三方手续费率（基点，100 = 1%，最高500）
+    #[arg(long, value_name = "BPS")]
+    pub fee_rate_bps: Option<u64>,
+    /// 第三方手续费接收地址
+    #[arg(long, value_name = "ADDRESS")]
+    pub fee_recipient: Option<String>,
+}
+
+#[derive(Debug, Default, Args)]
+pub struct CreateArgs {
+    /// 只预览发行经济参数（初始价格、预购数量、持仓占比、上线所需BNB），不签名也不调用API
+    #[arg(long)]
+    pub preview: bool,
+    /// 按YAML清单批量创建代币，中断后重新运行会从未完成的代币继续
+    #[arg(long, value_name = "FILE")]
+    pub manifest: Option<PathBuf>,
+    /// 募集代币符号（如BNB/CAKE），需为平台配置中已开放的代币
+    #[arg(long, value_name = "SYMBOL")]
+    pub raise_token: Option<String>,
+    /// 定时发布时间：RFC3339时间、毫秒时间戳，或相对时间如 +10m/+2h；创建后倒计时到开盘
+    #[arg(long, value_name = "TIME", conflicts_with = "manifest")]
+    pub launch_at: Option<String>,
+    /// 开盘后第一个区块用该数量的BNB买入
+    #[arg(long, value_name = "BNB", conflicts_with = "manifest")]
+    pub buy_at_launch: Option<String>,
+}
+
+impl Command {
+    // 把OPERATION_MODE映射为子命令，未知值按SELL处理
+    pub fn from_operation_mode(mode: &str) -> Self {
+        match mode {
+            "CREATE_AND_BUY" => Command::Create(CreateArgs::default()),
+            "PNL" => Command::Pnl,
+            "IMPORT_HISTORY" => Command::ImportHistory,
+            "CANDLES" => Command::Candles,
+            "QUOTE" => Command::Quote,
+            _ => Command::Sell(SellArgs::default()),
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct RouterArgs {
+    #[command(subcommand)]
+    pub action: RouterAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum RouterAction {
+    /// 部署路由合约并核对构造参数，部署记录写入data/router_deployments.jsonl
+    Deploy {
+        /// 手续费接收地址
+        #[arg(long, value_name = "ADDRESS")]
+        fee_recipient: String,
+        /// 手续费率（百分比，0-5）
+        #[arg(long, value_name = "PERCENT", default_value_t = 1)]
+        fee_rate: u64,
+        /// TokenManagerHelper3地址，默认使用配置常量
+        #[arg(long, value_name = "ADDRESS")]
+        helper: Option<String>,
+        /// 编译产物路径，不存在时用solc编译fourmeme/1.sol
+        #[arg(long, value_name = "FILE")]
+        artifact: Option<PathBuf>,
+    },
+}
@@ -0,0 +1,114 @@
+// 配置参数 - 所有可配置的参数都在这里
+// 命令行和库中的各个模块共用，部分参数可通过环境变量覆盖（见各参数的注释）。
+
+// 网络配置
+pub const CHAIN_ID: u64 = 56; // BSC链ID
+pub const RPC_URL: &str = "https://neat-practical-arrow.bsc.quiknode.pro/b2f485b14431f07a8e9e25951ad16fb364a0dd3a";
+
+// 钱包配置
+pub const PRIVATE_KEY: &str = "";
+
+// 合约地址配置
+pub const TOKEN_MANAGER_V1_ADDRESS: &str = "0xEC4549caDcE5DA21Df6E6422d448034B5233bFbC"; // TokenManager V1（2024-09-05之前创建的代币）
+pub const TOKEN_MANAGER2_ADDRESS: &str = "0x5c952063c7fc8610FFDB798152D69F0B9550762b"; // TokenManager2 V2
+pub const TOKEN_MANAGER_HELPER_ADDRESS: &str = "0xF251F83e40a78868FcfA3FA4599Dad6494E46034"; // Helper3
+pub const PANCAKE_ROUTER_V2_ADDRESS: &str = "0x10ED43C718714eb63d5aA57B78B54704E256024E"; // PancakeSwap V2 Router，已上线外盘的代币通过它交易
+pub const WBNB_ADDRESS: &str = "0xbb4CdB9CBd36B01bD1cBaEBF2De08d9173bc095c";
+pub const PANCAKE_SWAP_DEADLINE_SECS: i64 = 300; // PancakeSwap交易的deadline
+pub const PANCAKE_V2_FEE_BPS: u64 = 25; // PancakeSwap V2的LP手续费（0.25%）
+pub const PROXY_ROUTER_ADDRESS: &str = ""; // TokenTradeWithFee路由合约（fourmeme/1.sol），可通过--router或环境变量PROXY_ROUTER_ADDRESS覆盖
+pub const ROUTER_ARTIFACT_PATH: &str = "fourmeme/artifacts/TokenTradeWithFee.json"; // 路由编译产物（Hardhat/Foundry artifact或.bin）
+pub const ROUTER_SOURCE_PATH: &str = "fourmeme/1.sol"; // 没有编译产物时用solc编译的源码
+pub const ROUTER_DEPLOYMENTS_PATH: &str = "data/router_deployments.jsonl"; // 路由部署记录
+pub const DEFAULT_TOKEN_ADDRESS: &str = "0xa61619c6569fcc0f8ecdd62854b2e452f3a84444"; // 用于卖出模式的默认代币
+
+// 创建代币配置
+pub const CREATE_TOKEN_NAME: &str = "狐链fox"; // 代币名称
+pub const CREATE_TOKEN_SYMBOL: &str = "狐链fox";
+pub const CREATE_TOKEN_DESC: &str = "BullCoin is a powerful cryptocurrency that embodies the strength of the bull market. Combining blockchain innovation with community engagement, BullCoin offers unique opportunities for investors. Inspired by the bullish spirit, it aims to revolutionize the digital economy while supporting both Bitcoin and Ethereum ecosystems."; // 描述信息
+pub const CREATE_TOKEN_LABEL: &str = "Meme"; // AI/Meme/Defi/Games/Infra/De-Sci/Social/Depin/Charity/Others
+pub const CREATE_TOKEN_WEB_URL: &str = "https://difipay.vercel.app";
+pub const CREATE_TOKEN_TWITTER_URL: &str = "";
+pub const CREATE_TOKEN_TELEGRAM_URL: &str = "";
+pub const CREATE_TOKEN_PRE_SALE: &str = "0.2"; // 创建代币时预购买的数量（以募集代币计），"0"表示不预购买
+pub const CREATE_TOKEN_RAISE_SYMBOL: &str = "BNB"; // 募集代币，可通过--raise-token或环境变量CREATE_TOKEN_RAISE_TOKEN覆盖
+pub const TOKEN_NAME_MAX_CHARS: usize = 32; // 名称最大字符数
+pub const TOKEN_SYMBOL_MAX_CHARS: usize = 16; // 符号最大字符数
+pub const TOKEN_DESC_MAX_CHARS: usize = 1000; // 描述最大字符数
+pub const TOKEN_LAUNCH_MAX_DAYS_AHEAD: i64 = 30; // 发布时间最多提前多少天
+pub const CREATE_TOKEN_IMAGE_PATH: &str = "image/狐链fox.jpg"; // 本地图片路径或URL，可通过环境变量CREATE_TOKEN_IMAGE覆盖
+pub const TOKEN_IMAGE_MAX_BYTES: usize = 5 * 1024 * 1024; // 上传图片大小上限，超过时重新压缩
+pub const TOKEN_IMAGE_DOWNLOAD_MAX_BYTES: usize = 20 * 1024 * 1024; // 下载远程图片的大小上限，超过时直接放弃
+pub const TOKEN_IMAGE_MIN_DIMENSION: u32 = 64; // 图片宽高下限
+pub const TOKEN_IMAGE_MAX_DIMENSION: u32 = 1024; // 图片宽高上限，超过时等比缩小
+pub const BSC_BLOCK_TIME_SECS: u64 = 3; // 出块间隔，用于判断开盘首个区块
+pub const LAUNCH_BUY_GAS_LIMIT: u64 = 600_000; // 开盘买入的固定gas上限（开盘前无法估算）
+pub const LAUNCH_BUY_SLIPPAGE_PERCENT: u64 = 10; // 开盘买入的滑点保护
+pub const CREATION_CHECKPOINT_DIR: &str = "data/creations"; // 创建流程的断点记录目录
+pub const CREATE_TX_REPLACEMENT_GAS_BUMP_PERCENT: u64 = 20; // 沿用未使用的nonce重发创建交易时提高的gas价格（节点要求替换交易至少高10%）
+pub const CREATE_SIGNATURE_TTL_SECS: i64 = 600; // API签名在此时间内可直接复用，超过后重新获取
+pub const MANIFEST_STATE_DIR: &str = "data/manifests"; // 批量创建的进度记录目录
+pub const BUY_AMOUNT_BNB: &str = "0.2"; // 购买代币的BNB数量
+pub const SELL_ORIGIN: u64 = 0; // sellToken的origin参数
+pub const SELL_FEE_RATE_BPS: u64 = 0; // 卖出时给第三方的手续费率（基点，100 = 1%）
+pub const SELL_FEE_RECIPIENT: &str = "0xE1c727B62cF1ed816587E1005790f9E30299bf88"; // 第三方手续费接收地址
+pub const SELL_FEE_RATE_MAX_BPS: u64 = 500; // 合约允许的手续费率上限（5%）
+pub const SELL_FEE_CONFIG_PATH: &str = "sell_fees.yaml"; // 按钱包设置的手续费参数（可选，格式见sell_fees.example.yaml）
+pub const PROXY_FEE_RATE_MAX: u64 = 5; // 路由合约的手续费率上限（百分比）
+pub const PROXY_SLIPPAGE_PERCENT: u64 = 5; // 通过路由买卖的滑点保护
+
+// 平台固定的发行参数（不可调整）
+pub const CREATE_TOKEN_TOTAL_SUPPLY: u64 = 1_000_000_000; // 总供应量
+pub const CREATE_TOKEN_RAISED_AMOUNT: u32 = 24; // 募集上限（BNB）
+pub const CREATE_TOKEN_SALE_RATE: f64 = 0.8; // 内盘可售比例
+pub const CREATE_TOKEN_RESERVE_RATE: f64 = 0.0; // 保留比例
+
+// Four.meme API配置
+pub const FOURMEME_API_BASE_URL: &str = "https://four.meme/meme-api"; // 可通过环境变量FOURMEME_API_BASE_URL覆盖
+pub const DEFAULT_NETWORK_CODE: &str = "BSC";
+pub const DEFAULT_WALLET_NAME: &str = "MetaMask";
+pub const PLATFORM_CONFIG_CACHE_PATH: &str = "data/platform_config.json"; // 平台募集代币配置缓存
+pub const PLATFORM_CONFIG_TTL_SECS: i64 = 3600; // 平台配置缓存有效期
+pub const FOURMEME_SESSION_DIR: &str = "data/sessions"; // 按钱包地址缓存的登录会话（加密保存）
+pub const TRAFFIC_DIR: &str = "data/traffic"; // --record 未指定文件时的录制目录
+pub const FOURMEME_SESSION_TTL_HOURS: i64 = 24; // 会话缓存有效期，过期或API返回登录失效时重新登录
+
+// 交易记录与盈亏统计配置
+pub const TRADE_JOURNAL_PATH: &str = "data/trades.jsonl"; // 本地交易记录（JSONL）
+pub const PNL_COST_BASIS: &str = "FIFO"; // 持仓成本计算方式: FIFO/AVERAGE
+pub const PNL_CSV_PATH: &str = "data/pnl.csv";
+pub const PNL_JSON_PATH: &str = "data/pnl.json";
+pub const HISTORY_BLOCK_CHUNK: u64 = 5000; // 历史导入时每次get_logs的区块数
+
+// K线配置
+pub const CANDLES_DIR: &str = "data/candles"; // K线和原始成交存储目录
+pub const CANDLES_BLOCK_CHUNK: u64 = 2000; // K线索引时每次get_logs的区块数
+pub const ROUTER_INDEX_DIR: &str = "data/router"; // 路由事件和收入报告存储目录
+pub const ROUTER_BLOCK_CHUNK: u64 = 2000; // 路由事件索引时每次get_logs的区块数
+
+// 日志配置（--log-level/--log-format > 环境变量LOG_LEVEL/LOG_FORMAT > 以下默认值）
+pub const LOG_LEVEL: &str = "info"; // EnvFilter语法，如 "debug" 或 "warn,fourmeme_auto_sell::api=debug"
+pub const LOG_FORMAT: &str = "text"; // text / json
+
+// 库的运行时配置：API地址和本地文件位置。命令行使用以上默认值，
+// 嵌入使用时可改到其他目录；Option字段为None时不读写对应的文件
+#[derive(Debug, Clone)]
+pub struct RuntimeConfig {
+    pub api_base_url: String,
+    pub session_dir: Option<std::path::PathBuf>,           // None时每次运行重新登录
+    pub checkpoint_dir: std::path::PathBuf,                // 创建断点，中断后据此避免重复创建，因此不能关闭
+    pub platform_config_cache: Option<std::path::PathBuf>, // None时每次请求平台配置
+    pub trade_journal: Option<std::path::PathBuf>,         // None时不写交易记录
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            api_base_url: FOURMEME_API_BASE_URL.to_string(),
+            session_dir: Some(FOURMEME_SESSION_DIR.into()),
+            checkpoint_dir: CREATION_CHECKPOINT_DIR.into(),
+            platform_config_cache: Some(PLATFORM_CONFIG_CACHE_PATH.into()),
+            trade_journal: Some(TRADE_JOURNAL_PATH.into()),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+// 合约绑定
+// TokenManager V1/V2、Helper3、ERC20、PancakeSwap V2 Router和TokenTradeWithFee路由的abigen绑定，事件结构体（如TokenSaleFilter）也在这里生成。
+
+use ethers::prelude::abigen;
+
+// ERC20 ABI 简化版
+abigen!(
+    IERC20,
+    r#"[
+        function allowance(address owner, address spender) view returns (uint256)
+        function approve(address spender, uint256 amount) returns (bool)
+        function balanceOf(address account) view returns (uint256)
+        function transfer(address to, uint256 amount) returns (bool)
+        function transferFrom(address from, address to, uint256 amount) returns (bool)
+    ]"#,
+);
+
+// ITokenManager2 ABI - 更新后的方法签名，包含事件定义
+abigen!(
+    ITokenManager2,
+    r#"[
+        function sellToken(uint256 origin, address token, address from, uint256 amount, uint256 minFunds, uint256 feeRate, address feeRecipient) external
+        function buyTokenAMAP(address token, address to, uint256 funds, uint256 minAmount) external payable
+        function buyToken(address token, address to, uint256 amount, uint256 maxFunds) external payable
+        function createToken(bytes calldata createArg, bytes calldata sign) external payable returns (address token)
+        
+        event TokenCreated(address indexed token, address indexed creator, uint256 tokenId)
+        event TokenCreate(address creator, address token, uint256 requestId, string name, string symbol, uint256 totalSupply, uint256 launchTime, uint256 launchFee)
+        event Transfer(address indexed from, address indexed to, uint256 value)
+        event TokenPurchase(address token, address account, uint256 price, uint256 amount, uint256 cost, uint256 fee, uint256 offers, uint256 funds)
+        event TokenSale(address token, address account, uint256 price, uint256 amount, uint256 cost, uint256 fee, uint256 offers, uint256 funds)
+    ]"#,
+);
+
+// TokenManager V1 ABI - 只用于交易2024-09-05之前创建的代币，成交事件见journal.rs
+abigen!(
+    ITokenManagerV1,
+    r#"[
+        function purchaseTokenAMAP(address token, uint256 funds, uint256 minAmount) external payable
+        function saleToken(address token, uint256 amount) external
+    ]"#,
+);
+
+// ITokenManagerHelper3 ABI - 用于预估和获取信息
+abigen!(
+    ITokenManagerHelper3,
+    r#"[
+        function getTokenInfo(address token) external view returns (uint256 version, address tokenManager, address quote, uint256 lastPrice, uint256 tradingFeeRate, uint256 minTradingFee, uint256 launchTime, uint256 offers, uint256 maxOffers, uint256 funds, uint256 maxFunds, bool liquidityAdded)
+        function tryBuy(address token, uint256 amount, uint256 funds) external view returns (address tokenManager, address quote, uint256 estimatedAmount, uint256 estimatedCost, uint256 estimatedFee, uint256 amountMsgValue, uint256 amountApproval, uint256 amountFunds)
+        function trySell(address token, uint256 amount) external view returns (address tokenManager, address quote, uint256 funds, uint256 fee)
+        function buyWithEth(uint256 origin, address token, address to, uint256 funds, uint256 minAmount) external payable
+        function sellForEth(uint256 origin, address token, uint256 amount, uint256 minFunds, uint256 feeRate, address feeRecipient) external
+        function calcInitialPrice(uint256 maxRaising, uint256 totalSupply, uint256 offers, uint256 reserves) external view returns (uint256 priceWei)
+    ]"#,
+);
+
+// TokenTradeWithFee ABI - 带手续费的代理路由（fourmeme/1.sol）
+abigen!(
+    TokenTradeWithFee,
+    r#"[
+        function owner() external view returns (address)
+        function tokenManagerHelper() external view returns (address)
+        function feeRecipient() external view returns (address)
+        function feeRate() external view returns (uint256)
+        function setFee(uint256 _feeRate, address _feeRecipient) external
+        function tryBuy(address token, uint256 amount, uint256 funds) external view returns (address tokenManager, address quote, uint256 estimatedAmount, uint256 estimatedCost, uint256 estimatedFee, uint256 amountMsgValue, uint256 amountApproval, uint256 amountFunds)
+        function trySell(address token, uint256 amount) external view returns (address tokenManager, address quote, uint256 funds, uint256 fee)
+        function getTokenInfo(address token) external view returns (uint256 version, address tokenManager, address quote, uint256 lastPrice, uint256 tradingFeeRate, uint256 minTradingFee, uint256 launchTime, uint256 offers, uint256 maxOffers, uint256 funds, uint256 maxFunds, bool liquidityAdded)
+        function buyTokenWithFee(address token, uint256 minAmount) external payable
+        function sellTokenWithFee(address token, uint256 amount, uint256 minFunds) external
+
+        event ProxyTokenPurchase(address indexed token, address indexed account, uint256 tokenAmount, uint256 feeAmount)
+        event ProxyTokenSold(address indexed token, uint256 amount, uint256 fundsReceived)
+        event FeeSet(uint256 feeRate, address feeRecipient)
+    ]"#,
+);
+
+// PancakeSwap V2 Router - 内盘结束（liquidityAdded）后的代币在这里交易
+abigen!(
+    IPancakeRouter02,
+    r#"[
+        function getAmountsOut(uint256 amountIn, address[] calldata path) external view returns (uint256[] memory amounts)
+        function swapExactETHForTokensSupportingFeeOnTransferTokens(uint256 amountOutMin, address[] calldata path, address to, uint256 deadline) external payable
+        function swapExactTokensForETHSupportingFeeOnTransferTokens(uint256 amountIn, uint256 amountOutMin, address[] calldata path, address to, uint256 deadline) external
+    ]"#,
+);
@@ -0,0 +1,618 @@
+// 创建代币流程
+// 通过Four.meme API登录、上传图片并获取createToken签名，再调用TokenManager2创建代币（可附带预购）。
+// 每个阶段写入断点（见checkpoint模块），中断后重新运行不会重复创建；对任意带签名钱包的Middleware通用。
+
+use chrono::Duration;
+use ethers::{
+    prelude::*,
+    signers::LocalWallet,
+    types::{Address, H256, U256},
+    utils::format_ether,
+};
+use std::sync::Arc;
+use serde::Serialize;
+
+use crate::api::{CreateTokenData, CreateTokenParams, FourMemeApiClient, RaisedToken};
+use crate::checkpoint::{self, CreationCheckpoint, CreationStage, SentTx};
+use crate::config::{RuntimeConfig, CREATE_SIGNATURE_TTL_SECS, CREATE_TX_REPLACEMENT_GAS_BUMP_PERCENT, FOURMEME_SESSION_TTL_HOURS, PLATFORM_CONFIG_TTL_SECS};
+use crate::contracts::{ITokenManager2, IERC20};
+use crate::session::SessionStore;
+use crate::token_image::ImageSource;
+use crate::{journal, metadata, parse_bnb_amount, platform_config, schedule, sender};
+
+// 创建代币并购买的结果结构体
+#[derive(Debug, Serialize)]
+pub struct CreateAndBuyResult {
+    pub token_address: Address,
+    pub create_tx_hash: H256,
+    pub approve_tx_hash: Option<H256>,
+    pub tokens_received: U256,
+    pub bnb_spent: U256,
+    pub creation_block: Option<U256>,
+    pub launch_time_ms: u64,                  // API确认的发布时间
+    pub server_clock: schedule::ServerClock, // 按API返回的serverTime校准的时钟
+}
+
+pub fn print_create_result(create_result: &CreateAndBuyResult) {
+    logln!("\n🎉 创建代币并购买完成汇总:");
+    logln!("新代币地址: {:?}", create_result.token_address);
+    logln!("创建交易哈希: {:?}", create_result.create_tx_hash);
+    if let Some(approve_hash) = create_result.approve_tx_hash {
+        logln!("授权交易哈希: {:?}", approve_hash);
+    }
+    if let Some(creation_block) = create_result.creation_block {
+        logln!("创建区块号: {:?}", creation_block);
+    }
+    logln!("获得代币数量: {}", create_result.tokens_received);
+    logln!("花费BNB: {}", format_ether(create_result.bnb_spent));
+}
+
+// 通过Four.meme API获取createToken的签名参数：登录 -> 上传图片（已上传则跳过）-> 创建
+async fn request_create_signature<M: Middleware + 'static>(
+    client: &M,
+    api_client: &mut FourMemeApiClient,
+    checkpoint: &mut CreationCheckpoint,
+    create_params: &CreateTokenParams,
+    image_source: &ImageSource,
+    raised_token: &RaisedToken,
+) -> std::result::Result<CreateTokenData, Box<dyn std::error::Error>> {
+    logln!("\n🔹 步骤1: 通过Four.meme API创建代币");
+
+    // 1.1 登录（复用未过期的缓存会话，否则获取nonce并签名登录）
+    logln!("  检查登录会话...");
+    api_client.ensure_session().await?;
+    checkpoint.logged_in()?;
+
+    // 1.2 上传图片
+    let img_url = match checkpoint.img_url.clone() {
+        Some(img_url) => {
+            logln!("  ♻️  复用已上传的图片: {}", img_url);
+            img_url
+        }
+        None => {
+            logln!("  上传代币图片...");
+            let img_url = api_client.upload_image(image_source).await?;
+            logln!("  ✅ 图片上传成功: {}", &img_url);
+            checkpoint.image_uploaded(&img_url)?;
+            img_url
+        }
+    };
+
+    // 1.3 创建代币并获取签名参数
+    logln!("  创建代币API调用...");
+    let mut api_params = create_params.clone();
+    api_params.img_url = img_url;
+
+    let create_data = api_client.create_token(&api_params, raised_token).await?;
+    let server_clock = schedule::ServerClock::from_server_time(create_data.server_time);
+    let signed_block = client.get_block_number().await?.as_u64();
+    checkpoint.signed(&create_data, signed_block, server_clock.offset_ms)?;
+    logln!("  ✅ API创建成功，获得签名参数");
+
+    tracing::debug!(
+        token_id = create_data.token_id,
+        create_arg = %create_data.create_arg,
+        signature = %create_data.signature,
+        contract_address = ?create_data.contract_address,
+        "API返回的签名参数"
+    );
+
+    if let Some(addr) = &create_data.contract_address {
+        logln!("  合约地址: {}", addr);
+    } else {
+        logln!("  ⚠️  合约地址: API未返回contract_address字段");
+    }
+    Ok(create_data)
+}
+
+// 上次中断时的创建结果：交易已确认则返回receipt；交易回滚时回到已签名阶段；
+// 节点查不到上次的交易时按nonce判断：nonce已被使用则只在链上查找代币、绝不重发，
+// nonce未被使用则保留nonce，由调用方用同一nonce重发替换旧交易；
+// 已签名但没有交易记录时，按requestId在链上查找TokenCreate事件
+async fn resume_created_token<M: Middleware + 'static>(
+    client: &M,
+    token_manager_address: Address,
+    checkpoint: &mut CreationCheckpoint,
+) -> std::result::Result<Option<TransactionReceipt>, Box<dyn std::error::Error>> {
+    if let Some(tx_hash) = checkpoint.tx_hash {
+        let receipt = match checkpoint::sent_tx_status(client, tx_hash).await? {
+            SentTx::Mined(receipt) => Some(*receipt),
+            SentTx::Pending => {
+                logln!("  ⏳ 上次的创建交易仍在等待打包: {:?}", tx_hash);
+                PendingTransaction::new(tx_hash, client.provider()).await?
+            }
+            SentTx::NotFound => None,
+        };
+        match receipt {
+            Some(receipt) if receipt.status == Some(U64::one()) => {
+                logln!("  ✅ 上次的创建交易已确认: {:?}", tx_hash);
+                checkpoint.confirmed(tx_hash, receipt.block_number.map(|n| n.as_u64()))?;
+                return Ok(Some(receipt));
+            }
+            Some(_) => {
+                logln!("  ⚠️  上次的创建交易已回滚: {:?}", tx_hash);
+                checkpoint.clear_tx()?;
+            }
+            // 查不到不代表已丢弃，保留nonce，下面按nonce是否已被使用决定
+            None if checkpoint.tx_nonce.is_some() => {
+                logln!("  ⚠️  节点上查不到上次的创建交易: {:?}", tx_hash);
+                checkpoint.tx_dropped()?;
+            }
+            None => {
+                logln!("  ⚠️  上次的创建交易已被丢弃: {:?}", tx_hash);
+                checkpoint.clear_tx()?;
+            }
+        }
+    }
+
+    if let Some(tx_nonce) = checkpoint.tx_nonce {
+        let latest_nonce = client.get_transaction_count(checkpoint.wallet, Some(BlockNumber::Latest.into())).await?;
+        if checkpoint::nonce_consumed(latest_nonce, tx_nonce) {
+            // 该nonce上已有交易打包（可能就是上次的交易或其替换交易，只是当前节点查不到），不能重发
+            logln!("  ⚠️  上次创建交易的nonce {} 已被使用，在链上查找创建结果", tx_nonce);
+            if let Some(receipt) = find_created_receipt(client, token_manager_address, checkpoint).await? {
+                return Ok(Some(receipt));
+            }
+            return Err(format!(
+                "上次创建交易的nonce {} 已被使用，但链上未找到该签名创建的代币；为避免重复创建不会重发，\
+                 请在区块浏览器确认该nonce的交易，确定未创建后删除 {} 重新运行",
+                tx_nonce,
+                checkpoint.path().display()
+            ).into());
+        }
+        logln!("  ℹ️  上次创建交易的nonce {} 未被使用，将用同一nonce重发", tx_nonce);
+    }
+
+    find_created_receipt(client, token_manager_address, checkpoint).await
+}
+
+// 按API签名的requestId在链上查找已创建的代币，找到则记为已确认并返回该交易的receipt
+async fn find_created_receipt<M: Middleware + 'static>(
+    client: &M,
+    token_manager_address: Address,
+    checkpoint: &mut CreationCheckpoint,
+) -> std::result::Result<Option<TransactionReceipt>, Box<dyn std::error::Error>> {
+    if let (Some(data), Some(from_block)) = (&checkpoint.create_data, checkpoint.signed_block) {
+        let found = checkpoint::find_created_token(client, token_manager_address, checkpoint.wallet, data.token_id, from_block).await?;
+        if let Some((token, tx_hash)) = found {
+            logln!("  ✅ 链上已存在用该签名创建的代币 {:?} (交易 {:?})", token, tx_hash);
+            let receipt = client
+                .get_transaction_receipt(tx_hash)
+                .await?
+                .ok_or("无法获取已创建代币的交易回执")?;
+            checkpoint.confirmed(tx_hash, receipt.block_number.map(|n| n.as_u64()))?;
+            return Ok(Some(receipt));
+        }
+    }
+    Ok(None)
+}
+
+// createToken的msg.value
+fn create_token_value(
+    raised_token: &RaisedToken,
+    create_data: &CreateTokenData,
+    pre_sale_wei: U256,
+    launch_fee_config: U256,
+) -> std::result::Result<U256, Box<dyn std::error::Error>> {
+    if !raised_token.is_native() {
+        // 非BNB募集：msg.value只付发行费，预购用募集代币支付
+        logln!("  预购数量: {} {}", format_ether(pre_sale_wei), raised_token.symbol);
+        logln!("  发行费: {} {}", format_ether(launch_fee_config), raised_token.native_symbol);
+        return Ok(launch_fee_config);
+    }
+
+    // createToken的msg.value以API返回的bamount为准（发行费 + 预购），精确换算为wei
+    let required_bnb_wei = parse_bnb_amount("bamount", &create_data.bamount)?;
+    let launch_fee = required_bnb_wei.saturating_sub(pre_sale_wei);
+
+    logln!("  预购BNB数量: {} BNB", format_ether(pre_sale_wei));
+    logln!("  发行费: {} BNB (平台配置deployCost: {} BNB)", format_ether(launch_fee), format_ether(launch_fee_config));
+    logln!("  创建代币需要BNB: {} BNB (API返回bamount)", format_ether(required_bnb_wei));
+    if required_bnb_wei < pre_sale_wei {
+        logln!("  ⚠️  API返回的bamount小于预购数量，以API为准");
+    }
+    Ok(required_bnb_wei)
+}
+
+// 非BNB募集时，预购的募集代币需要先授权给TokenManager
+async fn approve_quote_for_pre_sale<M: Middleware + 'static>(
+    client: &Arc<M>,
+    raised_token: &RaisedToken,
+    token_manager_address: Address,
+    pre_sale_wei: U256,
+) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    if pre_sale_wei.is_zero() {
+        return Ok(());
+    }
+    let user_address = sender(&**client)?;
+    let quote_address: Address = raised_token.symbol_address.parse()?;
+    let quote = IERC20::new(quote_address, client.clone());
+    let quote_balance = quote.balance_of(user_address).call().await?;
+    if quote_balance < pre_sale_wei {
+        return Err(format!(
+            "{}余额不足: 预购需要 {}，当前余额 {}",
+            raised_token.symbol,
+            format_ether(pre_sale_wei),
+            format_ether(quote_balance)
+        ).into());
+    }
+    let allowance = quote.allowance(user_address, token_manager_address).call().await?;
+    if allowance < pre_sale_wei {
+        logln!("  授权{}给TokenManager...", raised_token.symbol);
+        let receipt = quote.approve(token_manager_address, pre_sale_wei).send().await?.await?;
+        logln!("  ✅ 授权确认! 区块: {:?}", receipt.and_then(|r| r.block_number));
+    }
+    Ok(())
+}
+
+// 创建代币并购买的方法 - 使用Four.meme API；购买即预购（preSale），在createToken交易中一起完成
+#[tracing::instrument(
+    name = "create",
+    skip_all,
+    fields(name = %create_params.name, symbol = %create_params.short_name, wallet = ?wallet.address(), token = tracing::field::Empty)
+)]
+pub async fn create_token_and_buy<M: Middleware + 'static>(
+    client: Arc<M>,
+    wallet: &LocalWallet, // 登录Four.meme API的钱包，需与client的签名钱包一致
+    token_manager_address: Address,
+    create_params: CreateTokenParams,
+    image_source: &ImageSource,
+    config: &RuntimeConfig,
+) -> std::result::Result<CreateAndBuyResult, Box<dyn std::error::Error>> {
+    let user_address = sender(&*client)?;
+    if wallet.address() != user_address {
+        return Err(format!("API登录钱包 {:?} 与交易钱包 {:?} 不一致", wallet.address(), user_address).into());
+    }
+    
+    // 创建合约实例
+    let token_manager2 = ITokenManager2::new(token_manager_address, client.clone());
+    
+    logln!("🚀 开始创建代币流程...");
+    logln!("代币名称: {}", create_params.name);
+    logln!("代币符号: {}", create_params.short_name);
+
+    let api_client = FourMemeApiClient::new().with_base_url(&config.api_base_url);
+    let mut api_client = match &config.session_dir {
+        Some(dir) => {
+            let sessions = SessionStore::new(dir, wallet, Duration::hours(FOURMEME_SESSION_TTL_HOURS));
+            api_client.with_session(wallet.clone(), sessions)
+        }
+        None => api_client.with_wallet(wallet.clone()),
+    };
+
+    // 募集代币配置以平台当前配置为准
+    let raised_tokens =
+        platform_config::load_raised_tokens(&api_client, config.platform_config_cache.as_deref(), PLATFORM_CONFIG_TTL_SECS).await;
+    let raised_token = platform_config::select_raised_token(&raised_tokens, &create_params.raise_symbol)?;
+    platform_config::print_raised_token(&raised_token);
+
+    // 登录和上传图片之前先在本地校验代币信息
+    metadata::validate_params(&create_params, &raised_token)?;
+
+    // 预购数量在调用API前校验，避免无效数值被静默替换
+    let pre_sale_wei = parse_bnb_amount("preSale", &create_params.pre_sale)?;
+    let launch_fee_config = parse_bnb_amount("deployCost", &raised_token.deploy_cost)?;
+    
+    // 获取创建前的BNB余额
+    let before_bnb_balance = client.get_balance(user_address, None).await?;
+    logln!("创建前BNB余额: {}", format_ether(before_bnb_balance));
+    
+    // 断点：每个阶段完成后写盘，中断后重新运行从最后一个阶段继续
+    let mut checkpoint = CreationCheckpoint::load_or_new(&config.checkpoint_dir, user_address, &create_params)?;
+    if checkpoint.stage != CreationStage::Started {
+        logln!("♻️  发现未完成的创建记录: {} (阶段: {})", checkpoint.path().display(), checkpoint.stage);
+    }
+
+    // 已发送过交易或已获得签名时，先确认链上是否已经创建，避免重复创建
+    let resumed_receipt = resume_created_token(&client, token_manager_address, &mut checkpoint).await?;
+    if resumed_receipt.is_some() {
+        logln!("  ℹ️  该代币已创建，不会重复创建；如需再次创建同名代币请删除 {}", checkpoint.path().display());
+    }
+
+    // 步骤1: 通过Four.meme API创建代币（复用未过期的签名）
+    let mut reused_signature = false;
+    let mut create_data = match (&resumed_receipt, checkpoint.create_data.clone()) {
+        (Some(_), Some(data)) => data,
+        _ => match checkpoint.reusable_signature(CREATE_SIGNATURE_TTL_SECS).cloned() {
+            Some(data) => {
+                logln!("\n♻️  复用未过期的API签名 (tokenId: {})", data.token_id);
+                reused_signature = true;
+                data
+            }
+            None => {
+                request_create_signature(&client, &mut api_client, &mut checkpoint, &create_params, image_source, &raised_token).await?
+            }
+        },
+    };
+
+    // 步骤2: 调用区块链合约创建代币
+    logln!("\n🔹 步骤2: 调用区块链合约创建代币");
+    let mut required_bnb_wei = create_token_value(&raised_token, &create_data, pre_sale_wei, launch_fee_config)?;
+
+    let (create_tx_hash, create_receipt) = match resumed_receipt {
+        Some(receipt) => (receipt.transaction_hash, receipt),
+        None => {
+            if !raised_token.is_native() {
+                approve_quote_for_pre_sale(&client, &raised_token, token_manager_address, pre_sale_wei).await?;
+            }
+
+            let build_create_tx = |data: &CreateTokenData, value: U256| -> std::result::Result<_, Box<dyn std::error::Error>> {
+                let create_arg_bytes = hex::decode(data.create_arg.trim_start_matches("0x"))?;
+                let signature_bytes = hex::decode(data.signature.trim_start_matches("0x"))?;
+                Ok(token_manager2.create_token(create_arg_bytes.into(), signature_bytes.into()).value(value))
+            };
+            // 余额连msg.value都不够时预估只会得到含糊的回滚错误，先直接报错
+            let balance = client.get_balance(user_address, None).await?;
+            if balance < required_bnb_wei {
+                return Err(format!(
+                    "BNB余额不足: 需要 {} BNB (msg.value，不含gas)，当前余额 {} BNB",
+                    format_ether(required_bnb_wei),
+                    format_ether(balance)
+                ).into());
+            }
+            let mut create_tx = build_create_tx(&create_data, required_bnb_wei)?;
+
+            // 复用的签名可能已失效，预估失败时重新获取签名
+            let gas_estimate = match create_tx.estimate_gas().await {
+                Ok(gas) => gas,
+                Err(e) if reused_signature => {
+                    logln!("  ⚠️  复用的签名无法通过预估，重新获取签名: {}", e);
+                    checkpoint.clear_signature()?;
+                    create_data = request_create_signature(&client, &mut api_client, &mut checkpoint, &create_params, image_source, &raised_token).await?;
+                    required_bnb_wei = create_token_value(&raised_token, &create_data, pre_sale_wei, launch_fee_config)?;
+                    create_tx = build_create_tx(&create_data, required_bnb_wei)?;
+                    create_tx.estimate_gas().await?
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            // 上次的交易在节点上查不到但nonce未被使用时，沿用该nonce并提高gas价格，
+            // 让新交易替换可能仍在其他节点内存池中的旧交易，而不是两笔都被打包
+            let (tx_nonce, gas_price) = match checkpoint.tx_nonce {
+                Some(nonce) => {
+                    let gas_price = client.get_gas_price().await? * (100 + CREATE_TX_REPLACEMENT_GAS_BUMP_PERCENT) / 100;
+                    logln!("  ♻️  沿用上次未使用的nonce {} 重发创建交易", nonce);
+                    (nonce, gas_price)
+                }
+                None => (
+                    client.get_transaction_count(user_address, Some(BlockNumber::Pending.into())).await?,
+                    client.get_gas_price().await?,
+                ),
+            };
+            create_tx = create_tx.nonce(tx_nonce).gas_price(gas_price);
+
+            // 发送前检查余额是否足够支付msg.value和gas
+            let gas_cost = gas_estimate * gas_price;
+            let balance = client.get_balance(user_address, None).await?;
+            logln!("  预估gas费用: {} BNB", format_ether(gas_cost));
+            if balance < required_bnb_wei + gas_cost {
+                return Err(format!(
+                    "BNB余额不足: 需要 {} BNB (msg.value {} + gas {})，当前余额 {} BNB",
+                    format_ether(required_bnb_wei + gas_cost),
+                    format_ether(required_bnb_wei),
+                    format_ether(gas_cost),
+                    format_ether(balance)
+                ).into());
+            }
+
+            let create_pending = create_tx.send().await?;
+            let create_tx_hash = create_pending.tx_hash();
+            checkpoint.tx_sent(create_tx_hash, tx_nonce)?;
+            tracing::info!(tx_hash = ?create_tx_hash, nonce = %tx_nonce, "创建代币交易已发送");
+
+            // 等待创建交易确认
+            let create_receipt = create_pending.await?.ok_or("创建代币交易未被打包")?;
+            if create_receipt.status != Some(U64::one()) {
+                checkpoint.clear_tx()?;
+                return Err(format!("创建代币交易回滚: {:?}", create_tx_hash).into());
+            }
+            checkpoint.confirmed(create_tx_hash, create_receipt.block_number.map(|n| n.as_u64()))?;
+            (create_tx_hash, create_receipt)
+        }
+    };
+    let server_clock = schedule::ServerClock {
+        offset_ms: checkpoint.clock_offset_ms.unwrap_or_default(),
+    };
+    tracing::info!(tx_hash = ?create_tx_hash, block = ?create_receipt.block_number, "创建代币交易确认");
+    // 预购会产生TokenPurchase事件，写入交易记录（按交易哈希去重，重复运行不会重复记录）
+    journal::record_receipt(&client, config.trade_journal.as_deref(), &create_receipt).await;
+    
+    // 按API签名的requestId匹配TokenManager的TokenCreate事件：先解析本交易的日志，
+    // 解析不到时再查询该区块的事件（只认同一交易）。不按日志地址猜测，
+    // 非BNB募集时交易日志里还有募集代币等其他合约的事件
+    tracing::debug!(logs = create_receipt.logs.len(), "分析交易日志");
+    let mut token_address = create_receipt
+        .logs
+        .iter()
+        .find_map(|log| checkpoint::created_token_from_log(log, token_manager_address, user_address, create_data.token_id));
+    if token_address.is_none() {
+        if let Some(block) = create_receipt.block_number {
+            let found = checkpoint::find_created_token(&client, token_manager_address, user_address, create_data.token_id, block.as_u64()).await?;
+            token_address = found.filter(|(_, tx_hash)| *tx_hash == create_tx_hash).map(|(token, _)| token);
+        }
+    }
+
+    // 交易已确认但拿不到代币地址：不能当作创建成功返回零地址，断点保留已确认的交易，重新运行不会重复创建
+    let Some(token_address) = token_address.filter(|token| !token.is_zero()) else {
+        tracing::warn!(tx_hash = ?create_tx_hash, "未能从交易日志中解析到代币地址");
+        return Err(format!(
+            "创建交易 {:?} 已确认，但未能解析到代币地址；请在区块浏览器核对后重新运行（断点 {} 会复用该交易，不会重复创建）",
+            create_tx_hash,
+            checkpoint.path().display()
+        ).into());
+    };
+
+    checkpoint.token_found(token_address)?;
+    tracing::Span::current().record("token", tracing::field::debug(token_address));
+
+    logln!("\n🎉 代币创建完成!");
+    logln!("📝 新创建的代币信息:"); 
+    logln!("   代币地址: {:?}", token_address);
+    logln!("   交易哈希: {:?}", create_tx_hash);
+    if let Some(block_number) = create_receipt.block_number {
+        logln!("   区块号: {}", block_number);
+    }
+    
+    // 步骤3: 使用新创建的代币地址进行approve授权
+    logln!("\n🔹 步骤3: 使用新代币地址进行approve授权");
+    
+    // 使用从区块链解析出的代币地址
+    let new_token = IERC20::new(token_address, client.clone());
+    
+    // 获取代币余额
+    let token_balance = new_token.balance_of(user_address).call().await?;
+    logln!("代币余额: {}", token_balance);
+    
+    let mut approve_tx_hash = None;
+    
+    if token_balance > U256::zero() {
+        // 检查当前授权额度
+        let current_allowance = new_token.allowance(user_address, token_manager_address).call().await?;
+        tracing::debug!(allowance = %current_allowance, "当前授权额度");
+        
+        if current_allowance < token_balance {
+            logln!("  发送approve交易...");
+            let approve_tx = new_token.approve(token_manager_address, token_balance);
+            let approve_pending = approve_tx.send().await?;
+            approve_tx_hash = Some(approve_pending.tx_hash());
+            tracing::info!(tx_hash = ?approve_pending.tx_hash(), "approve交易已发送");
+            
+            // 等待approve交易确认
+            let approve_receipt = approve_pending.await?.ok_or("approve交易未被打包")?;
+            if approve_receipt.status != Some(U64::one()) {
+                return Err(format!("approve交易回滚: {:?}", approve_receipt.transaction_hash).into());
+            }
+            tracing::info!(tx_hash = ?approve_receipt.transaction_hash, block = ?approve_receipt.block_number, "approve交易确认");
+            
+            // 检查新的授权额度
+            let new_allowance = new_token.allowance(user_address, token_manager_address).call().await?;
+            tracing::debug!(allowance = %new_allowance, "新的授权额度");
+        } else {
+            logln!("  ✅ 授权已足够，无需重新授权");
+        }
+    } else {
+        logln!("  ℹ️  代币余额为0，无需进行授权");
+    }
+    
+    // 返回包含实际代币地址的结果
+    Ok(CreateAndBuyResult {
+        token_address, // 使用从区块链解析出的代币地址
+        create_tx_hash,
+        approve_tx_hash,
+        tokens_received: token_balance, // 返回实际代币余额
+        bnb_spent: required_bnb_wei, // 使用实际花费的BNB
+        creation_block: create_receipt.block_number.map(|n| U256::from(n.as_u64())),
+        launch_time_ms: create_data.launch_time,
+        server_clock,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkpoint::tests::{create_data, params, token_create_log};
+    use crate::traffic::{Exchange, Traffic, TrafficClient};
+    use serde_json::{json, Value};
+
+    const TX_NONCE: u64 = 7;
+
+    fn rpc(method: &str, result: Value) -> Exchange {
+        Exchange::Rpc {
+            method: method.to_string(),
+            params: Value::Null,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    // 已发送nonce为7的创建交易，当前节点上查不到该交易
+    fn not_found(latest_nonce: u64) -> Vec<Exchange> {
+        vec![
+            rpc("eth_getTransactionReceipt", Value::Null),
+            rpc("eth_getTransactionByHash", Value::Null),
+            rpc("eth_getTransactionCount", json!(format!("{:#x}", latest_nonce))),
+        ]
+    }
+
+    // 按回放的RPC响应执行resume_created_token，返回结果、重新加载的断点和未用完的记录数
+    async fn resume(
+        exchanges: Vec<Exchange>,
+    ) -> (std::result::Result<Option<TransactionReceipt>, String>, CreationCheckpoint, usize) {
+        let dir = tempfile::tempdir().unwrap();
+        let wallet = Address::repeat_byte(0x11);
+        let mut checkpoint = CreationCheckpoint::load_or_new(dir.path(), wallet, &params()).unwrap();
+        checkpoint.signed(&create_data(), 100, 0).unwrap();
+        checkpoint.tx_sent(H256::repeat_byte(0x22), U256::from(TX_NONCE)).unwrap();
+
+        let path = dir.path().join("traffic.jsonl");
+        let recorder = Traffic::record(&path).unwrap();
+        for exchange in exchanges {
+            recorder.log(exchange);
+        }
+        drop(recorder);
+        let traffic = Arc::new(Traffic::replay(&path).unwrap());
+        let client = Provider::new(TrafficClient::replay_only(traffic.clone()));
+
+        let result = resume_created_token(&client, Address::repeat_byte(0x5c), &mut checkpoint)
+            .await
+            .map_err(|e| e.to_string());
+        let reloaded = CreationCheckpoint::load_or_new(dir.path(), wallet, &params()).unwrap();
+        (result, reloaded, traffic.remaining())
+    }
+
+    #[tokio::test]
+    async fn unused_nonce_is_kept_for_replacement() {
+        let mut exchanges = not_found(TX_NONCE);
+        exchanges.push(rpc("eth_blockNumber", json!("0x80")));
+        exchanges.push(rpc("eth_getLogs", json!([])));
+
+        let (result, checkpoint, remaining) = resume(exchanges).await;
+        assert!(result.unwrap().is_none());
+        assert_eq!(checkpoint.stage, CreationStage::Signed);
+        assert_eq!(checkpoint.tx_hash, None);
+        assert_eq!(checkpoint.tx_nonce, Some(U256::from(TX_NONCE)));
+        assert_eq!(remaining, 0);
+    }
+
+    #[tokio::test]
+    async fn used_nonce_without_token_refuses_to_resend() {
+        let mut exchanges = not_found(TX_NONCE + 1);
+        exchanges.push(rpc("eth_blockNumber", json!("0x80")));
+        exchanges.push(rpc("eth_getLogs", json!([])));
+
+        let (result, checkpoint, remaining) = resume(exchanges).await;
+        assert!(result.unwrap_err().contains("不会重发"));
+        // 再次运行仍然先检查nonce，不会用新的nonce发送
+        assert_eq!(checkpoint.tx_nonce, Some(U256::from(TX_NONCE)));
+        assert_eq!(remaining, 0);
+    }
+
+    #[tokio::test]
+    async fn used_nonce_finds_token_created_by_replacement() {
+        let creator = Address::repeat_byte(0x11);
+        let token = Address::repeat_byte(0x44);
+        let mined_hash = H256::repeat_byte(0x33);
+        let log = Log {
+            transaction_hash: Some(mined_hash),
+            block_number: Some(U64::from(0x70)),
+            ..token_create_log(Address::repeat_byte(0x5c), creator, token, create_data().token_id)
+        };
+        let receipt = TransactionReceipt {
+            transaction_hash: mined_hash,
+            block_number: Some(U64::from(0x70)),
+            status: Some(U64::one()),
+            ..Default::default()
+        };
+
+        let mut exchanges = not_found(TX_NONCE + 1);
+        exchanges.push(rpc("eth_blockNumber", json!("0x80")));
+        exchanges.push(rpc("eth_getLogs", json!([log])));
+        exchanges.push(rpc("eth_getTransactionReceipt", serde_json::to_value(&receipt).unwrap()));
+
+        let (result, checkpoint, remaining) = resume(exchanges).await;
+        assert_eq!(result.unwrap().map(|r| r.transaction_hash), Some(mined_hash));
+        assert_eq!(checkpoint.stage, CreationStage::Confirmed);
+        assert_eq!(checkpoint.tx_hash, Some(mined_hash));
+        assert_eq!(remaining, 0);
+    }
+}
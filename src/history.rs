@@ -0,0 +1,389 @@
+// 链上历史导入
+// 按区块范围分页扫描TokenManager V1/V2的TokenPurchase/TokenSale日志，
+// 找出指定钱包的全部成交，写入本地交易记录，用于盈亏统计和后续卖出策略。
+// 事件中的account字段没有indexed，只能按事件签名拉取后在本地过滤。
+// 经Helper3（buyWithEth/sellForEth）或手续费路由成交时account是这些合约的地址，
+// 对account属于routers的日志再按交易发送者（from）归属到钱包；
+// 内盘结束后在PancakeSwap上的成交没有TokenManager事件，不会被导入。
+
+use ethers::{
+    prelude::*,
+    types::{Address, H256, U256},
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use serde::Serialize;
+
+use crate::journal::{self, TradeJournal, TradeRecord};
+
+// 单页区块数过小时不再继续拆分
+const MIN_BLOCK_CHUNK: u64 = 50;
+
+pub struct ImportConfig {
+    pub wallet: Address,
+    pub token_managers: Vec<Address>,
+    pub routers: Vec<Address>, // 代替钱包调用TokenManager的合约（Helper3、手续费路由）
+    pub from_block: u64,
+    pub to_block: Option<u64>, // 为空时扫描到最新区块
+    pub block_chunk: u64,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ImportSummary {
+    pub from_block: u64,
+    pub to_block: u64,
+    pub pages: usize,
+    pub matched: usize, // 属于该钱包的成交数
+    pub written: usize, // 新写入交易记录的条数
+}
+
+// 拉取一页日志，RPC报错（通常是范围或结果数超限）时把区间对半拆分重试
+pub async fn get_logs_split<M: Middleware + 'static>(
+    client: &M,
+    addresses: &[Address],
+    topics: &[H256],
+    from: u64,
+    to: u64,
+) -> std::result::Result<Vec<Log>, Box<dyn std::error::Error>> {
+    let mut pending = vec![(from, to)];
+    let mut logs = Vec::new();
+
+    while let Some((start, end)) = pending.pop() {
+        let filter = Filter::new()
+            .address(addresses.to_vec())
+            .topic0(topics.to_vec())
+            .from_block(start)
+            .to_block(end);
+
+        match client.get_logs(&filter).await {
+            Ok(mut page) => logs.append(&mut page),
+            Err(e) if end - start + 1 > MIN_BLOCK_CHUNK => {
+                let mid = start + (end - start) / 2;
+                logln!("  ⚠️  区块 {}-{} 拉取失败，拆分重试: {}", start, end, e);
+                // 先处理前半段，保持日志顺序
+                pending.push((mid + 1, end));
+                pending.push((start, mid));
+            }
+            Err(e) => return Err(format!("区块 {}-{} 拉取日志失败: {}", start, end, e).into()),
+        }
+    }
+
+    Ok(logs)
+}
+
+// 扫描钱包的历史成交并写入交易记录
+pub async fn import_wallet_history<M: Middleware + 'static>(
+    client: Arc<M>,
+    config: &ImportConfig,
+    trade_journal: &TradeJournal,
+) -> std::result::Result<ImportSummary, Box<dyn std::error::Error>> {
+    let to_block = match config.to_block {
+        Some(block) => block,
+        None => client.get_block_number().await?.as_u64(),
+    };
+    if config.from_block > to_block {
+        return Err(format!("起始区块 {} 大于结束区块 {}", config.from_block, to_block).into());
+    }
+
+    let mut summary = ImportSummary {
+        from_block: config.from_block,
+        to_block,
+        ..Default::default()
+    };
+    let mut block_times: HashMap<u64, u64> = HashMap::new();
+    let chunk = config.block_chunk.max(1);
+    let topics = journal::trade_event_topics();
+
+    let mut page_start = config.from_block;
+    while page_start <= to_block {
+        let page_end = (page_start + chunk - 1).min(to_block);
+        let logs = get_logs_split(&client, &config.token_managers, &topics, page_start, page_end).await?;
+        summary.pages += 1;
+
+        // 本页属于该钱包的成交；交易的发送者和gas每笔只查询一次，gas只计入该交易的第一条成交
+        let mut records: Vec<TradeRecord> = Vec::new();
+        let mut senders: HashMap<H256, Option<(Address, U256)>> = HashMap::new();
+        let mut gas_counted: HashSet<H256> = HashSet::new();
+
+        for log in &logs {
+            let Some((side, token, account, token_amount, funds, platform_fee)) = journal::decode_trade_log(log) else {
+                continue;
+            };
+            let routed = account != config.wallet;
+            if routed && !config.routers.contains(&account) {
+                continue;
+            }
+            let (Some(tx_hash), Some(block_number)) = (log.transaction_hash, log.block_number) else {
+                continue;
+            };
+            let block_number = block_number.as_u64();
+
+            let sender = match senders.get(&tx_hash) {
+                Some(sender) => *sender,
+                None => {
+                    let sender = client
+                        .get_transaction_receipt(tx_hash)
+                        .await?
+                        .map(|receipt| (receipt.from, journal::receipt_gas_cost(&receipt)));
+                    senders.insert(tx_hash, sender);
+                    sender
+                }
+            };
+            let sent_by_wallet = matches!(sender, Some((from, _)) if from == config.wallet);
+            if routed && !sent_by_wallet {
+                continue;
+            }
+
+            let timestamp = match block_times.get(&block_number) {
+                Some(ts) => *ts,
+                None => {
+                    let ts = client
+                        .get_block(block_number)
+                        .await?
+                        .map(|block| block.timestamp.as_u64())
+                        .unwrap_or_default();
+                    block_times.insert(block_number, ts);
+                    ts
+                }
+            };
+
+            // 只有钱包自己发送的交易才计入gas
+            let gas_cost = match sender {
+                Some((_, gas)) if sent_by_wallet && gas_counted.insert(tx_hash) => gas,
+                _ => U256::zero(),
+            };
+
+            records.push(TradeRecord {
+                side,
+                wallet: config.wallet,
+                token,
+                tx_hash,
+                log_index: log.log_index.map(|i| i.as_u64()).unwrap_or_default(),
+                block_number,
+                timestamp,
+                token_amount,
+                funds,
+                platform_fee,
+                gas_cost,
+            });
+        }
+
+        // 每页写入一次，中断后重新运行会自动跳过已写入的记录
+        summary.matched += records.len();
+        summary.written += trade_journal.append(&records)?;
+        logln!(
+            "  已扫描区块 {}-{}: 日志 {} 条，本钱包成交 {} 条",
+            page_start,
+            page_end,
+            logs.len(),
+            records.len()
+        );
+
+        page_start = page_end + 1;
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use ethers::providers::{HttpClientError, JsonRpcClient, JsonRpcError};
+    use serde::{de::DeserializeOwned, Serialize};
+    use serde_json::Value;
+    use std::sync::Mutex;
+
+    // 超过max_span个区块的eth_getLogs返回"range too large"，否则每个区块返回一条日志
+    #[derive(Debug)]
+    struct RangeLimitedRpc {
+        max_span: u64,
+        requests: Mutex<Vec<(u64, u64)>>,
+    }
+
+    impl RangeLimitedRpc {
+        fn new(max_span: u64) -> Self {
+            Self { max_span, requests: Mutex::new(Vec::new()) }
+        }
+    }
+
+    fn block_param(filter: &Value, key: &str) -> u64 {
+        u64::from_str_radix(filter[key].as_str().unwrap().trim_start_matches("0x"), 16).unwrap()
+    }
+
+    #[async_trait]
+    impl JsonRpcClient for RangeLimitedRpc {
+        type Error = HttpClientError;
+
+        async fn request<T, R>(&self, method: &str, params: T) -> std::result::Result<R, Self::Error>
+        where
+            T: std::fmt::Debug + Serialize + Send + Sync,
+            R: DeserializeOwned + Send,
+        {
+            assert_eq!(method, "eth_getLogs");
+            let params = serde_json::to_value(&params).unwrap();
+            let (from, to) = (block_param(&params[0], "fromBlock"), block_param(&params[0], "toBlock"));
+            self.requests.lock().unwrap().push((from, to));
+
+            if to - from + 1 > self.max_span {
+                return Err(HttpClientError::JsonRpcError(JsonRpcError {
+                    code: -32005,
+                    message: "block range too large".to_string(),
+                    data: None,
+                }));
+            }
+            let logs: Vec<Log> = (from..=to)
+                .map(|block| Log {
+                    block_number: Some(U64::from(block)),
+                    ..Default::default()
+                })
+                .collect();
+            Ok(serde_json::from_value(serde_json::to_value(logs).unwrap()).unwrap())
+        }
+    }
+
+    fn blocks(logs: &[Log]) -> Vec<u64> {
+        logs.iter().map(|log| log.block_number.unwrap().as_u64()).collect()
+    }
+
+    #[tokio::test]
+    async fn bisects_too_large_ranges_and_keeps_order() {
+        let provider = Provider::new(RangeLimitedRpc::new(60));
+        let logs = get_logs_split(&provider, &[Address::repeat_byte(1)], &[H256::repeat_byte(2)], 1000, 1199)
+            .await
+            .unwrap();
+
+        // 每个区块恰好一条，按区块顺序拼接，没有遗漏和重复
+        assert_eq!(blocks(&logs), (1000..=1199).collect::<Vec<_>>());
+        // 200 -> 100 -> 50，前半段先处理
+        let requests = provider.as_ref().requests.lock().unwrap().clone();
+        assert_eq!(
+            requests,
+            vec![
+                (1000, 1199),
+                (1000, 1099),
+                (1000, 1049),
+                (1050, 1099),
+                (1100, 1199),
+                (1100, 1149),
+                (1150, 1199),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn small_range_that_still_fails_is_an_error() {
+        let provider = Provider::new(RangeLimitedRpc::new(10));
+        let err = get_logs_split(&provider, &[Address::repeat_byte(1)], &[H256::repeat_byte(2)], 0, 99)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("区块 0-49 拉取日志失败"), "{}", err);
+        assert!(err.to_string().contains("block range too large"));
+    }
+
+    #[tokio::test]
+    async fn range_within_limit_is_fetched_once() {
+        let provider = Provider::new(RangeLimitedRpc::new(1000));
+        let logs = get_logs_split(&provider, &[Address::repeat_byte(1)], &[H256::repeat_byte(2)], 5, 5).await.unwrap();
+        assert_eq!(blocks(&logs), vec![5]);
+        assert_eq!(provider.as_ref().requests.lock().unwrap().len(), 1);
+    }
+
+    // 按交易哈希返回固定的日志、receipt发送者和区块时间
+    #[derive(Debug)]
+    struct WalletHistoryRpc {
+        logs: Vec<Log>,
+        senders: HashMap<H256, Address>,
+    }
+
+    #[async_trait]
+    impl JsonRpcClient for WalletHistoryRpc {
+        type Error = HttpClientError;
+
+        async fn request<T, R>(&self, method: &str, params: T) -> std::result::Result<R, Self::Error>
+        where
+            T: std::fmt::Debug + Serialize + Send + Sync,
+            R: DeserializeOwned + Send,
+        {
+            let params = serde_json::to_value(&params).unwrap();
+            let result = match method {
+                "eth_getLogs" => serde_json::to_value(&self.logs).unwrap(),
+                "eth_getTransactionReceipt" => {
+                    let tx_hash: H256 = serde_json::from_value(params[0].clone()).unwrap();
+                    serde_json::to_value(TransactionReceipt {
+                        transaction_hash: tx_hash,
+                        from: self.senders[&tx_hash],
+                        gas_used: Some(U256::from(100_000)),
+                        effective_gas_price: Some(U256::from(1_000_000_000u64)),
+                        ..Default::default()
+                    })
+                    .unwrap()
+                }
+                "eth_getBlockByNumber" => serde_json::to_value(Block::<H256> {
+                    timestamp: U256::from(1_700_000_000u64),
+                    ..Default::default()
+                })
+                .unwrap(),
+                other => panic!("unexpected method {}", other),
+            };
+            Ok(serde_json::from_value(result).unwrap())
+        }
+    }
+
+    fn v1_sale_log(manager: Address, account: Address, tx_hash: H256, log_index: u64) -> Log {
+        let token = Address::repeat_byte(0xaa);
+        Log {
+            address: manager,
+            topics: vec![journal::TokenSaleV1Filter::signature()],
+            data: ethers::abi::encode(&[
+                ethers::abi::Token::Address(token),
+                ethers::abi::Token::Address(account),
+                ethers::abi::Token::Uint(U256::from(1000)),
+                ethers::abi::Token::Uint(U256::from(5)),
+            ])
+            .into(),
+            transaction_hash: Some(tx_hash),
+            block_number: Some(U64::from(10)),
+            log_index: Some(U256::from(log_index)),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn routed_trades_are_attributed_to_the_sending_wallet() {
+        let manager = Address::repeat_byte(1);
+        let wallet = Address::repeat_byte(2);
+        let helper = Address::repeat_byte(3);
+        let other = Address::repeat_byte(4);
+        let (direct, routed, foreign) = (H256::repeat_byte(0x10), H256::repeat_byte(0x11), H256::repeat_byte(0x12));
+
+        let rpc = WalletHistoryRpc {
+            logs: vec![
+                v1_sale_log(manager, wallet, direct, 0),
+                // 钱包经Helper3卖出，事件中的account是Helper3
+                v1_sale_log(manager, helper, routed, 1),
+                // 其他人经Helper3卖出
+                v1_sale_log(manager, helper, foreign, 2),
+            ],
+            senders: HashMap::from([(direct, wallet), (routed, wallet), (foreign, other)]),
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let trade_journal = TradeJournal::new(dir.path().join("trades.jsonl"));
+        let config = ImportConfig {
+            wallet,
+            token_managers: vec![manager],
+            routers: vec![helper],
+            from_block: 0,
+            to_block: Some(20),
+            block_chunk: 100,
+        };
+
+        let summary = import_wallet_history(Arc::new(Provider::new(rpc)), &config, &trade_journal).await.unwrap();
+        assert_eq!(summary.matched, 2);
+
+        let records = trade_journal.load().unwrap();
+        assert_eq!(records.iter().map(|r| r.tx_hash).collect::<Vec<_>>(), vec![direct, routed]);
+        assert!(records.iter().all(|r| r.wallet == wallet));
+        assert_eq!(records[1].gas_cost, U256::from(100_000u64) * U256::from(1_000_000_000u64));
+    }
+}
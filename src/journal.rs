@@ -0,0 +1,246 @@
+// 交易记录（Trade Journal）
+// 每一笔买入/卖出以一行JSON追加到本地文件，作为盈亏统计等功能的数据来源。
+// 记录来自TokenManager的TokenPurchase/TokenSale事件，可以在交易确认后直接写入，
+// 也可以通过交易哈希重新拉取receipt生成。
+
+use ethers::{
+    prelude::*,
+    types::{Address, H256, U256},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use crate::contracts::{TokenPurchaseFilter, TokenSaleFilter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+// 单笔成交记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeRecord {
+    pub side: TradeSide,
+    pub wallet: Address,
+    pub token: Address,
+    pub tx_hash: H256,
+    pub log_index: u64,
+    pub block_number: u64,
+    pub timestamp: u64,     // 区块时间（秒）
+    pub token_amount: U256, // 成交的代币数量
+    pub funds: U256,        // 成交的BNB数量（事件中的cost，不含平台手续费）
+    pub platform_fee: U256, // 平台手续费
+    pub gas_cost: U256,     // gas费用，只记在交易发送者的第一条记录上
+}
+
+impl TradeRecord {
+    // 实际现金流：买入为总支出，卖出为到手金额（都不含gas）
+    pub fn net_funds(&self) -> U256 {
+        match self.side {
+            TradeSide::Buy => self.funds + self.platform_fee,
+            TradeSide::Sell => self.funds.saturating_sub(self.platform_fee),
+        }
+    }
+}
+
+// TokenManager V1的成交事件，与V2同名但参数不同，单独声明
+#[derive(Debug, Clone, EthEvent)]
+#[ethevent(name = "TokenPurchase", abi = "TokenPurchase(address,address,uint256,uint256)")]
+pub struct TokenPurchaseV1Filter {
+    pub token: Address,
+    pub account: Address,
+    pub token_amount: U256,
+    pub ether_amount: U256,
+}
+
+#[derive(Debug, Clone, EthEvent)]
+#[ethevent(name = "TokenSale", abi = "TokenSale(address,address,uint256,uint256)")]
+pub struct TokenSaleV1Filter {
+    pub token: Address,
+    pub account: Address,
+    pub token_amount: U256,
+    pub ether_amount: U256,
+}
+
+// V1/V2成交事件的topic0，用于get_logs过滤
+pub fn trade_event_topics() -> Vec<H256> {
+    vec![
+        TokenPurchaseFilter::signature(),
+        TokenSaleFilter::signature(),
+        TokenPurchaseV1Filter::signature(),
+        TokenSaleV1Filter::signature(),
+    ]
+}
+
+// 解析单条日志，返回(方向, 代币, 账户, 代币数量, BNB数量, 平台手续费)
+// V1事件没有单独的手续费字段，手续费记为0
+pub fn decode_trade_log(log: &Log) -> Option<(TradeSide, Address, Address, U256, U256, U256)> {
+    let raw_log = ethers::abi::RawLog::from(log.clone());
+    if let Ok(event) = <TokenPurchaseFilter as EthEvent>::decode_log(&raw_log) {
+        Some((TradeSide::Buy, event.token, event.account, event.amount, event.cost, event.fee))
+    } else if let Ok(event) = <TokenSaleFilter as EthEvent>::decode_log(&raw_log) {
+        Some((TradeSide::Sell, event.token, event.account, event.amount, event.cost, event.fee))
+    } else if let Ok(event) = <TokenPurchaseV1Filter as EthEvent>::decode_log(&raw_log) {
+        Some((TradeSide::Buy, event.token, event.account, event.token_amount, event.ether_amount, U256::zero()))
+    } else if let Ok(event) = <TokenSaleV1Filter as EthEvent>::decode_log(&raw_log) {
+        Some((TradeSide::Sell, event.token, event.account, event.token_amount, event.ether_amount, U256::zero()))
+    } else {
+        None
+    }
+}
+
+// 交易的gas费用
+pub fn receipt_gas_cost(receipt: &TransactionReceipt) -> U256 {
+    receipt
+        .gas_used
+        .unwrap_or_default()
+        .saturating_mul(receipt.effective_gas_price.unwrap_or_default())
+}
+
+// 从交易receipt中解析成交记录
+pub async fn records_from_receipt<M: Middleware + 'static>(
+    client: &M,
+    receipt: &TransactionReceipt,
+) -> std::result::Result<Vec<TradeRecord>, Box<dyn std::error::Error>> {
+    let block_number = receipt.block_number.ok_or("receipt缺少区块号")?.as_u64();
+    let timestamp = client
+        .get_block(block_number)
+        .await?
+        .map(|block| block.timestamp.as_u64())
+        .unwrap_or_default();
+    let gas_cost = receipt_gas_cost(receipt);
+
+    let mut records = Vec::new();
+    let mut gas_assigned = false;
+
+    for log in &receipt.logs {
+        let Some((side, token, account, token_amount, funds, platform_fee)) = decode_trade_log(log) else {
+            continue;
+        };
+
+        // gas只由交易发送者承担
+        let record_gas = if !gas_assigned && account == receipt.from {
+            gas_assigned = true;
+            gas_cost
+        } else {
+            U256::zero()
+        };
+
+        records.push(TradeRecord {
+            side,
+            wallet: account,
+            token,
+            tx_hash: receipt.transaction_hash,
+            log_index: log.log_index.map(|i| i.as_u64()).unwrap_or_default(),
+            block_number,
+            timestamp,
+            token_amount,
+            funds,
+            platform_fee,
+            gas_cost: record_gas,
+        });
+    }
+
+    Ok(records)
+}
+
+// 通过交易哈希重新拉取receipt并解析成交记录
+pub async fn records_from_tx_hash<M: Middleware + 'static>(
+    client: &M,
+    tx_hash: H256,
+) -> std::result::Result<Vec<TradeRecord>, Box<dyn std::error::Error>> {
+    let receipt = client
+        .get_transaction_receipt(tx_hash)
+        .await?
+        .ok_or_else(|| format!("未找到交易receipt: {:?}", tx_hash))?;
+    records_from_receipt(client, &receipt).await
+}
+
+// 本地JSONL交易记录文件
+pub struct TradeJournal {
+    path: PathBuf,
+}
+
+impl TradeJournal {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    // 读取全部记录，按区块和日志顺序排列
+    pub fn load(&self) -> std::result::Result<Vec<TradeRecord>, Box<dyn std::error::Error>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let file = std::fs::File::open(&self.path)?;
+        let mut records = Vec::new();
+        for (line_no, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: TradeRecord = serde_json::from_str(&line)
+                .map_err(|e| format!("交易记录第{}行解析失败: {}", line_no + 1, e))?;
+            records.push(record);
+        }
+
+        records.sort_by_key(|r| (r.block_number, r.log_index));
+        Ok(records)
+    }
+
+    // 追加记录，已存在的(tx_hash, log_index)会被跳过，返回实际写入的条数
+    pub fn append(&self, records: &[TradeRecord]) -> std::result::Result<usize, Box<dyn std::error::Error>> {
+        let existing: HashSet<(H256, u64)> = self
+            .load()?
+            .into_iter()
+            .map(|r| (r.tx_hash, r.log_index))
+            .collect();
+
+        if let Some(parent) = self.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+
+        let mut written = 0;
+        for record in records {
+            if existing.contains(&(record.tx_hash, record.log_index)) {
+                continue;
+            }
+            writeln!(file, "{}", serde_json::to_string(record)?)?;
+            written += 1;
+        }
+
+        Ok(written)
+    }
+}
+
+// 交易确认后写入记录，失败时只打印警告，不影响交易流程；journal_path为None时不记录
+pub async fn record_receipt<M: Middleware + 'static>(
+    client: &M,
+    journal_path: Option<&Path>,
+    receipt: &TransactionReceipt,
+) {
+    let Some(journal_path) = journal_path else { return };
+    let result = async {
+        let records = records_from_receipt(client, receipt).await?;
+        TradeJournal::new(journal_path).append(&records)
+    }
+    .await;
+
+    match result {
+        Ok(written) => logln!("📒 已写入交易记录 {} 条: {}", written, journal_path.display()),
+        Err(e) => logln!("⚠️  写入交易记录失败: {}", e),
+    }
+}
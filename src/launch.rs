@@ -0,0 +1,153 @@
+// 发行经济预览
+// 在签名和调用Four.meme API之前，根据平台固定的发行参数和raisedToken配置，
+// 通过Helper3的calcInitialPrice取得初始价格，并用本地曲线模型估算：
+// 预购能买到的代币数量、占总供应量的比例，以及买空内盘（上线PancakeSwap）还需要的BNB。
+
+use ethers::{
+    prelude::*,
+    types::{Address, U256},
+    utils::{format_ether, parse_ether},
+};
+use std::sync::Arc;
+use serde::Serialize;
+
+use crate::curve::{BondingCurve, BuyQuote, CurveState};
+use crate::api::RaisedToken;
+use crate::contracts::ITokenManagerHelper3;
+
+const BPS: u64 = 10_000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LaunchEconomics {
+    pub total_supply: U256, // 代币数量均为1e18精度
+    pub offers: U256,       // 内盘可售数量
+    pub reserves: U256,     // 保留数量
+    pub max_raising: U256,  // 募集上限（wei）
+    pub buy_fee_bps: u64,
+    pub launch_fee: U256, // raisedToken.deployCost
+    pub quote_symbol: String,  // 募集代币
+    pub native_symbol: String, // 发行费币种
+}
+
+impl LaunchEconomics {
+    // 平台固定参数：总量（整数个代币）、募集上限（以募集代币计）、可售比例、保留比例
+    pub fn new(
+        total_supply: u64,
+        raised_amount: u32,
+        sale_rate: f64,
+        reserve_rate: f64,
+        raised_token: &RaisedToken,
+    ) -> std::result::Result<Self, Box<dyn std::error::Error>> {
+        let total_supply = parse_ether(total_supply)?;
+        let buy_fee: f64 = raised_token.buy_fee.parse()?;
+
+        Ok(Self {
+            total_supply,
+            offers: total_supply * U256::from((sale_rate * BPS as f64).round() as u64) / U256::from(BPS),
+            reserves: total_supply * U256::from((reserve_rate * BPS as f64).round() as u64) / U256::from(BPS),
+            max_raising: parse_ether(raised_amount)?,
+            buy_fee_bps: (buy_fee * BPS as f64).round() as u64,
+            launch_fee: parse_ether(raised_token.deploy_cost.as_str())?,
+            quote_symbol: raised_token.symbol.clone(),
+            native_symbol: raised_token.native_symbol.clone(),
+        })
+    }
+
+    // 刚发行时的曲线
+    pub fn initial_curve(&self) -> std::result::Result<BondingCurve, Box<dyn std::error::Error>> {
+        Ok(BondingCurve::new(CurveState {
+            offers: self.offers,
+            max_offers: self.offers,
+            funds: U256::zero(),
+            max_funds: self.max_raising,
+            trading_fee_rate: U256::from(self.buy_fee_bps),
+            min_trading_fee: U256::zero(),
+        })?)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LaunchPreview {
+    pub economics: LaunchEconomics,
+    pub initial_price: U256, // calcInitialPrice返回值
+    pub model_price: U256,   // 本地曲线的初始价格，用于核对
+    pub pre_sale_total: U256,
+    pub pre_sale: BuyQuote,
+    pub supply_share_bps: u64,
+    pub price_after_pre_sale: U256,
+    pub funds_to_graduate: U256, // 预购后买空内盘还需要进入曲线的资金
+    pub fee_to_graduate: U256,   // 对应的买入手续费
+}
+
+// 计算发行预览，只做只读调用，不签名、不发送交易、不登录Four.meme
+pub async fn preview_launch<M: Middleware + 'static>(
+    client: Arc<M>,
+    token_manager_helper_address: Address,
+    economics: LaunchEconomics,
+    pre_sale_total: U256,
+) -> std::result::Result<LaunchPreview, Box<dyn std::error::Error>> {
+    let helper = ITokenManagerHelper3::new(token_manager_helper_address, client);
+    let initial_price = helper
+        .calc_initial_price(economics.max_raising, economics.total_supply, economics.offers, economics.reserves)
+        .call()
+        .await?;
+
+    let curve = economics.initial_curve()?;
+    let pre_sale = curve.quote_buy(pre_sale_total);
+    let after = curve.after_buy(&pre_sale);
+    let funds_to_graduate = after.funds_to_graduate();
+
+    Ok(LaunchPreview {
+        initial_price,
+        model_price: curve.spot_price(),
+        pre_sale_total,
+        supply_share_bps: (pre_sale.token_amount * U256::from(BPS) / economics.total_supply).low_u64(),
+        price_after_pre_sale: after.spot_price(),
+        funds_to_graduate,
+        fee_to_graduate: after.fee_for(funds_to_graduate),
+        pre_sale,
+        economics,
+    })
+}
+
+pub fn print_preview(preview: &LaunchPreview) {
+    let economics = &preview.economics;
+    let quote = economics.quote_symbol.as_str();
+    logln!("\n📐 发行经济预览（未签名，未登录）");
+    logln!("  总供应量: {}", format_ether(economics.total_supply));
+    logln!("  内盘可售: {}，保留: {}", format_ether(economics.offers), format_ether(economics.reserves));
+    logln!("  募集上限: {} {}", format_ether(economics.max_raising), quote);
+    logln!("  买入手续费: {:.2}%", economics.buy_fee_bps as f64 / 100.0);
+    logln!("  发行费: {} {}", format_ether(economics.launch_fee), economics.native_symbol);
+    logln!("  初始价格(calcInitialPrice): {} {}", format_ether(preview.initial_price), quote);
+    logln!("  初始价格(本地曲线): {} {}", format_ether(preview.model_price), quote);
+
+    logln!("\n  预购: {} {}", format_ether(preview.pre_sale_total), quote);
+    logln!("    进入曲线: {} {}，手续费: {} {}", format_ether(preview.pre_sale.cost), quote, format_ether(preview.pre_sale.fee), quote);
+    logln!("    获得代币: {}", format_ether(preview.pre_sale.token_amount));
+    logln!("    占总供应量: {:.2}%", preview.supply_share_bps as f64 / 100.0);
+    logln!("    预购后价格: {} {}", format_ether(preview.price_after_pre_sale), quote);
+
+    logln!(
+        "\n  上线PancakeSwap还需: {} {}（另加手续费 {} {}）",
+        format_ether(preview.funds_to_graduate),
+        quote,
+        format_ether(preview.fee_to_graduate),
+        quote
+    );
+    if economics.quote_symbol == economics.native_symbol {
+        logln!(
+            "  创建交易预计支付: {} {}（发行费 + 预购）",
+            format_ether(economics.launch_fee + preview.pre_sale_total),
+            quote
+        );
+    } else {
+        logln!(
+            "  创建交易预计支付: {} {} 发行费 + {} {} 预购",
+            format_ether(economics.launch_fee),
+            economics.native_symbol,
+            format_ether(preview.pre_sale_total),
+            quote
+        );
+    }
+}
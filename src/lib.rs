@@ -0,0 +1,62 @@
+// Four.meme 交易库
+// 命令行工具（src/main.rs）只负责解析参数和输出，卖出、创建代币等流程都在这里，其他服务可以直接嵌入：
+// - api: Four.meme Web API客户端（登录、上传图片、创建代币签名、平台配置）
+// - contracts: TokenManager2/Helper3/ERC20/TokenTradeWithFee合约绑定
+// - trading: 卖出流程
+// - traffic: RPC和API流量的录制与回放
+// - venue: 交易场所抽象（TokenManager V1/V2、Helper3 ERC20计价、PancakeSwap），按代币自动选择
+// - creation: 创建代币并预购流程
+// - output: 命令输出格式（text/json），过程日志统一用 `logln!`
+// - logging: tracing结构化日志（级别、text/json格式、凭证脱敏）
+// 链上流程对任意带签名钱包的 `M: Middleware` 通用。
+
+use ethers::{
+    providers::Middleware,
+    types::{Address, U256},
+    utils::parse_ether,
+};
+
+// logln!宏需要在其他模块之前声明
+#[macro_use]
+pub mod output;
+
+pub mod api;
+pub mod candles;
+pub mod checkpoint;
+pub mod config;
+pub mod contracts;
+pub mod creation;
+pub mod curve;
+pub mod history;
+pub mod journal;
+pub mod launch;
+pub mod logging;
+pub mod manifest;
+pub mod metadata;
+pub mod platform_config;
+pub mod pnl;
+pub mod proxy;
+pub mod revenue;
+pub mod router;
+pub mod schedule;
+pub mod sell_fee;
+pub mod session;
+pub mod token_image;
+pub mod trading;
+pub mod traffic;
+pub mod venue;
+
+#[cfg(test)]
+mod mock_server;
+
+// 把十进制BNB字符串（如"0.2"）精确换算为wei
+pub fn parse_bnb_amount(field: &str, value: &str) -> std::result::Result<U256, Box<dyn std::error::Error>> {
+    parse_ether(value.trim()).map_err(|e| format!("{}不是有效的BNB数量: '{}' ({})", field, value, e).into())
+}
+
+// 发送交易的钱包地址，要求Middleware带签名钱包（如SignerMiddleware）
+pub fn sender<M: Middleware>(client: &M) -> std::result::Result<Address, Box<dyn std::error::Error>> {
+    client
+        .default_sender()
+        .ok_or_else(|| "Middleware没有配置签名钱包，无法发送交易".into())
+}
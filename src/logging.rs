@@ -0,0 +1,120 @@
+// 结构化日志（tracing）
+// 卖出、创建代币和Four.meme API的诊断信息通过tracing写到stderr，按操作划分span（sell、create、api.login），
+// 事件带token、wallet、tx_hash等字段；命令结果和人读的汇总仍然通过 logln!（见output.rs）输出。
+// - 级别：EnvFilter语法，如 "info"、"warn,fourmeme_auto_sell::api=debug"
+// - 格式：text（默认）或json（每行一个JSON对象，带当前span及其字段）
+// 凭证不进日志：register_secret登记的私钥、access_token在写出前统一替换为[REDACTED]，
+// 请求体另外按traffic的脱敏规则处理。
+
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::RwLock;
+use tracing_subscriber::EnvFilter;
+
+use crate::traffic::REDACTED;
+
+// 太短的值替换后会误伤正常日志，不登记
+const MIN_SECRET_LEN: usize = 8;
+
+static SECRETS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            other => Err(format!("未知的日志格式 '{}'，可选 text / json", other)),
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Text => "text",
+            Self::Json => "json",
+        })
+    }
+}
+
+// 安装全局subscriber，进程中只需调用一次
+pub fn init(level: &str, format: LogFormat) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let filter = EnvFilter::try_new(level.trim()).map_err(|e| format!("日志级别无效 '{}': {}", level, e))?;
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(|| RedactingWriter)
+        .with_target(false);
+    let result = match format {
+        LogFormat::Text => builder.try_init(),
+        LogFormat::Json => builder.json().flatten_event(true).with_current_span(true).with_span_list(false).try_init(),
+    };
+    result.map_err(|e| format!("初始化日志失败: {}", e).into())
+}
+
+// 登记需要从日志中抹掉的凭证（私钥、access_token等），0x前缀不影响匹配
+pub fn register_secret(secret: &str) {
+    let secret = secret.trim();
+    let secret = secret.strip_prefix("0x").unwrap_or(secret);
+    if secret.len() < MIN_SECRET_LEN {
+        return;
+    }
+    let mut secrets = SECRETS.write().unwrap_or_else(|e| e.into_inner());
+    if !secrets.iter().any(|s| s == secret) {
+        secrets.push(secret.to_string());
+    }
+}
+
+// 把已登记的凭证替换为[REDACTED]
+pub fn redact(text: &str) -> String {
+    let secrets = SECRETS.read().unwrap_or_else(|e| e.into_inner());
+    secrets
+        .iter()
+        .fold(text.to_string(), |text, secret| text.replace(secret.as_str(), REDACTED))
+}
+
+// 日志写到stderr，写出前替换已登记的凭证
+struct RedactingWriter;
+
+impl Write for RedactingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        std::io::stderr().write_all(redact(&String::from_utf8_lossy(buf)).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        std::io::stderr().flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registered_secrets_are_redacted() {
+        register_secret("0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318");
+        register_secret("mock-access-token-000042");
+        register_secret("short");
+
+        let line = "wallet=0xf39f key=4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318 \
+                    header=mock-access-token-000042 note=short";
+        assert_eq!(redact(line), "wallet=0xf39f key=[REDACTED] header=[REDACTED] note=short");
+    }
+
+    #[test]
+    fn parses_log_format() {
+        assert_eq!("JSON".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!("text".parse::<LogFormat>().unwrap(), LogFormat::Text);
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+}
@@ -3,6 +3,7 @@
 // 2. 内置Multicall提供了简单易用的API
 // 3. ITokenManager2接口提供了直接的卖出功能
 // 4. 新增Four.meme平台API支持，通过Web API创建代币
+// 交易和创建流程在库中（src/lib.rs），这里只解析命令行参数并输出结果；配置参数见 src/config.rs。
 
 use ethers::{
     prelude::*,
@@ -11,998 +12,734 @@ use ethers::{
     types::{Address, U256},
     utils::format_ether,
 };
-use reqwest;
-use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 use std::sync::Arc;
-use chrono::{Utc, Duration};
-use hex;
-
-// ================================
-// 配置参数 - 所有可配置的参数都在这里
-// ================================
-
-// 网络配置
-const CHAIN_ID: u64 = 56; // BSC链ID
-const RPC_URL: &str = "https://neat-practical-arrow.bsc.quiknode.pro/b2f485b14431f07a8e9e25951ad16fb364a0dd3a";
-
-// 钱包配置
-const PRIVATE_KEY: &str = "";
-
-// 合约地址配置
-const TOKEN_MANAGER2_ADDRESS: &str = "0x5c952063c7fc8610FFDB798152D69F0B9550762b"; // TokenManager2 V2
-const TOKEN_MANAGER_HELPER_ADDRESS: &str = "0xF251F83e40a78868FcfA3FA4599Dad6494E46034"; // Helper3
-const DEFAULT_TOKEN_ADDRESS: &str = "0xa61619c6569fcc0f8ecdd62854b2e452f3a84444"; // 用于卖出模式的默认代币
-
-// 创建代币配置
-const CREATE_TOKEN_NAME: &str = "狐链fox"; // 代币名称
-const CREATE_TOKEN_SYMBOL: &str = "狐链fox";
-const CREATE_TOKEN_DESC: &str = "BullCoin is a powerful cryptocurrency that embodies the strength of the bull market. Combining blockchain innovation with community engagement, BullCoin offers unique opportunities for investors. Inspired by the bullish spirit, it aims to revolutionize the digital economy while supporting both Bitcoin and Ethereum ecosystems."; // 描述信息
-const CREATE_TOKEN_LABEL: &str = "Meme"; // AI/Meme/Defi/Games/Infra/De-Sci/Social/Depin/Charity/Others
-const CREATE_TOKEN_WEB_URL: &str = "https://difipay.vercel.app";
-const CREATE_TOKEN_TWITTER_URL: &str = "";
-const CREATE_TOKEN_TELEGRAM_URL: &str = "";
-const CREATE_TOKEN_PRE_SALE: &str = "0.2"; // 创建代币时预购买的BNB数量，"0"表示不预购买
-const CREATE_TOKEN_IMAGE_PATH: &str = "image/狐链fox.jpg"; // 本地图片路径
-const BUY_AMOUNT_BNB: f64 = 0.2; // 购买代币的BNB数量
-
-// Four.meme API配置
-const FOURMEME_API_BASE_URL: &str = "https://four.meme/meme-api";
-const DEFAULT_NETWORK_CODE: &str = "BSC";
-const DEFAULT_WALLET_NAME: &str = "MetaMask";
-
-// ================================
-// 以下为结构体定义和函数实现
-// ================================
-
-// Four.meme API 相关结构体定义
-#[derive(Debug, Serialize, Deserialize)]
-struct NonceRequest {
-    #[serde(rename = "accountAddress")]
-    account_address: String,
-    #[serde(rename = "verifyType")]
-    verify_type: String,
-    #[serde(rename = "networkCode")]
-    network_code: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct NonceResponse {
-    code: i32,     // code是整数类型
-    msg: String,   // 添加msg字段
-    data: String,  // data是字符串类型（nonce值）
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct VerifyInfo {
-    address: String,
-    #[serde(rename = "networkCode")]
-    network_code: String,
-    signature: String,
-    #[serde(rename = "verifyType")]
-    verify_type: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct LoginRequest {
-    region: String,
-    #[serde(rename = "langType")]
-    lang_type: String,
-    #[serde(rename = "loginIp")]
-    login_ip: String,
-    #[serde(rename = "inviteCode")]
-    invite_code: String,
-    #[serde(rename = "verifyInfo")]
-    verify_info: VerifyInfo,
-    #[serde(rename = "walletName")]
-    wallet_name: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct LoginResponse {
-    code: i32,        // code是整数类型
-    msg: String,      // 添加msg字段
-    data: String,     // access_token
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct RaisedToken {
-    symbol: String,
-    #[serde(rename = "nativeSymbol")]
-    native_symbol: String,
-    #[serde(rename = "symbolAddress")]
-    symbol_address: String,
-    #[serde(rename = "deployCost")]
-    deploy_cost: String,
-    #[serde(rename = "buyFee")]
-    buy_fee: String,
-    #[serde(rename = "sellFee")]
-    sell_fee: String,
-    #[serde(rename = "minTradeFee")]
-    min_trade_fee: String,
-    #[serde(rename = "b0Amount")]
-    b0_amount: String,
-    #[serde(rename = "totalBAmount")]
-    total_b_amount: String,
-    #[serde(rename = "totalAmount")]
-    total_amount: String,
-    #[serde(rename = "logoUrl")]
-    logo_url: String,
-    #[serde(rename = "tradeLevel")]
-    trade_level: Vec<String>,
-    status: String,
-    #[serde(rename = "buyTokenLink")]
-    buy_token_link: String,
-    #[serde(rename = "reservedNumber")]
-    reserved_number: u32,
-    #[serde(rename = "saleRate")]
-    sale_rate: String,
-    #[serde(rename = "networkCode")]
-    network_code: String,
-    platform: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct CreateTokenRequest {
-    name: String,
-    #[serde(rename = "shortName")]
-    short_name: String,
-    desc: String,
-    #[serde(rename = "imgUrl")]
-    img_url: String,
-    #[serde(rename = "launchTime")]
-    launch_time: u64,
-    label: String,
-    #[serde(rename = "lpTradingFee")]
-    lp_trading_fee: f64,
-    #[serde(rename = "webUrl")]
-    web_url: String,
-    #[serde(rename = "twitterUrl")]
-    twitter_url: String,
-    #[serde(rename = "telegramUrl")]
-    telegram_url: String,
-    #[serde(rename = "preSale")]
-    pre_sale: String,
-    // 固定参数
-    #[serde(rename = "totalSupply")]
-    total_supply: u64,
-    #[serde(rename = "raisedAmount")]
-    raised_amount: u32,
-    #[serde(rename = "saleRate")]
-    sale_rate: f64,
-    #[serde(rename = "reserveRate")]
-    reserve_rate: f64,
-    #[serde(rename = "funGroup")]
-    fun_group: bool,
-    #[serde(rename = "clickFun")]
-    click_fun: bool,
-    symbol: String,
-    #[serde(rename = "raisedToken")]
-    raised_token: RaisedToken,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct CreateTokenResponse {
-    code: i32,                // code是整数类型
-    msg: String,              // 添加msg字段
-    data: CreateTokenData,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct CreateTokenData {
-    #[serde(rename = "tokenId")]
-    token_id: u64,
-    #[serde(rename = "totalAmount")]
-    total_amount: String,
-    #[serde(rename = "saleAmount")]
-    sale_amount: String,
-    template: u32,
-    #[serde(rename = "launchTime")]
-    launch_time: u64,
-    #[serde(rename = "serverTime")]
-    server_time: u64,
-    #[serde(rename = "createArg")]
-    create_arg: String,
-    signature: String,
-    bamount: String,  // 需要的BNB数量
-    tamount: String,  // 代币数量
-    #[serde(rename = "contractAddress")]
-    contract_address: Option<String>,  // 合约地址（可选，API可能不返回）
-}
+use std::time::Duration;
+use chrono::Utc;
+
+mod cli;
+
+use clap::Parser;
+use cli::Command;
+use fourmeme_auto_sell::api::{default_raised_token, CreateTokenParams, FourMemeApiClient};
+use fourmeme_auto_sell::config::*;
+use fourmeme_auto_sell::contracts::{ITokenManagerHelper3, IERC20};
+use fourmeme_auto_sell::creation::{create_token_and_buy, print_create_result};
+use fourmeme_auto_sell::sell_fee::{SellFeeConfig, SellFeeOverride};
+use fourmeme_auto_sell::token_image::ImageSource;
+use fourmeme_auto_sell::trading::execute_sell_process;
+use fourmeme_auto_sell::traffic::{self, Traffic, TrafficClient};
+use fourmeme_auto_sell::{
+    candles, curve, history, journal, launch, logging, logln, manifest, output, parse_bnb_amount, platform_config, pnl, proxy,
+    revenue, router, schedule,
+};
 
-// ERC20 ABI 简化版
-abigen!(
-    IERC20,
-    r#"[
-        function allowance(address owner, address spender) view returns (uint256)
-        function approve(address spender, uint256 amount) returns (bool)
-        function balanceOf(address account) view returns (uint256)
-        function transfer(address to, uint256 amount) returns (bool)
-        function transferFrom(address from, address to, uint256 amount) returns (bool)
-    ]"#,
-);
-
-// ITokenManager2 ABI - 更新后的方法签名，包含事件定义
-abigen!(
-    ITokenManager2,
-    r#"[
-        function sellToken(uint256 origin, address token, address from, uint256 amount, uint256 minFunds, uint256 feeRate, address feeRecipient) external
-        function buyTokenAMAP(address token, address to, uint256 funds, uint256 minAmount) external payable
-        function buyToken(address token, address to, uint256 amount, uint256 maxFunds) external payable
-        function createToken(bytes calldata createArg, bytes calldata sign) external payable returns (address token)
-        
-        event TokenCreated(address indexed token, address indexed creator, uint256 tokenId)
-        event Transfer(address indexed from, address indexed to, uint256 value)
-    ]"#,
-);
-
-// ITokenManagerHelper3 ABI - 用于预估和获取信息
-abigen!(
-    ITokenManagerHelper3,
-    r#"[
-        function getTokenInfo(address token) external view returns (uint256 version, address tokenManager, address quote, uint256 lastPrice, uint256 tradingFeeRate, uint256 minTradingFee, uint256 launchTime, uint256 offers, uint256 maxOffers, uint256 funds, uint256 maxFunds, bool liquidityAdded)
-        function tryBuy(address token, uint256 amount, uint256 funds) external view returns (address tokenManager, address quote, uint256 estimatedAmount, uint256 estimatedCost, uint256 estimatedFee, uint256 amountMsgValue, uint256 amountApproval, uint256 amountFunds)
-        function trySell(address token, uint256 amount) external view returns (address tokenManager, address quote, uint256 funds, uint256 fee)
-        function buyWithEth(uint256 origin, address token, address to, uint256 funds, uint256 minAmount) external payable
-        function sellForEth(uint256 origin, address token, uint256 amount, uint256 minFunds, uint256 feeRate, address feeRecipient) external
-        function calcInitialPrice(uint256 maxRaising, uint256 totalSupply, uint256 offers, uint256 reserves) external view returns (uint256 priceWei)
-    ]"#,
-);
-
-// 卖出代币的结果结构体
-#[derive(Debug)]
-struct SellResult {
-    token_sold: U256,
-    bnb_received: U256,
-    sell_tx_hash: H256,
-    block_number: Option<U256>,
-    approve_tx_hash: Option<H256>,
+// 募集代币：--raise-token > 环境变量CREATE_TOKEN_RAISE_TOKEN > CREATE_TOKEN_RAISE_SYMBOL
+fn raise_symbol(create_args: &cli::CreateArgs) -> String {
+    create_args
+        .raise_token
+        .clone()
+        .or_else(|| std::env::var("CREATE_TOKEN_RAISE_TOKEN").ok())
+        .unwrap_or_else(|| CREATE_TOKEN_RAISE_SYMBOL.to_string())
 }
 
-// 完整的卖出代币流程方法
-async fn execute_sell_process(
-    client: Arc<SignerMiddleware<Provider<Http>, LocalWallet>>,
-    token_address: Address,
-    token_manager_helper_address: Address,
-) -> std::result::Result<SellResult, Box<dyn std::error::Error>> {
-    // 创建合约实例
-    let token = IERC20::new(token_address, client.clone());
-    let token_manager_helper = ITokenManagerHelper3::new(token_manager_helper_address, client.clone());
-    let user_address = client.address();
-    
-    println!("开始卖出代币流程...");
-    
-    // 查询卖出前的余额
-    let before_token_balance = token.balance_of(user_address).call().await?;
-    let before_bnb_balance = client.get_balance(user_address, None).await?;
-    
-    println!("卖出前代币余额: {}", before_token_balance);
-    println!("卖出前BNB余额: {}", format_ether(before_bnb_balance));
-    
-    // 检查代币余额
-    if before_token_balance == U256::zero() {
-        return Err("没有代币可以卖出!".into());
-    }
-    
-    // 获取token信息和预估卖出结果
-    let token_info = token_manager_helper.get_token_info(token_address).call().await?;
-    let token_manager_address = token_info.1;
-    let quote = token_info.2;
-    let liquidity_added = token_info.11;
-    
-    println!("Token Manager: {:?}", token_manager_address);
-    println!("Quote Token: {:?}", quote);
-    println!("Liquidity Added: {}", liquidity_added);
-    
-    
-    // 预估卖出结果
-    let sell_estimate = token_manager_helper.try_sell(token_address, before_token_balance).call().await?;
-    let estimated_min_funds = sell_estimate.2;
-    let fee = sell_estimate.3;
-    println!("预估能获得BNB: {}", format_ether(estimated_min_funds));
-    println!("预估手续费: {}", format_ether(fee));
-    println!("TokenManager地址: {:?}", token_manager_address);
-    
-    // 检查授权并在必要时进行approve
-    let current_allowance = token.allowance(user_address, token_manager_address).call().await?;
-    println!("当前授权额度: {}", current_allowance);
-    println!("需要授权额度: {}", before_token_balance);
-    
-    let mut approve_tx_hash = None;
-    
-    if current_allowance < before_token_balance {
-        println!("🔹 步骤1: 发送approve交易");
-        
-        let approve_tx = token.approve(token_manager_address, before_token_balance);
-        let approve_pending = approve_tx.send().await?;
-        approve_tx_hash = Some(approve_pending.tx_hash());
-        println!("✅ approve交易已发送: {:?}", approve_tx_hash.unwrap());
-        
-        // 等待approve交易确认
-        let approve_receipt = approve_pending.await?.unwrap();
-        println!("✅ approve交易确认! 区块: {:?}", approve_receipt.block_number);
-        
-        // 检查新的授权额度
-        let new_allowance = token.allowance(user_address, token_manager_address).call().await?;
-        println!("新的授权额度: {}", new_allowance);
-    } else {
-        println!("✅ 授权已足够，直接执行卖出");
+// 手续费无法从成交事件推算时（Helper3 ERC20报价、PancakeSwap）显示未知
+fn format_fee(fee: Option<U256>) -> String {
+    match fee {
+        Some(fee) => format!("{} BNB", format_ether(fee)),
+        None => "未知".to_string(),
     }
-    
-    // 使用纯粹的卖币方法执行卖出操作
-    println!("\n使用纯粹卖币方法进行卖出...");
-    
-    let sell_result = sell_tokens_only(
-        client.clone(),
-        token_address,
-        token_manager_address,
-        before_token_balance,
-    ).await?;
-    
-    // 返回完整结果，包含approve信息
-    Ok(SellResult {
-        token_sold: sell_result.token_sold,
-        bnb_received: sell_result.bnb_received,
-        sell_tx_hash: sell_result.sell_tx_hash,
-        block_number: sell_result.block_number,
-        approve_tx_hash,
-    })
-}
-
-// 纯粹的卖币方法（不包含approve逻辑）
-async fn sell_tokens_only(
-    client: Arc<SignerMiddleware<Provider<Http>, LocalWallet>>,
-    token_address: Address,
-    token_manager_address: Address,
-    token_amount: U256,
-) -> std::result::Result<SellResult, Box<dyn std::error::Error>> {
-    let user_address = client.address();
-    
-    // 创建合约实例
-    let token_manager2 = ITokenManager2::new(token_manager_address, client.clone());
-    
-    // 卖出参数
-    let origin = 0u64;
-    let fee_rate = 0u64;
-    let fee_recipient: Address = "0xE1c727B62cF1ed816587E1005790f9E30299bf88".parse()?;
-    let min_funds = U256::zero();
-    
-    // 获取卖出前的BNB余额
-    let before_bnb_balance = client.get_balance(user_address, None).await?;
-    
-    println!("\n🚀 开始卖出代币...");
-    println!("代币数量: {}", token_amount);
-    
-    // 执行卖出交易
-    println!("🔹 发送sellToken交易");
-    
-    let sell_tx = token_manager2.sell_token(
-        U256::from(origin),
-        token_address,
-        user_address,
-        token_amount,
-        min_funds,
-        U256::from(fee_rate),
-        fee_recipient
-    );
-    
-    let sell_pending = sell_tx.send().await?;
-    let sell_tx_hash = sell_pending.tx_hash();
-    println!("✅ sellToken交易已发送: {:?}", sell_tx_hash);
-    
-    // 等待卖出交易确认
-    let sell_receipt = sell_pending.await?.unwrap();
-    println!("✅ sellToken交易确认! 区块: {:?}", sell_receipt.block_number);
-    
-    // 获取卖出后的BNB余额
-    let after_bnb_balance = client.get_balance(user_address, None).await?;
-    
-    // 计算卖出结果
-    let token_sold = token_amount; // 假设全部卖出成功
-    let bnb_received = after_bnb_balance.saturating_sub(before_bnb_balance);
-    
-    println!("🎉 卖出完成!");
-    println!("代币卖出数量: {}", token_sold);
-    println!("获得BNB数量: {}", format_ether(bnb_received));
-    
-    Ok(SellResult {
-        token_sold,
-        bnb_received,
-        sell_tx_hash,
-        block_number: sell_receipt.block_number.map(|n| U256::from(n.as_u64())),
-        approve_tx_hash: None, // 纯粹卖币方法不包含approve
-    })
-}
-
-// 创建代币并购买的结果结构体
-#[derive(Debug)]
-struct CreateAndBuyResult {
-    token_address: Address,
-    create_tx_hash: H256,
-    buy_tx_hash: H256,
-    approve_tx_hash: Option<H256>,
-    tokens_received: U256,
-    bnb_spent: U256,
-    creation_block: Option<U256>,
-    buy_block: Option<U256>,
-}
-
-// 创建代币参数结构体 - 更新为Four.meme API格式
-#[derive(Debug, Clone)]
-struct CreateTokenParams {
-    name: String,
-    short_name: String, // symbol
-    desc: String,
-    img_url: String,
-    launch_time: Option<u64>, // 可选，默认为当前时间+1小时
-    label: String, // AI/Meme/Defi/Games/Infra/De-Sci/Social/Depin/Charity/Others
-    web_url: Option<String>,
-    twitter_url: Option<String>,
-    telegram_url: Option<String>,
-    pre_sale: String, // 预购买的BNB数量，"0"表示不预购买
 }
 
-// Four.meme API客户端结构体
-struct FourMemeApiClient {
-    client: reqwest::Client,
-    base_url: String,
-    access_token: Option<String>,
-}
-
-impl FourMemeApiClient {
-    fn new() -> Self {
-        Self {
-            client: reqwest::Client::new(),
-            base_url: FOURMEME_API_BASE_URL.to_string(),
-            access_token: None,
-        }
-    }
-
-    // 1. 获取nonce
-    async fn get_nonce(&self, account_address: &str) -> std::result::Result<String, Box<dyn std::error::Error>> {
-        let nonce_request = NonceRequest {
-            account_address: account_address.to_string(),
-            verify_type: "LOGIN".to_string(),
-            network_code: DEFAULT_NETWORK_CODE.to_string(),
-        };
-
-        // 调试：打印实际发送的JSON
-        let json_body = serde_json::to_string(&nonce_request)?;
-        println!("发送的JSON: {}", json_body);
-
-        let response = self
-            .client
-            .post(&format!("{}/v1/private/user/nonce/generate", self.base_url))
-            .json(&nonce_request)
-            .send()
-            .await?;
-
-        let nonce_response: NonceResponse = response.json().await?;
-        
-        if nonce_response.code != 0 {
-            return Err(format!("获取nonce失败: {} - {}", nonce_response.code, nonce_response.msg).into());
-        }
-
-        Ok(nonce_response.data)
-    }
-
-    // 2. 用户登录
-    async fn login(&mut self, wallet: &LocalWallet, nonce: &str) -> std::result::Result<String, Box<dyn std::error::Error>> {
-        let account_address = format!("{:?}", wallet.address());
-        
-        // 签名消息
-        let message = format!("You are sign in Meme {}", nonce);
-        let signature = wallet.sign_message(message.as_bytes()).await?;
-        let signature_hex = format!("0x{}", hex::encode(signature.to_vec()));
-
-        let verify_info = VerifyInfo {
-            address: account_address.clone(),
-            network_code: DEFAULT_NETWORK_CODE.to_string(),
-            signature: signature_hex,
-            verify_type: "LOGIN".to_string(),
-        };
-
-        let login_request = LoginRequest {
-            region: "WEB".to_string(),
-            lang_type: "EN".to_string(),
-            login_ip: "".to_string(),
-            invite_code: "".to_string(),
-            verify_info,
-            wallet_name: DEFAULT_WALLET_NAME.to_string(),
-        };
-
-        let response = self
-            .client
-            .post(&format!("{}/v1/private/user/login/dex", self.base_url))
-            .json(&login_request)
-            .send()
-            .await?;
-
-        let login_response: LoginResponse = response.json().await?;
-        
-        if login_response.code != 0 {
-            return Err(format!("登录失败: {}", login_response.code).into());
-        }
-
-        self.access_token = Some(login_response.data.clone());
-        Ok(login_response.data)
-    }
-
-    // 3. 上传代币图片
-    async fn upload_image(&self) -> std::result::Result<String, Box<dyn std::error::Error>> {
-        let access_token = self.access_token.as_ref()
-            .ok_or("需要先登录获取access_token")?;
-
-        // 检查本地图片文件是否存在
-        let image_path = std::path::Path::new(CREATE_TOKEN_IMAGE_PATH);
-        if !image_path.exists() {
-            return Err(format!("图片文件不存在: {}", CREATE_TOKEN_IMAGE_PATH).into());
-        }
-
-        // 读取图片文件
-        let image_data = std::fs::read(image_path)?;
-        println!("  读取图片文件: {} ({}字节)", CREATE_TOKEN_IMAGE_PATH, image_data.len());
-
-        // 获取文件扩展名
-        let extension = image_path.extension()
-            .and_then(|ext| ext.to_str())
-            .unwrap_or("jpg");
-
-        // 生成随机文件名
-        let random_filename = format!("{:x}.{}", 
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos() % 0xffffffffffffffff, 
-            extension
-        );
-
-        // 创建multipart表单
-        let form = reqwest::multipart::Form::new()
-            .part("file", reqwest::multipart::Part::bytes(image_data)
-                .file_name(random_filename)
-                .mime_str(&format!("image/{}", extension))?);
-
-        // 发送上传请求 - 尝试不同的API端点
-        let response = self
-            .client
-            .post(&format!("{}/v1/private/token/upload", self.base_url))  
-            .header("meme-web-access", access_token)
-            .multipart(form)
-            .send()
-            .await?;
-
-        // 解析响应
-        let response_text = response.text().await?;
-        println!("  上传响应: {}", response_text);
-
-        // 解析JSON响应获取图片URL
-        if let Ok(upload_response) = serde_json::from_str::<serde_json::Value>(&response_text) {
-            if let Some(data) = upload_response.get("data") {
-                if let Some(url) = data.as_str() {
-                    println!("  ✅ 图片上传成功: {}", url);
-                    return Ok(url.to_string());
-                }
-            }
-        }
-        return Err(format!("图片上传失败").into());
+#[tokio::main]
+async fn main() {
+    let cli = cli::Cli::parse();
+    output::set_format(cli.output);
+    if let Err(e) = init_logging(&cli) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
     }
 
-    // 4. 创建代币
-    async fn create_token(&self, params: &CreateTokenParams) -> std::result::Result<CreateTokenData, Box<dyn std::error::Error>> {
-        let access_token = self.access_token.as_ref()
-            .ok_or("需要先登录获取access_token")?;
-
-        // 获取默认的raisedToken配置
-        let raised_token = RaisedToken {
-            symbol: "BNB".to_string(),
-            native_symbol: "BNB".to_string(),
-            symbol_address: "0xbb4cdb9cbd36b01bd1cbaebf2de08d9173bc095c".to_string(),
-            deploy_cost: "0".to_string(),
-            buy_fee: "0.01".to_string(),
-            sell_fee: "0.01".to_string(),
-            min_trade_fee: "0".to_string(),
-            b0_amount: "8".to_string(),
-            total_b_amount: "24".to_string(),
-            total_amount: "1000000000".to_string(),
-            logo_url: "https://static.four.meme/market/fc6c4c92-63a3-4034-bc27-355ea380a6795959172881106751506.png".to_string(),
-            trade_level: vec!["0.1".to_string(), "0.5".to_string(), "1".to_string()],
-            status: "PUBLISH".to_string(),
-            buy_token_link: "https://pancakeswap.finance/swap".to_string(),
-            reserved_number: 10,
-            sale_rate: "0.8".to_string(),
-            network_code: "BSC".to_string(),
-            platform: "MEME".to_string(),
-        };
-
-        let launch_time = params.launch_time.unwrap_or_else(|| {
-            (Utc::now() + Duration::hours(1)).timestamp_millis() as u64
-        });
-
-        let create_request = CreateTokenRequest {
-            name: params.name.clone(),
-            short_name: params.short_name.clone(),
-            desc: params.desc.clone(),
-            img_url: params.img_url.clone(),
-            launch_time,
-            label: params.label.clone(),
-            lp_trading_fee: 0.0025,
-            web_url: params.web_url.clone().unwrap_or_default(),
-            twitter_url: params.twitter_url.clone().unwrap_or_default(),
-            telegram_url: params.telegram_url.clone().unwrap_or_default(),
-            pre_sale: params.pre_sale.clone(),
-            // 固定参数
-            total_supply: 1000000000,
-            raised_amount: 24,
-            sale_rate: 0.8,
-            reserve_rate: 0.0,
-            fun_group: false,
-            click_fun: false,
-            symbol: "BNB".to_string(),
-            raised_token,
-        };
-
-        let response = self
-            .client
-            .post(&format!("{}/v1/private/token/create", self.base_url))
-            .header("meme-web-access", access_token)
-            .json(&create_request)
-            .send()
-            .await?;
-
-        // 先获取原始响应文本进行调试
-        let response_text = response.text().await?;
-        println!("🔍 创建代币API原始响应: {}", response_text);
-        
-        // 解析为JSON
-        let create_response: CreateTokenResponse = serde_json::from_str(&response_text)?;
-        
-        if create_response.code != 0 {
-            return Err(format!("创建代币失败: {} - {}", create_response.code, create_response.msg).into());
+    // json模式下错误也是一行JSON，方便脚本区分成功和失败；退出码均为1
+    if let Err(e) = run(cli).await {
+        if output::is_json() {
+            println!("{}", output::error_line(&e));
+        } else {
+            eprintln!("Error: {}", e);
         }
-
-        Ok(create_response.data)
+        std::process::exit(1);
     }
 }
 
-// 创建代币并购买的方法 - 使用Four.meme API
-async fn create_token_and_buy(
-    client: Arc<SignerMiddleware<Provider<Http>, LocalWallet>>,
-    token_manager_address: Address,
-    _token_manager_helper_address: Address,
-    create_params: CreateTokenParams,
-    _buy_amount_bnb: U256,
-) -> std::result::Result<CreateAndBuyResult, Box<dyn std::error::Error>> {
-    let user_address = client.address();
-    
-    // 创建合约实例
-    let token_manager2 = ITokenManager2::new(token_manager_address, client.clone());
-    
-    println!("🚀 开始创建代币流程...");
-    println!("代币名称: {}", create_params.name);
-    println!("代币符号: {}", create_params.short_name);
-    
-    // 获取创建前的BNB余额
-    let before_bnb_balance = client.get_balance(user_address, None).await?;
-    println!("创建前BNB余额: {}", format_ether(before_bnb_balance));
-    
-    // 步骤1: 通过Four.meme API创建代币
-    println!("\n🔹 步骤1: 通过Four.meme API创建代币");
-    
-    let mut api_client = FourMemeApiClient::new();
-    
-    // 1.1 获取nonce
-    println!("  获取nonce...");
-    let account_address = format!("{:?}", user_address);
-    let nonce = api_client.get_nonce(&account_address).await?;
-    println!("  ✅ nonce获取成功: {}", nonce);
-    
-    // 1.2 用户登录
-    println!("  用户登录...");
-    let _access_token = api_client.login(&client.signer(), &nonce).await?;
-    println!("  ✅ 登录成功，获得access_token");
-    
-    // 1.3 上传图片（使用默认图片）
-    println!("  上传代币图片...");
-    let img_url = api_client.upload_image().await?;
-    println!("  ✅ 图片上传成功: {}", &img_url);
-    
-    // 1.4 创建代币并获取签名参数
-    println!("  创建代币API调用...");
-    let mut api_params = create_params.clone();
-    api_params.img_url = img_url;
-    
-    let create_data = api_client.create_token(&api_params).await?;
-    println!("  ✅ API创建成功，获得签名参数");
-    
-    // 调试：打印完整的create_data对象
-    println!("  🔍 调试信息 - create_data对象:");
-    println!("    token_id: {}", create_data.token_id);
-    println!("    create_arg: {}", create_data.create_arg);
-    println!("    signature: {}", create_data.signature);
-    println!("    contract_address: {:?}", create_data.contract_address);
-    
-    if let Some(addr) = &create_data.contract_address {
-        println!("  合约地址: {}", addr);
-    } else {
-        println!("  ⚠️  合约地址: API未返回contract_address字段");
-    }
-    
-    // 步骤2: 调用区块链合约创建代币
-    println!("\n🔹 步骤2: 调用区块链合约创建代币");
-    
-    let create_arg_bytes = hex::decode(&create_data.create_arg.trim_start_matches("0x"))?;
-    let signature_bytes = hex::decode(&create_data.signature.trim_start_matches("0x"))?;
-    
-    // 使用预购BNB数量的110%作为创建代币的费用
-    let pre_sale_amount: f64 = CREATE_TOKEN_PRE_SALE.parse().unwrap_or(0.1);
-    let required_bnb: f64 = pre_sale_amount * 1.0; // 102%
-    let required_bnb_wei = U256::from((required_bnb * 1e18) as u64);
-    
-    println!("  预购BNB数量: {} BNB", pre_sale_amount);
-    println!("  创建代币需要BNB: {} BNB (预购数量的102%)", required_bnb);
+// 日志级别和格式：命令行参数 > 环境变量 > config中的默认值
+fn init_logging(cli: &cli::Cli) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let level = cli
+        .log_level
+        .clone()
+        .or_else(|| std::env::var("LOG_LEVEL").ok())
+        .unwrap_or_else(|| LOG_LEVEL.to_string());
+    let format = match cli.log_format {
+        Some(format) => format,
+        None => std::env::var("LOG_FORMAT").unwrap_or_else(|_| LOG_FORMAT.to_string()).parse()?,
+    };
+    logging::init(&level, format)
+}
 
-    let create_tx = token_manager2.create_token(
-        create_arg_bytes.into(),
-        signature_bytes.into()
-    ).value(required_bnb_wei); // 使用预购数量的102%
-    
-    let create_pending = create_tx.send().await?;
-    let create_tx_hash = create_pending.tx_hash();
-    println!("✅ 创建代币交易已发送: {:?}", create_tx_hash);
-    
-    // 等待创建交易确认
-    let create_receipt = create_pending.await?.unwrap();
-    println!("✅ 创建代币交易确认! 区块: {:?}", create_receipt.block_number);
-    
-    // 方法1: 尝试从合约调用的返回值中获取代币地址
-    // 注意：createToken函数声明返回address token，但通过交易receipt无法直接获取返回值
-    // 我们需要通过事件或日志来解析
-    
-    // 方法2: 从交易receipt的logs中解析出新创建的代币地址
-    let mut token_address = Address::zero();
-    
-    println!("🔍 分析交易日志 (共{}条):", create_receipt.logs.len());
-    
-    // 查找代币创建相关的日志
-    for (i, log) in create_receipt.logs.iter().enumerate() {
-        println!("  日志 {}: 地址 {:?}, topics数量: {}", i, log.address, log.topics.len());
-        
-        // 方法2a: 查找Transfer事件（通常新创建的代币会有mint transfer）
-        if log.topics.len() >= 3 {
-            // Transfer事件的签名: Transfer(address,address,uint256)
-            let transfer_sig = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
-            if format!("{:?}", log.topics[0]) == transfer_sig {
-                // 检查是否是从零地址的转账（mint操作）
-                let from_addr = Address::from(log.topics[1]);
-                if from_addr == Address::zero() {
-                    token_address = log.address;
-                    println!("    🎯 通过Transfer事件找到代币地址: {:?}", token_address);
-                    break;
-                }
-            }
-        }
-        
-        // 方法2b: 查找TokenCreated事件（如果存在）
-        if log.address == token_manager_address && log.topics.len() >= 2 {
-            // 可能是TokenCreated事件，第二个topic是代币地址
-            let potential_token = Address::from(log.topics[1]);
-            if potential_token != Address::zero() {
-                token_address = potential_token;
-                println!("    🎯 通过TokenManager事件找到代币地址: {:?}", token_address);
-                break;
-            }
-        }
-    }
-    
-    // 方法3: 如果还没找到，通过其他方式
-    if token_address == Address::zero() {
-        // 遍历所有非零地址的日志，找到最可能的代币地址
-        for log in &create_receipt.logs {
-            // 跳过已知的合约地址
-            if log.address != token_manager_address && log.address != Address::zero() {
-                // 检查这个地址是否看起来像ERC20代币
-                token_address = log.address;
-                println!("    💡 尝试使用日志地址作为代币地址: {:?}", token_address);
-                break;
-            }
+async fn run(cli: cli::Cli) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    // 1. 设置提供者和钱包
+    // RPC、链ID和私钥可通过环境变量覆盖，便于连接本地anvil节点测试
+    let rpc_url = std::env::var("RPC_URL").unwrap_or_else(|_| RPC_URL.to_string());
+    let chain_id = match std::env::var("CHAIN_ID") {
+        Ok(value) => value.trim().parse().map_err(|e| format!("CHAIN_ID无效 '{}': {}", value, e))?,
+        Err(_) => CHAIN_ID,
+    };
+    // 录制/回放（--record/--replay），Four.meme API客户端通过traffic::current()自动使用
+    let traffic = match (&cli.record, &cli.replay) {
+        (Some(path), _) => {
+            let path = path.clone().unwrap_or_else(Traffic::default_record_path);
+            let traffic = traffic::install(Traffic::record(&path)?);
+            logln!("⏺️  录制RPC和API流量到 {}", traffic.path().display());
+            Some(traffic)
         }
-    }
-    
-    if token_address == Address::zero() {
-        println!("⚠️  未能从交易日志中解析到代币地址，尝试其他方法...");
-        // 如果无法从日志解析，可以尝试调用合约查询或其他方法
-        return Ok(CreateAndBuyResult {
-            token_address: Address::zero(),
-            create_tx_hash,
-            buy_tx_hash: H256::zero(),
-            approve_tx_hash: None,
-            tokens_received: U256::zero(),
-            bnb_spent: required_bnb_wei,
-            creation_block: create_receipt.block_number.map(|n| U256::from(n.as_u64())),
-            buy_block: None,
-        });
-    }
-    
-    println!("\n🎉 代币创建完成!");
-    println!("📝 新创建的代币信息:"); 
-    println!("   代币地址: {:?}", token_address);
-    println!("   交易哈希: {:?}", create_tx_hash);
-    if let Some(block_number) = create_receipt.block_number {
-        println!("   区块号: {}", block_number);
-    }
-    
-    // 步骤3: 使用新创建的代币地址进行approve授权
-    println!("\n🔹 步骤3: 使用新代币地址进行approve授权");
-    
-    // 使用从区块链解析出的代币地址
-    let new_token = IERC20::new(token_address, client.clone());
-    
-    // 获取代币余额
-    let token_balance = new_token.balance_of(user_address).call().await?;
-    println!("代币余额: {}", token_balance);
-    
-    let mut approve_tx_hash = None;
-    
-    if token_balance > U256::zero() {
-        // 检查当前授权额度
-        let current_allowance = new_token.allowance(user_address, token_manager_address).call().await?;
-        println!("当前授权额度: {}", current_allowance);
-        
-        if current_allowance < token_balance {
-            println!("  发送approve交易...");
-            let approve_tx = new_token.approve(token_manager_address, token_balance);
-            let approve_pending = approve_tx.send().await?;
-            approve_tx_hash = Some(approve_pending.tx_hash());
-            println!("  ✅ approve交易已发送: {:?}", approve_tx_hash.unwrap());
-            
-            // 等待approve交易确认
-            let approve_receipt = approve_pending.await?.unwrap();
-            println!("  ✅ approve交易确认! 区块: {:?}", approve_receipt.block_number);
-            
-            // 检查新的授权额度
-            let new_allowance = new_token.allowance(user_address, token_manager_address).call().await?;
-            println!("  新的授权额度: {}", new_allowance);
-        } else {
-            println!("  ✅ 授权已足够，无需重新授权");
+        (None, Some(path)) => {
+            let traffic = traffic::install(Traffic::replay(path)?);
+            logln!("▶️  从 {} 回放RPC和API响应，不访问网络", traffic.path().display());
+            Some(traffic)
         }
-    } else {
-        println!("  ℹ️  代币余额为0，无需进行授权");
+        (None, None) => None,
+    };
+    let replaying = traffic.as_ref().is_some_and(|t| t.is_replay());
+    let mut provider = Provider::new(TrafficClient::new(Http::from_str(&rpc_url)?, traffic));
+    if replaying {
+        // 回放时等待交易确认不需要真实的轮询间隔
+        provider = provider.interval(Duration::from_millis(10));
     }
-    
-    // 返回包含实际代币地址的结果
-    Ok(CreateAndBuyResult {
-        token_address, // 使用从区块链解析出的代币地址
-        create_tx_hash,
-        buy_tx_hash: H256::zero(), // 没有购买交易
-        approve_tx_hash,
-        tokens_received: token_balance, // 返回实际代币余额
-        bnb_spent: required_bnb_wei, // 使用实际花费的BNB
-        creation_block: create_receipt.block_number.map(|n| U256::from(n.as_u64())),
-        buy_block: None, // 没有购买区块
-    })
-}
-
-#[tokio::main]
-async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
-    // 1. 设置提供者和钱包
-    let provider = Provider::<Http>::try_from(RPC_URL)?;
-    let mut wallet: LocalWallet = PRIVATE_KEY.parse()?;
+    let private_key = std::env::var("PRIVATE_KEY").unwrap_or_else(|_| PRIVATE_KEY.to_string());
+    logging::register_secret(&private_key);
+    let mut wallet: LocalWallet = private_key.parse()?;
     
     // 设置钱包的链ID
-    wallet = wallet.with_chain_id(CHAIN_ID);
+    wallet = wallet.with_chain_id(chain_id);
     
     let client = Arc::new(SignerMiddleware::new(provider, wallet));
     
     // 2. 合约地址
     // 从环境变量获取代币地址，如果没有则使用默认地址
     let token_address_str = std::env::var("TOKEN_ADDRESS").unwrap_or_else(|_| DEFAULT_TOKEN_ADDRESS.to_string());
-    println!("🔍 调试信息: 代币地址字符串 = '{}'", token_address_str);
+    tracing::debug!(token_address = %token_address_str, "代币地址配置");
     
-    let token_address: Address = match token_address_str.parse() {
-        Ok(addr) => addr,
-        Err(e) => {
-            eprintln!("❌ 解析代币地址失败: {} (地址: '{}')", e, token_address_str);
-            return Err(e.into());
-        }
-    };
+    let token_address: Address = token_address_str
+        .parse()
+        .map_err(|e| format!("解析代币地址失败: {} (地址: '{}')", e, token_address_str))?;
     
     let token_manager_helper_address: Address = TOKEN_MANAGER_HELPER_ADDRESS.parse()?;
     let token_manager2_address: Address = TOKEN_MANAGER2_ADDRESS.parse()?;
+
+    // 库使用的API地址和本地文件位置，API地址可通过环境变量FOURMEME_API_BASE_URL覆盖
+    let runtime = RuntimeConfig {
+        api_base_url: std::env::var("FOURMEME_API_BASE_URL").unwrap_or_else(|_| FOURMEME_API_BASE_URL.to_string()),
+        ..RuntimeConfig::default()
+    };
     
-    println!("📋 配置信息:");
-    println!("   代币地址: {:?}", token_address);
-    println!("   TokenManager2地址: {:?}", token_manager2_address);
-    println!("   TokenManagerHelper地址: {:?}", token_manager_helper_address);
-    
-    // 选择操作模式
-    let operation_mode = std::env::var("OPERATION_MODE").unwrap_or_else(|_| "SELL".to_string());
-    
-    match operation_mode.as_str() {
-        "CREATE_AND_BUY" => {
+    logln!("📋 配置信息:");
+    logln!("   代币地址: {:?}", token_address);
+    logln!("   TokenManager2地址: {:?}", token_manager2_address);
+    logln!("   TokenManagerHelper地址: {:?}", token_manager_helper_address);
+    
+    // 选择操作模式：优先使用子命令，否则读取OPERATION_MODE
+    let command = cli.command.unwrap_or_else(|| {
+        Command::from_operation_mode(&std::env::var("OPERATION_MODE").unwrap_or_else(|_| "SELL".to_string()))
+    });
+    
+    match command {
+        Command::Create(create_args) if create_args.preview => {
+            // 3. 发行经济预览模式
+            logln!("🎯 模式: 创建代币预览");
+
+            // 只读取公开配置，不登录
+            let api_client = FourMemeApiClient::new().with_base_url(&runtime.api_base_url);
+            let raised_tokens =
+                platform_config::load_raised_tokens(&api_client, runtime.platform_config_cache.as_deref(), PLATFORM_CONFIG_TTL_SECS).await;
+            let default_raise = raise_symbol(&create_args);
+
+            // 指定清单时逐个预览清单中的代币
+            let targets = match &create_args.manifest {
+                Some(path) => manifest::Manifest::load(path)?
+                    .tokens
+                    .into_iter()
+                    .map(|token| {
+                        let raise = token.raise_token.clone().unwrap_or_else(|| default_raise.clone());
+                        (token.name, token.symbol, token.pre_sale, raise)
+                    })
+                    .collect(),
+                None => vec![(
+                    CREATE_TOKEN_NAME.to_string(),
+                    CREATE_TOKEN_SYMBOL.to_string(),
+                    CREATE_TOKEN_PRE_SALE.to_string(),
+                    default_raise.clone(),
+                )],
+            };
+
+            for (name, symbol, pre_sale, raise) in targets {
+                let raised_token = platform_config::select_raised_token(&raised_tokens, &raise)?;
+                let economics = launch::LaunchEconomics::new(
+                    CREATE_TOKEN_TOTAL_SUPPLY,
+                    raised_token.raised_amount()?,
+                    CREATE_TOKEN_SALE_RATE,
+                    CREATE_TOKEN_RESERVE_RATE,
+                    &raised_token,
+                )?;
+                let pre_sale_total = parse_bnb_amount("preSale", &pre_sale)?;
+                logln!("\n代币名称: {}", name);
+                logln!("代币符号: {}", symbol);
+                let preview = launch::preview_launch(
+                    client.clone(),
+                    token_manager_helper_address,
+                    economics,
+                    pre_sale_total,
+                ).await?;
+                launch::print_preview(&preview);
+                output::emit("launch_preview", &serde_json::json!({ "name": name, "symbol": symbol, "preview": preview }))?;
+            }
+        },
+        Command::Create(ref create_args @ cli::CreateArgs { manifest: Some(ref manifest_path), .. }) => {
+            // 3. 按清单批量创建代币
+            logln!("🎯 模式: 按清单批量创建代币");
+
+            let manifest = manifest::Manifest::load(manifest_path)?;
+            let mut state = manifest::ManifestState::load(MANIFEST_STATE_DIR, manifest_path)?;
+            let default_raise = raise_symbol(create_args);
+            let raised_tokens = platform_config::load_raised_tokens(
+                &FourMemeApiClient::new().with_base_url(&runtime.api_base_url),
+                runtime.platform_config_cache.as_deref(),
+                PLATFORM_CONFIG_TTL_SECS,
+            ).await;
+            manifest.validate_pending(&state, &default_raise, &raised_tokens)?;
+            let total = manifest.tokens.len();
+            logln!("清单: {} (共{}个代币)", manifest_path.display(), total);
+            logln!("进度记录: {}", state.path().display());
+
+            let mut created = 0;
+            for (i, token) in manifest.tokens.iter().enumerate() {
+                if state.is_created(token) {
+                    logln!("\n⏭️  [{}/{}] {} 已创建，跳过", i + 1, total, token.key());
+                    created += 1;
+                    continue;
+                }
+
+                logln!("\n📦 [{}/{}] {}", i + 1, total, token.key());
+                let result = match token.to_params(&default_raise) {
+                    Ok(params) => create_token_and_buy(
+                        client.clone(),
+                        client.signer(),
+                        token_manager2_address,
+                        params,
+                        &token.image_source(),
+                        &runtime,
+                    ).await,
+                    Err(e) => Err(e),
+                };
+
+                match result {
+                    Ok(create_result) => {
+                        print_create_result(&create_result);
+                        output::emit("create", &create_result)?;
+                        state.record_created(
+                            token,
+                            create_result.token_address,
+                            create_result.create_tx_hash,
+                            format_ether(create_result.bnb_spent),
+                        )?;
+                        created += 1;
+                    }
+                    Err(e) => {
+                        state.record_failed(token, e.to_string())?;
+                        logln!("❌ {} 创建失败: {}", token.key(), e);
+                        logln!("已完成 {}/{}，修正后重新运行同一清单即可从该代币继续", created, total);
+                        return Err(e);
+                    }
+                }
+            }
+
+            logln!("\n🎉 清单全部完成: {}/{}", created, total);
+            output::emit("manifest", &serde_json::json!({ "manifest": manifest_path, "created": created, "total": total }))?;
+        },
+        Command::Create(create_args) => {
             // 3. 创建代币并购买模式
-            println!("🎯 模式: 创建代币并购买");
+            logln!("🎯 模式: 创建代币并购买");
+
+            // 指定--launch-at时定时发布，否则立即发布
+            let launch_time = match &create_args.launch_at {
+                Some(value) => schedule::parse_launch_at(value)?,
+                None => Utc::now().timestamp_millis() as u64,
+            };
+            let launch_buy_funds = match &create_args.buy_at_launch {
+                Some(value) => Some(parse_bnb_amount("--buy-at-launch", value)?),
+                None => None,
+            };
+            // 开盘买入附带BNB调用buyTokenAMAP，创建前就拒绝非BNB募集，避免创建后才失败
+            if launch_buy_funds.is_some() && !raise_symbol(&create_args).eq_ignore_ascii_case(&default_raised_token().symbol) {
+                return Err(format!("--buy-at-launch 只支持BNB募集的代币，当前募集代币为 {}", raise_symbol(&create_args)).into());
+            }
             
             let create_params = CreateTokenParams {
                 name: CREATE_TOKEN_NAME.to_string(),
                 short_name: CREATE_TOKEN_SYMBOL.to_string(),
                 desc: CREATE_TOKEN_DESC.to_string(),
                 img_url: String::new(), // 这将在create_token方法中通过upload_image更新
-                launch_time: Some(Utc::now().timestamp_millis() as u64), // 立即发布
-                label: CREATE_TOKEN_LABEL.to_string(),
+                launch_time: Some(launch_time),
+                label: CREATE_TOKEN_LABEL.parse()?,
                 web_url: Some(CREATE_TOKEN_WEB_URL.to_string()),
                 twitter_url: Some(CREATE_TOKEN_TWITTER_URL.to_string()),
                 telegram_url: Some(CREATE_TOKEN_TELEGRAM_URL.to_string()),
                 pre_sale: CREATE_TOKEN_PRE_SALE.to_string(),
+                raise_symbol: raise_symbol(&create_args),
             };
             
-            let buy_amount = U256::from((BUY_AMOUNT_BNB * 1e18) as u64); // 购买BNB的代币
+
+            let image_arg = std::env::var("CREATE_TOKEN_IMAGE").unwrap_or_else(|_| CREATE_TOKEN_IMAGE_PATH.to_string());
+            let image_source = if image_arg == "-" {
+                // 从标准输入读取图片
+                let mut data = Vec::new();
+                std::io::Read::read_to_end(&mut std::io::stdin(), &mut data)?;
+                ImageSource::Bytes { data, name: "stdin".to_string() }
+            } else {
+                ImageSource::parse(&image_arg)
+            };
             
             let create_result = create_token_and_buy(
                 client.clone(),
+                client.signer(),
                 token_manager2_address,
-                token_manager_helper_address,
                 create_params,
-                buy_amount,
+                &image_source,
+                &runtime,
             ).await?;
             
             // 显示创建和购买结果
-            println!("\n🎉 创建代币并购买完成汇总:");
-            println!("新代币地址: {:?}", create_result.token_address);
-            println!("创建交易哈希: {:?}", create_result.create_tx_hash);
-            println!("购买交易哈希: {:?}", create_result.buy_tx_hash);
-            if let Some(approve_hash) = create_result.approve_tx_hash {
-                println!("授权交易哈希: {:?}", approve_hash);
-            }
-            if let Some(creation_block) = create_result.creation_block {
-                println!("创建区块号: {:?}", creation_block);
-            }
-            if let Some(buy_block) = create_result.buy_block {
-                println!("购买区块号: {:?}", buy_block);
+            print_create_result(&create_result);
+            output::emit("create", &create_result)?;
+
+            let scheduled = create_result.launch_time_ms as i64 > create_result.server_clock.now_ms();
+            if scheduled || launch_buy_funds.is_some() {
+                logln!("\n⏰ 定时发布");
+                let launch_secs = schedule::verify_launch_time(
+                    client.clone(),
+                    token_manager_helper_address,
+                    create_result.token_address,
+                    create_result.launch_time_ms,
+                ).await?;
+
+                // 开盘买入需要提前一个区块开始盯盘
+                let lead_ms = if launch_buy_funds.is_some() { BSC_BLOCK_TIME_SECS as i64 * 1000 * 2 } else { 0 };
+                schedule::countdown(create_result.server_clock, launch_secs, lead_ms).await;
+
+                if let Some(funds) = launch_buy_funds {
+                    let buy = schedule::buy_at_launch(
+                        client.clone(),
+                        token_manager_helper_address,
+                        create_result.token_address,
+                        launch_secs,
+                        funds,
+                        LAUNCH_BUY_SLIPPAGE_PERCENT,
+                        &runtime,
+                    ).await?;
+                    logln!("开盘买入交易哈希: {:?}", buy.tx_hash);
+                    logln!("开盘买入区块: {:?} (时间戳 {})", buy.block_number, buy.block_timestamp);
+                    logln!("花费BNB: {}，最少获得代币: {}", format_ether(buy.funds), format_ether(buy.min_amount));
+                    output::emit("launch_buy", &buy)?;
+                }
             }
-            println!("获得代币数量: {}", create_result.tokens_received);
-            println!("花费BNB: {}", format_ether(create_result.bnb_spent));
 
-            // println!("\n使用纯粹卖币方法进行卖出...");
+            // logln!("\n使用纯粹卖币方法进行卖出...");
             
             // let sell_result = sell_tokens_only(
             //     client.clone(),
             //     create_result.token_address,
             //     token_manager2_address,
+            //     token_manager_helper_address,
             //     create_result.tokens_received,
             // ).await?;
             // // 4. 显示最终结果
-            // println!("\n📊 交易完成汇总:");
+            // logln!("\n📊 交易完成汇总:");
             // if let Some(approve_hash) = sell_result.approve_tx_hash {
-            //     println!("Approve交易哈希: {:?}", approve_hash);
+            //     logln!("Approve交易哈希: {:?}", approve_hash);
             // }
-            // println!("Sell交易哈希: {:?}", sell_result.sell_tx_hash);
+            // logln!("Sell交易哈希: {:?}", sell_result.sell_tx_hash);
             // if let Some(block_number) = sell_result.block_number {
-            //     println!("确认区块号: {:?}", block_number);
+            //     logln!("确认区块号: {:?}", block_number);
             // }
-            // println!("代币卖出数量: {}", sell_result.token_sold);
-            // println!("BNB收益: {}", format_ether(sell_result.bnb_received));
+            // logln!("代币卖出数量: {}", sell_result.token_sold);
+            // logln!("BNB收益: {}", format_ether(sell_result.bnb_received));
+        },
+        Command::Pnl => {
+            // 3. 盈亏统计模式
+            logln!("🎯 模式: 盈亏统计");
+
+            let trade_journal = journal::TradeJournal::new(TRADE_JOURNAL_PATH);
+
+            // 可通过PNL_TX_HASHES（逗号分隔）补录历史交易
+            if let Ok(tx_hashes) = std::env::var("PNL_TX_HASHES") {
+                for hash_str in tx_hashes.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                    let tx_hash: H256 = hash_str.parse()?;
+                    let records = journal::records_from_tx_hash(&client, tx_hash).await?;
+                    let written = trade_journal.append(&records)?;
+                    logln!("📒 交易 {:?}: 解析 {} 条成交，新增 {} 条", tx_hash, records.len(), written);
+                }
+            }
+
+            let records = trade_journal.load()?;
+            logln!("交易记录: {} 条 ({})", records.len(), TRADE_JOURNAL_PATH);
+
+            let method: pnl::CostBasisMethod = std::env::var("PNL_COST_BASIS")
+                .unwrap_or_else(|_| PNL_COST_BASIS.to_string())
+                .parse()?;
+
+            let mut positions = pnl::compute_positions(&records, method);
+            pnl::mark_to_market(client.clone(), token_manager_helper_address, &mut positions).await;
+            let report = pnl::build_report(method, positions);
+
+            pnl::print_report(&report);
+            pnl::export_csv(&report, PNL_CSV_PATH)?;
+            pnl::export_json(&report, PNL_JSON_PATH)?;
+            logln!("\n✅ 已导出: {} / {}", PNL_CSV_PATH, PNL_JSON_PATH);
+            output::emit("pnl", &pnl::report_json(&report))?;
+        },
+        Command::ImportHistory => {
+            // 3. 链上历史导入模式
+            logln!("🎯 模式: 导入链上历史成交");
+
+            // IMPORT_WALLET默认为当前钱包，IMPORT_FROM_BLOCK必填，IMPORT_TO_BLOCK默认最新区块
+            let wallet: Address = match std::env::var("IMPORT_WALLET") {
+                Ok(addr) => addr.parse()?,
+                Err(_) => client.address(),
+            };
+            let from_block: u64 = std::env::var("IMPORT_FROM_BLOCK")
+                .map_err(|_| "请设置IMPORT_FROM_BLOCK（起始区块）")?
+                .parse()?;
+            let to_block = match std::env::var("IMPORT_TO_BLOCK") {
+                Ok(block) => Some(block.parse()?),
+                Err(_) => None,
+            };
+
+            // Helper3和手续费路由代替钱包调用TokenManager，这些成交按交易发送者归属
+            let mut routers = vec![token_manager_helper_address];
+            let proxy_router = std::env::var("PROXY_ROUTER_ADDRESS").unwrap_or_else(|_| PROXY_ROUTER_ADDRESS.to_string());
+            if !proxy_router.trim().is_empty() {
+                routers.push(proxy_router.trim().parse()?);
+            }
+            routers.extend(router::load_deployments(ROUTER_DEPLOYMENTS_PATH)?.iter().map(|d| d.address));
+            routers.sort();
+            routers.dedup();
+
+            let import_config = history::ImportConfig {
+                wallet,
+                token_managers: vec![TOKEN_MANAGER_V1_ADDRESS.parse()?, token_manager2_address],
+                routers,
+                from_block,
+                to_block,
+                block_chunk: HISTORY_BLOCK_CHUNK,
+            };
+
+            logln!("钱包: {:?}", wallet);
+            let trade_journal = journal::TradeJournal::new(TRADE_JOURNAL_PATH);
+            let summary = history::import_wallet_history(client.clone(), &import_config, &trade_journal).await?;
+
+            logln!("\n✅ 导入完成: 区块 {}-{}，共 {} 页", summary.from_block, summary.to_block, summary.pages);
+            logln!("本钱包成交: {} 条，新增记录: {} 条", summary.matched, summary.written);
+
+            // 根据完整交易记录重建当前持仓
+            let records = trade_journal.load()?;
+            let positions = pnl::compute_positions(&records, PNL_COST_BASIS.parse()?);
+            logln!("\n📋 当前持仓:");
+            let mut holdings = Vec::new();
+            for position in positions.iter().filter(|p| p.wallet == wallet && !p.position.is_zero()) {
+                logln!(
+                    "  代币 {:?}: 持仓 {}，成本 {} BNB",
+                    position.token,
+                    position.position,
+                    format_ether(position.cost_basis)
+                );
+                holdings.push(serde_json::json!({
+                    "token": position.token,
+                    "position": position.position,
+                    "cost_basis": position.cost_basis,
+                }));
+            }
+            output::emit("import_history", &serde_json::json!({ "wallet": wallet, "summary": summary, "positions": holdings }))?;
         },
-        "SELL" | _ => {
+        Command::Candles => {
+            // 3. K线索引模式
+            logln!("🎯 模式: 构建K线");
+
+            // 首次索引需要CANDLES_FROM_BLOCK，之后从上次的区块继续；
+            // 设置CANDLES_WATCH_SECS后按间隔持续索引
+            let from_block = match std::env::var("CANDLES_FROM_BLOCK") {
+                Ok(block) => Some(block.parse()?),
+                Err(_) => None,
+            };
+            let watch_secs: Option<u64> = match std::env::var("CANDLES_WATCH_SECS") {
+                Ok(secs) => Some(secs.parse()?),
+                Err(_) => None,
+            };
+
+            let token_manager_helper = ITokenManagerHelper3::new(token_manager_helper_address, client.clone());
+            let token_info = token_manager_helper.get_token_info(token_address).call().await?;
+            let token_manager_address = token_info.1;
+            let store = candles::CandleStore::new(CANDLES_DIR, token_address);
+
+            loop {
+                let new_trades = candles::index_token(
+                    &client,
+                    token_manager_address,
+                    token_address,
+                    from_block,
+                    CANDLES_BLOCK_CHUNK,
+                    &store,
+                ).await?;
+                logln!("\n✅ 新增成交 {} 笔", new_trades);
+
+                let mut latest = Vec::new();
+                for interval in [candles::CandleInterval::OneMinute, candles::CandleInterval::FiveMinutes] {
+                    let series = store.load_candles(interval)?;
+                    if let Some(last) = series.last() {
+                        logln!(
+                            "[{}] 最新K线 {}: 开 {} 高 {} 低 {} 收 {} 量 {} BNB 笔数 {}",
+                            interval.label(),
+                            last.open_time,
+                            last.open,
+                            last.high,
+                            last.low,
+                            last.close,
+                            format_ether(last.volume_bnb),
+                            last.trades
+                        );
+                    }
+                    if let Some(m) = candles::momentum(&series, 12) {
+                        logln!(
+                            "[{}] 最近{}根: 涨跌 {:.2}%，成交 {} BNB / {} 笔，买入占比 {:.0}%",
+                            interval.label(),
+                            m.candles,
+                            m.change_pct,
+                            format_ether(m.volume_bnb),
+                            m.trades,
+                            m.buy_ratio * 100.0
+                        );
+                    }
+                    latest.push(serde_json::json!({
+                        "interval": interval.label(),
+                        "last": series.last(),
+                        "momentum": candles::momentum(&series, 12),
+                    }));
+                }
+                output::emit("candles", &serde_json::json!({ "token": token_address, "new_trades": new_trades, "intervals": latest }))?;
+
+                match watch_secs {
+                    Some(secs) => tokio::time::sleep(std::time::Duration::from_secs(secs)).await,
+                    None => break,
+                }
+            }
+        },
+        Command::Proxy(proxy_args) => {
+            // 3. 代理路由模式：通过TokenTradeWithFee买卖或管理手续费
+            logln!("🎯 模式: TokenTradeWithFee代理路由");
+
+            let router_str = proxy_args
+                .router
+                .or_else(|| std::env::var("PROXY_ROUTER_ADDRESS").ok())
+                .unwrap_or_else(|| PROXY_ROUTER_ADDRESS.to_string());
+            if router_str.trim().is_empty() {
+                return Err("未配置路由合约地址：使用--router、环境变量PROXY_ROUTER_ADDRESS或常量PROXY_ROUTER_ADDRESS".into());
+            }
+            let router_address: Address = router_str
+                .trim()
+                .parse()
+                .map_err(|e| format!("路由合约地址无效 '{}': {}", router_str, e))?;
+
+            match proxy_args.action {
+                cli::ProxyAction::Fee => {
+                    let config = proxy::fee_config(client.clone(), router_address).await?;
+                    proxy::print_fee_config(router_address, &config);
+                    output::emit("proxy_fee", &serde_json::json!({ "router": router_address, "config": config }))?;
+                }
+                cli::ProxyAction::SetFee { rate, recipient } => {
+                    let recipient: Address = recipient.trim().parse()?;
+                    proxy::set_fee(client.clone(), router_address, rate, recipient).await?;
+                    let config = proxy::fee_config(client.clone(), router_address).await?;
+                    proxy::print_fee_config(router_address, &config);
+                    output::emit("proxy_fee", &serde_json::json!({ "router": router_address, "config": config }))?;
+                }
+                cli::ProxyAction::Index { from_block } => {
+                    let store = revenue::RouterStore::new(ROUTER_INDEX_DIR, router_address);
+                    let new_events = revenue::index_router(&client, router_address, from_block, ROUTER_BLOCK_CHUNK, &store).await?;
+                    logln!("\n✅ 新增路由事件 {} 个", new_events);
+                    output::emit("router_index", &serde_json::json!({ "router": router_address, "new_events": new_events }))?;
+                }
+                cli::ProxyAction::Report => {
+                    let store = revenue::RouterStore::new(ROUTER_INDEX_DIR, router_address);
+                    let report = revenue::build_report(&store.load_events()?, &store.load_balances()?);
+                    revenue::print_report(&report);
+                    revenue::export_json(&report, store.report_path())?;
+                    logln!("\n📁 报告已导出: {}", store.report_path().display());
+                    output::emit("revenue_report", &report)?;
+                }
+                cli::ProxyAction::Buy { amount } => {
+                    let amount = amount.unwrap_or_else(|| BUY_AMOUNT_BNB.to_string());
+                    let funds = parse_bnb_amount("买入金额", &amount)?;
+                    let buy_result = proxy::buy(client.clone(), router_address, token_address, funds, PROXY_SLIPPAGE_PERCENT, &runtime).await?;
+
+                    logln!("\n📊 交易完成汇总:");
+                    logln!("Buy交易哈希: {:?}", buy_result.tx_hash);
+                    logln!("确认区块号: {:?}", buy_result.block_number);
+                    logln!("花费BNB: {}", format_ether(buy_result.bnb_spent));
+                    logln!("路由手续费: {} BNB", format_ether(buy_result.fee_paid));
+                    logln!("获得代币: {} (最少 {})", buy_result.tokens_received, buy_result.min_amount);
+                    output::emit("proxy_buy", &buy_result)?;
+                }
+                cli::ProxyAction::Sell => {
+                    let sell_result = proxy::sell(client.clone(), router_address, token_address, PROXY_SLIPPAGE_PERCENT, &runtime).await?;
+
+                    logln!("\n📊 交易完成汇总:");
+                    if let Some(approve_hash) = sell_result.approve_tx_hash {
+                        logln!("Approve交易哈希: {:?}", approve_hash);
+                    }
+                    logln!("Sell交易哈希: {:?}", sell_result.sell_tx_hash);
+                    if let Some(block_number) = sell_result.block_number {
+                        logln!("确认区块号: {:?}", block_number);
+                    }
+                    logln!("代币卖出数量: {}", sell_result.token_sold);
+                    logln!("BNB收益: {}", format_ether(sell_result.bnb_received));
+                    logln!("平台手续费: {}", format_fee(sell_result.platform_fee));
+                    logln!("路由手续费: {}", format_fee(sell_result.third_party_fee));
+                    output::emit("proxy_sell", &sell_result)?;
+                }
+            }
+        },
+        Command::Router(router_args) => {
+            // 3. 部署TokenTradeWithFee路由
+            let cli::RouterAction::Deploy { fee_recipient, fee_rate, helper, artifact } = router_args.action;
+            logln!("🎯 模式: 部署TokenTradeWithFee路由");
+
+            let fee_recipient: Address = fee_recipient.trim().parse()?;
+            let helper: Address = match helper {
+                Some(helper) => helper.trim().parse()?,
+                None => token_manager_helper_address,
+            };
+            router::validate_deploy_params(helper, fee_recipient, fee_rate)?;
+
+            let artifact = artifact.unwrap_or_else(|| ROUTER_ARTIFACT_PATH.into());
+            let bytecode = router::resolve_bytecode(&artifact, ROUTER_SOURCE_PATH)?;
+            let deployment = router::deploy(client.clone(), bytecode, helper, fee_recipient, fee_rate).await?;
+            router::record_deployment(ROUTER_DEPLOYMENTS_PATH, &deployment)?;
+
+            logln!("\n📊 部署完成:");
+            router::print_deployment(&deployment);
+            logln!("📒 已写入部署记录: {}", ROUTER_DEPLOYMENTS_PATH);
+            logln!("  使用: PROXY_ROUTER_ADDRESS={:?} cargo run -- proxy fee", deployment.address);
+            output::emit("router_deployment", &deployment)?;
+        },
+        Command::Quote => {
+            // 3. 本地曲线报价模式
+            logln!("🎯 模式: 本地联合曲线报价");
+
+            let token_manager_helper = ITokenManagerHelper3::new(token_manager_helper_address, client.clone());
+            let token_info = token_manager_helper.get_token_info(token_address).call().await?;
+            let curve = curve::BondingCurve::new(curve::CurveState::from_token_info(&token_info))?;
+
+            logln!("现价: {} BNB / 1e18代币", format_ether(curve.spot_price()));
+            logln!("虚拟BNB储备: {} BNB", format_ether(curve.virtual_funds()));
+            logln!("内盘剩余代币: {}", curve.state().offers);
+            logln!("距离上线还需募集: {} BNB", format_ether(curve.funds_to_graduate()));
+
+            // 买入报价，同时与链上tryBuy对比
+            let buy_funds = parse_bnb_amount("BUY_AMOUNT_BNB", BUY_AMOUNT_BNB)?;
+            let buy_quote = curve.quote_buy(buy_funds);
+            let (_, _, chain_amount, chain_cost, chain_fee, _, _, _) =
+                token_manager_helper.try_buy(token_address, U256::zero(), buy_funds).call().await?;
+            logln!("\n买入 {} BNB:", format_ether(buy_funds));
+            logln!("  本地: 代币 {}，成本 {} BNB，手续费 {} BNB，价格影响 {:.2}%",
+                buy_quote.token_amount, format_ether(buy_quote.cost), format_ether(buy_quote.fee),
+                buy_quote.price_impact_bps as f64 / 100.0);
+            logln!("  链上: 代币 {}，成本 {} BNB，手续费 {} BNB",
+                chain_amount, format_ether(chain_cost), format_ether(chain_fee));
+            logln!("  买入后现价: {} BNB / 1e18代币", format_ether(curve.after_buy(&buy_quote).spot_price()));
+
+            let mut quote = serde_json::json!({
+                "token": token_address,
+                "spot_price": curve.spot_price(),
+                "virtual_funds": curve.virtual_funds(),
+                "offers": curve.state().offers,
+                "funds_to_graduate": curve.funds_to_graduate(),
+                "buy": {
+                    "funds": buy_funds,
+                    "local": buy_quote,
+                    "chain": { "token_amount": chain_amount, "cost": chain_cost, "fee": chain_fee },
+                },
+            });
+
+            // 卖出报价：按当前钱包持仓
+            let token = IERC20::new(token_address, client.clone());
+            let balance = token.balance_of(client.address()).call().await?;
+            if balance > U256::zero() {
+                let sell_quote = curve.quote_sell(balance);
+                let (_, _, chain_funds, chain_sell_fee) = token_manager_helper.try_sell(token_address, balance).call().await?;
+                logln!("\n卖出全部持仓 {}:", balance);
+                logln!("  本地: 资金 {} BNB，手续费 {} BNB，到手 {} BNB，价格影响 {:.2}%",
+                    format_ether(sell_quote.funds), format_ether(sell_quote.fee), format_ether(sell_quote.net),
+                    sell_quote.price_impact_bps as f64 / 100.0);
+                logln!("  链上: 资金 {} BNB，手续费 {} BNB", format_ether(chain_funds), format_ether(chain_sell_fee));
+                quote["sell"] = serde_json::json!({
+                    "token_amount": balance,
+                    "local": sell_quote,
+                    "chain": { "funds": chain_funds, "fee": chain_sell_fee },
+                });
+            }
+            output::emit("quote", &quote)?;
+        },
+        Command::Sell(sell_args) => {
             // 3. 卖出代币模式（默认）
-            println!("🎯 模式: 卖出现有代币");
+            logln!("🎯 模式: 卖出现有代币");
+
+            let base_fee_config = SellFeeConfig {
+                origin: U256::from(SELL_ORIGIN),
+                fee_rate_bps: SELL_FEE_RATE_BPS,
+                fee_recipient: SELL_FEE_RECIPIENT.parse()?,
+            };
+            let cli_fee = SellFeeOverride {
+                origin: sell_args.origin,
+                fee_rate_bps: sell_args.fee_rate_bps,
+                fee_recipient: sell_args.fee_recipient.as_deref().map(str::parse).transpose()?,
+            };
+            let fee_config = SellFeeConfig::resolve(
+                base_fee_config,
+                SELL_FEE_CONFIG_PATH,
+                client.address(),
+                &SellFeeOverride::from_env()?,
+                &cli_fee,
+            )?;
             
             let sell_result = execute_sell_process(
                 client.clone(),
                 token_address,
                 token_manager_helper_address,
+                &fee_config,
+                &runtime,
             ).await?;
             
             // 4. 显示最终结果
-            println!("\n📊 交易完成汇总:");
+            logln!("\n📊 交易完成汇总:");
             if let Some(approve_hash) = sell_result.approve_tx_hash {
-                println!("Approve交易哈希: {:?}", approve_hash);
+                logln!("Approve交易哈希: {:?}", approve_hash);
             }
-            println!("Sell交易哈希: {:?}", sell_result.sell_tx_hash);
+            logln!("Sell交易哈希: {:?}", sell_result.sell_tx_hash);
             if let Some(block_number) = sell_result.block_number {
-                println!("确认区块号: {:?}", block_number);
+                logln!("确认区块号: {:?}", block_number);
             }
-            println!("代币卖出数量: {}", sell_result.token_sold);
-            println!("BNB收益: {}", format_ether(sell_result.bnb_received));
+            logln!("代币卖出数量: {}", sell_result.token_sold);
+            logln!("BNB收益: {}", format_ether(sell_result.bnb_received));
+            logln!("平台手续费: {}", format_fee(sell_result.platform_fee));
+            logln!("第三方手续费: {}", format_fee(sell_result.third_party_fee));
+            output::emit("sell", &sell_result)?;
         }
     }
 
@@ -0,0 +1,409 @@
+// 批量创建代币清单
+// `create --manifest tokens.yaml` 按顺序为清单中的每个代币执行 API + createToken 流程。
+// 每个代币完成或失败后立即写入状态文件 data/manifests/<清单文件名>.state.json，
+// 中断后重新运行会跳过已创建的代币，从第一个未完成的代币继续。
+
+use chrono::{DateTime, Utc};
+use ethers::types::{Address, H256};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::api::{CreateTokenParams, RaisedToken};
+use crate::metadata::{validate_params, TokenLabel};
+use crate::platform_config::select_raised_token;
+use crate::token_image::ImageSource;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Manifest {
+    pub tokens: Vec<ManifestToken>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManifestToken {
+    pub name: String,
+    pub symbol: String,
+    #[serde(alias = "desc")]
+    pub description: String,
+    #[serde(default = "default_label")]
+    pub label: TokenLabel,
+    pub image: String, // 本地路径或URL
+    #[serde(default)]
+    pub website: Option<String>,
+    #[serde(default)]
+    pub twitter: Option<String>,
+    #[serde(default)]
+    pub telegram: Option<String>,
+    #[serde(default = "default_pre_sale")]
+    pub pre_sale: String, // BNB数量，"0"表示不预购
+    #[serde(default)]
+    pub launch_time: Option<String>, // RFC3339时间或毫秒时间戳，不填为立即发布
+    #[serde(default)]
+    pub raise_token: Option<String>, // 募集代币符号，不填使用命令行/默认配置
+}
+
+fn default_label() -> TokenLabel {
+    TokenLabel::Meme
+}
+
+fn default_pre_sale() -> String {
+    "0".to_string()
+}
+
+impl ManifestToken {
+    // 状态文件中的唯一标识
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.name, self.symbol)
+    }
+
+    pub fn launch_time_millis(&self) -> std::result::Result<u64, Box<dyn std::error::Error>> {
+        let Some(value) = self.launch_time.as_deref().map(str::trim).filter(|v| !v.is_empty()) else {
+            return Ok(Utc::now().timestamp_millis() as u64);
+        };
+        if let Ok(millis) = value.parse::<u64>() {
+            return Ok(millis);
+        }
+        let time = DateTime::parse_from_rfc3339(value)
+            .map_err(|e| format!("{}: launch_time格式错误 '{}' ({})", self.key(), value, e))?;
+        Ok(time.timestamp_millis() as u64)
+    }
+
+    pub fn to_params(&self, default_raise: &str) -> std::result::Result<CreateTokenParams, Box<dyn std::error::Error>> {
+        Ok(CreateTokenParams {
+            name: self.name.clone(),
+            short_name: self.symbol.clone(),
+            desc: self.description.clone(),
+            img_url: String::new(), // 上传图片后填入
+            launch_time: Some(self.launch_time_millis()?),
+            label: self.label,
+            web_url: self.website.clone(),
+            twitter_url: self.twitter.clone(),
+            telegram_url: self.telegram.clone(),
+            pre_sale: self.pre_sale.clone(),
+            raise_symbol: self.raise_token.clone().unwrap_or_else(|| default_raise.to_string()),
+        })
+    }
+
+    pub fn image_source(&self) -> ImageSource {
+        ImageSource::parse(&self.image)
+    }
+}
+
+impl Manifest {
+    pub fn load(path: impl AsRef<Path>) -> std::result::Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| format!("读取清单失败 {}: {}", path.display(), e))?;
+        let manifest: Manifest = serde_yaml::from_str(&content).map_err(|e| format!("清单格式错误 {}: {}", path.display(), e))?;
+
+        if manifest.tokens.is_empty() {
+            return Err(format!("清单中没有代币: {}", path.display()).into());
+        }
+        let mut seen = std::collections::HashSet::new();
+        for token in &manifest.tokens {
+            if !seen.insert(token.key()) {
+                return Err(format!("清单中有重复的代币: {}", token.key()).into());
+            }
+        }
+        Ok(manifest)
+    }
+
+    // 批量执行前校验所有未创建的代币，避免跑到一半才发现格式错误
+    // raised_tokens为平台募集代币配置，每个代币按自己的募集代币校验预购上限
+    pub fn validate_pending(
+        &self,
+        state: &ManifestState,
+        default_raise: &str,
+        raised_tokens: &[RaisedToken],
+    ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let problems: Vec<String> = self
+            .tokens
+            .iter()
+            .filter(|token| !state.is_created(token))
+            .filter_map(|token| {
+                let checked = token
+                    .to_params(default_raise)
+                    .and_then(|params| {
+                        let raised_token = select_raised_token(raised_tokens, &params.raise_symbol)?;
+                        validate_params(&params, &raised_token).map_err(|e| e.into())
+                    });
+                checked.err().map(|e| format!("{}: {}", token.key(), e))
+            })
+            .collect();
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems.join("\n").into())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum EntryStatus {
+    Created,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntryResult {
+    pub name: String,
+    pub symbol: String,
+    pub status: EntryStatus,
+    pub token_address: Option<Address>,
+    pub create_tx_hash: Option<H256>,
+    pub bnb_spent: Option<String>,
+    pub error: Option<String>,
+    pub attempts: u32,
+    pub updated_at: i64,
+}
+
+// 清单执行状态，按代币标识记录结果
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ManifestState {
+    pub manifest: String,
+    pub entries: BTreeMap<String, ManifestEntryResult>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl ManifestState {
+    pub fn state_path(dir: impl AsRef<Path>, manifest_path: &Path) -> PathBuf {
+        let stem = manifest_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("manifest");
+        dir.as_ref().join(format!("{}.state.json", stem))
+    }
+
+    pub fn load(dir: impl AsRef<Path>, manifest_path: &Path) -> std::result::Result<Self, Box<dyn std::error::Error>> {
+        let path = Self::state_path(dir, manifest_path);
+        let mut state = if path.exists() {
+            serde_json::from_str::<ManifestState>(&std::fs::read_to_string(&path)?)?
+        } else {
+            ManifestState {
+                manifest: manifest_path.display().to_string(),
+                ..Default::default()
+            }
+        };
+        state.path = path;
+        Ok(state)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_created(&self, token: &ManifestToken) -> bool {
+        self.entries
+            .get(&token.key())
+            .is_some_and(|entry| entry.status == EntryStatus::Created)
+    }
+
+    fn attempts(&self, token: &ManifestToken) -> u32 {
+        self.entries.get(&token.key()).map_or(0, |entry| entry.attempts)
+    }
+
+    pub fn record_created(
+        &mut self,
+        token: &ManifestToken,
+        token_address: Address,
+        create_tx_hash: H256,
+        bnb_spent: String,
+    ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        // 零地址说明没有确认代币已创建，不能标记为完成，否则重新运行会跳过它
+        if token_address.is_zero() {
+            return Err(format!("{}: 代币地址为零地址，不能标记为已创建 (交易 {:?})", token.key(), create_tx_hash).into());
+        }
+        let result = ManifestEntryResult {
+            name: token.name.clone(),
+            symbol: token.symbol.clone(),
+            status: EntryStatus::Created,
+            token_address: Some(token_address),
+            create_tx_hash: Some(create_tx_hash),
+            bnb_spent: Some(bnb_spent),
+            error: None,
+            attempts: self.attempts(token) + 1,
+            updated_at: Utc::now().timestamp(),
+        };
+        self.entries.insert(token.key(), result);
+        self.save()
+    }
+
+    pub fn record_failed(&mut self, token: &ManifestToken, error: String) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let result = ManifestEntryResult {
+            name: token.name.clone(),
+            symbol: token.symbol.clone(),
+            status: EntryStatus::Failed,
+            token_address: None,
+            create_tx_hash: None,
+            bnb_spent: None,
+            error: Some(error),
+            attempts: self.attempts(token) + 1,
+            updated_at: Utc::now().timestamp(),
+        };
+        self.entries.insert(token.key(), result);
+        self.save()
+    }
+
+    // 先写临时文件再重命名，避免中断时留下半个文件
+    fn save(&self) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::default_raised_token;
+
+    const YAML: &str = r#"
+tokens:
+  - name: 狐链fox
+    symbol: FOX
+    desc: 第一个代币
+    image: image/狐链fox.jpg
+    pre_sale: "0.1"
+    launch_time: "2026-11-01T20:00:00+08:00"
+  - name: Wolf Coin
+    symbol: WOLF
+    description: 第二个代币
+    label: AI
+    image: https://example.com/wolf.png
+    website: https://wolf.example.com
+    raise_token: USD1
+"#;
+
+    fn write_manifest(dir: &Path, content: &str) -> PathBuf {
+        let path = dir.join("tokens.yaml");
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn token(name: &str, symbol: &str) -> ManifestToken {
+        serde_yaml::from_str(&format!("{{name: '{}', symbol: '{}', desc: test, image: a.png}}", name, symbol)).unwrap()
+    }
+
+    #[test]
+    fn parses_yaml_with_defaults_and_aliases() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = Manifest::load(write_manifest(dir.path(), YAML)).unwrap();
+        assert_eq!(manifest.tokens.len(), 2);
+
+        let fox = &manifest.tokens[0];
+        assert_eq!(fox.key(), "狐链fox:FOX");
+        assert_eq!(fox.description, "第一个代币");
+        assert_eq!(fox.label, TokenLabel::Meme);
+        assert_eq!(fox.pre_sale, "0.1");
+
+        let wolf = &manifest.tokens[1];
+        assert_eq!(wolf.description, "第二个代币");
+        assert_eq!(wolf.label, TokenLabel::Ai);
+        assert_eq!(wolf.pre_sale, "0");
+        assert_eq!(wolf.to_params("BNB").unwrap().raise_symbol, "USD1");
+        assert_eq!(fox.to_params("BNB").unwrap().raise_symbol, "BNB");
+        assert_eq!(fox.to_params("BNB").unwrap().web_url, None);
+    }
+
+    #[test]
+    fn rejects_empty_and_duplicate_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(Manifest::load(write_manifest(dir.path(), "tokens: []")).is_err());
+
+        let duplicated = "tokens:\n  - {name: A, symbol: A, desc: a, image: a.png}\n  - {name: A, symbol: A, desc: b, image: b.png}\n";
+        let err = Manifest::load(write_manifest(dir.path(), duplicated)).unwrap_err();
+        assert!(err.to_string().contains("重复"));
+
+        assert!(Manifest::load(write_manifest(dir.path(), "tokens:\n  - {name: A}\n")).is_err());
+    }
+
+    #[test]
+    fn launch_time_accepts_millis_rfc3339_or_now() {
+        let mut fox = token("Fox", "FOX");
+        fox.launch_time = Some("1793534400000".to_string());
+        assert_eq!(fox.launch_time_millis().unwrap(), 1_793_534_400_000);
+
+        fox.launch_time = Some("2026-11-01T20:00:00+08:00".to_string());
+        assert_eq!(fox.launch_time_millis().unwrap(), 1_793_534_400_000);
+
+        for empty in [None, Some("  ".to_string())] {
+            fox.launch_time = empty;
+            let now = Utc::now().timestamp_millis() as u64;
+            assert!(fox.launch_time_millis().unwrap().abs_diff(now) < 1_000);
+        }
+
+        fox.launch_time = Some("明天".to_string());
+        assert!(fox.launch_time_millis().unwrap_err().to_string().contains("Fox:FOX"));
+    }
+
+    #[test]
+    fn validate_pending_reports_every_bad_token_and_skips_created() {
+        let dir = tempfile::tempdir().unwrap();
+        let good = token("Good", "GOOD");
+        let mut bad_symbol = token("Bad", "BAD SYM");
+        bad_symbol.pre_sale = "abc".to_string();
+        let mut bad_time = token("Late", "LATE");
+        bad_time.launch_time = Some("not-a-time".to_string());
+        let mut bad_raise = token("Cake", "CAKE");
+        bad_raise.raise_token = Some("CAKE".to_string());
+        let manifest = Manifest {
+            tokens: vec![good, bad_symbol.clone(), bad_time.clone(), bad_raise.clone()],
+        };
+
+        let mut state = ManifestState::load(dir.path(), Path::new("tokens.yaml")).unwrap();
+        let err = manifest.validate_pending(&state, "BNB", &[default_raised_token()]).unwrap_err().to_string();
+        // 每个代币一段，同一代币的多个问题都列出
+        let bad = err.find("Bad:BAD SYM: ").unwrap();
+        let late = err.find("Late:LATE: ").unwrap();
+        assert!(bad < late);
+        assert!(err[bad..late].contains("符号") && err[bad..late].contains("预购数量"));
+        let cake = err.find("Cake:CAKE: ").unwrap();
+        assert!(err[late..cake].contains("launch_time"));
+        // 平台配置里没有的募集代币也在批量开始前报出
+        assert!(err[cake..].contains("不支持以 CAKE 募集"));
+        assert!(!err.contains("Good:GOOD"));
+
+        // 已创建的代币不再校验
+        state.record_created(&bad_symbol, Address::repeat_byte(1), H256::repeat_byte(1), "0".into()).unwrap();
+        state.record_created(&bad_time, Address::repeat_byte(2), H256::repeat_byte(2), "0".into()).unwrap();
+        state.record_created(&bad_raise, Address::repeat_byte(3), H256::repeat_byte(3), "0".into()).unwrap();
+        manifest.validate_pending(&state, "BNB", &[default_raised_token()]).unwrap();
+    }
+
+    #[test]
+    fn state_skips_created_and_counts_attempts() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest_path = Path::new("batches/tokens.yaml");
+        let fox = token("Fox", "FOX");
+        let wolf = token("Wolf", "WOLF");
+
+        let mut state = ManifestState::load(dir.path(), manifest_path).unwrap();
+        assert_eq!(state.path(), dir.path().join("tokens.state.json"));
+        assert!(!state.is_created(&fox));
+
+        state.record_failed(&fox, "RPC超时".to_string()).unwrap();
+        assert!(!state.is_created(&fox));
+
+        // 零地址不能记为已创建
+        assert!(state.record_created(&fox, Address::zero(), H256::repeat_byte(9), "0.1".into()).is_err());
+        assert!(!state.is_created(&fox));
+
+        state.record_created(&fox, Address::repeat_byte(0x44), H256::repeat_byte(9), "0.1".into()).unwrap();
+
+        // 重新加载后已创建的跳过，失败次数累计
+        let state = ManifestState::load(dir.path(), manifest_path).unwrap();
+        assert!(state.is_created(&fox));
+        assert!(!state.is_created(&wolf));
+        let entry = &state.entries[&fox.key()];
+        assert_eq!(entry.status, EntryStatus::Created);
+        assert_eq!(entry.attempts, 2);
+        assert_eq!(entry.error, None);
+        assert_eq!(entry.token_address, Some(Address::repeat_byte(0x44)));
+        assert!(!dir.path().join("tokens.state.json.tmp").exists());
+    }
+}
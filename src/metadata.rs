@@ -0,0 +1,300 @@
+// 代币元数据本地校验
+// 在登录和上传图片之前检查名称、符号、描述、分类、链接、预购数量和发布时间，
+// 一次列出所有问题，避免等服务端拒绝后再逐个修改。
+
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use url::Url;
+
+use crate::api::{CreateTokenParams, RaisedToken};
+use crate::config::{TOKEN_DESC_MAX_CHARS, TOKEN_LAUNCH_MAX_DAYS_AHEAD, TOKEN_NAME_MAX_CHARS, TOKEN_SYMBOL_MAX_CHARS};
+
+// 允许的发布时间回拨，覆盖本地与服务器的时钟误差
+const LAUNCH_TIME_PAST_TOLERANCE_SECS: i64 = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum TokenLabel {
+    Ai,
+    Meme,
+    Defi,
+    Games,
+    Infra,
+    DeSci,
+    Social,
+    Depin,
+    Charity,
+    Others,
+}
+
+impl TokenLabel {
+    pub const ALL: [TokenLabel; 10] = [
+        TokenLabel::Ai,
+        TokenLabel::Meme,
+        TokenLabel::Defi,
+        TokenLabel::Games,
+        TokenLabel::Infra,
+        TokenLabel::DeSci,
+        TokenLabel::Social,
+        TokenLabel::Depin,
+        TokenLabel::Charity,
+        TokenLabel::Others,
+    ];
+
+    // API中使用的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenLabel::Ai => "AI",
+            TokenLabel::Meme => "Meme",
+            TokenLabel::Defi => "Defi",
+            TokenLabel::Games => "Games",
+            TokenLabel::Infra => "Infra",
+            TokenLabel::DeSci => "De-Sci",
+            TokenLabel::Social => "Social",
+            TokenLabel::Depin => "Depin",
+            TokenLabel::Charity => "Charity",
+            TokenLabel::Others => "Others",
+        }
+    }
+}
+
+impl fmt::Display for TokenLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for TokenLabel {
+    type Err = String;
+
+    // 忽略大小写，如 "meme"、"DE-SCI"
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        TokenLabel::ALL
+            .into_iter()
+            .find(|label| label.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let all: Vec<&str> = TokenLabel::ALL.iter().map(|l| l.as_str()).collect();
+                format!("不支持的代币分类: '{}' (可选 {})", s, all.join("/"))
+            })
+    }
+}
+
+impl TryFrom<String> for TokenLabel {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<TokenLabel> for String {
+    fn from(label: TokenLabel) -> Self {
+        label.as_str().to_string()
+    }
+}
+
+// 校验失败时汇总所有问题
+#[derive(Debug)]
+pub struct MetadataErrors(pub Vec<String>);
+
+impl fmt::Display for MetadataErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "代币信息校验失败:")?;
+        for problem in &self.0 {
+            write!(f, "\n  - {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for MetadataErrors {}
+
+// raised_token为按raise_symbol选定的平台募集代币，预购数量不能超过它的募集上限
+pub fn validate_params(params: &CreateTokenParams, raised_token: &RaisedToken) -> std::result::Result<(), MetadataErrors> {
+    let mut problems = Vec::new();
+
+    check_text("名称", &params.name, TOKEN_NAME_MAX_CHARS, &mut problems);
+    check_text("符号", &params.short_name, TOKEN_SYMBOL_MAX_CHARS, &mut problems);
+    if params.short_name.chars().any(|c| !c.is_alphanumeric()) {
+        problems.push(format!("符号只能包含字母、数字或中文，不能有空格和符号: '{}'", params.short_name));
+    }
+    check_text("描述", &params.desc, TOKEN_DESC_MAX_CHARS, &mut problems);
+
+    check_url("网站", params.web_url.as_deref(), &[], &mut problems);
+    check_url("Twitter", params.twitter_url.as_deref(), &["x.com", "twitter.com"], &mut problems);
+    check_url("Telegram", params.telegram_url.as_deref(), &["t.me", "telegram.me"], &mut problems);
+
+    if !params.raise_symbol.trim().eq_ignore_ascii_case(&raised_token.symbol) {
+        problems.push(format!("募集代币 {} 与选定的平台配置 {} 不一致", params.raise_symbol.trim(), raised_token.symbol));
+    }
+    match (crate::parse_bnb_amount("预购数量", &params.pre_sale), raised_token.raised_amount()) {
+        (Ok(pre_sale), Ok(cap)) => {
+            if pre_sale > ethers::utils::parse_ether(cap).unwrap_or_default() {
+                problems.push(format!(
+                    "预购数量 {} {} 超过募集上限 {} {}",
+                    params.pre_sale.trim(),
+                    raised_token.symbol,
+                    cap,
+                    raised_token.symbol
+                ));
+            }
+        }
+        (pre_sale, cap) => {
+            problems.extend(pre_sale.err().map(|e| e.to_string()));
+            problems.extend(cap.err());
+        }
+    }
+
+    if let Some(launch_time) = params.launch_time {
+        let now = Utc::now();
+        let earliest = (now - Duration::seconds(LAUNCH_TIME_PAST_TOLERANCE_SECS)).timestamp_millis();
+        let latest = (now + Duration::days(TOKEN_LAUNCH_MAX_DAYS_AHEAD)).timestamp_millis();
+        match i64::try_from(launch_time) {
+            // 秒级时间戳误传会落在1970年附近
+            Ok(ms) if ms < earliest => problems.push(format!(
+                "发布时间 {} 早于当前时间（launchTime需为毫秒时间戳）",
+                format_millis(ms)
+            )),
+            Ok(ms) if ms > latest => problems.push(format!(
+                "发布时间 {} 超过 {} 天后",
+                format_millis(ms),
+                TOKEN_LAUNCH_MAX_DAYS_AHEAD
+            )),
+            Ok(_) => {}
+            Err(_) => problems.push(format!("发布时间无效: {}", launch_time)),
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(MetadataErrors(problems))
+    }
+}
+
+fn check_text(field: &str, value: &str, max_chars: usize, problems: &mut Vec<String>) {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        problems.push(format!("{}不能为空", field));
+        return;
+    }
+    if trimmed != value {
+        problems.push(format!("{}首尾不能有空白: '{}'", field, value));
+    }
+    let chars = value.chars().count();
+    if chars > max_chars {
+        problems.push(format!("{}过长: {}个字符，最多{}个", field, chars, max_chars));
+    }
+    if value.chars().any(|c| c.is_control() && c != '\n') {
+        problems.push(format!("{}包含控制字符", field));
+    }
+}
+
+// 空链接视为未填写；allowed_hosts非空时要求域名匹配（含子域名）
+fn check_url(field: &str, value: Option<&str>, allowed_hosts: &[&str], problems: &mut Vec<String>) {
+    let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) else {
+        return;
+    };
+    let url = match Url::parse(value) {
+        Ok(url) => url,
+        Err(e) => {
+            problems.push(format!("{}链接无效: '{}' ({})", field, value, e));
+            return;
+        }
+    };
+    if url.scheme() != "https" && url.scheme() != "http" {
+        problems.push(format!("{}链接必须是http(s): '{}'", field, value));
+        return;
+    }
+    let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+    if !allowed_hosts.is_empty()
+        && !allowed_hosts
+            .iter()
+            .any(|allowed| host == *allowed || host.ends_with(&format!(".{}", allowed)))
+    {
+        problems.push(format!("{}链接的域名应为 {}: '{}'", field, allowed_hosts.join("/"), value));
+    }
+}
+
+fn format_millis(ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(ms)
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| ms.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::default_raised_token;
+
+    fn params() -> CreateTokenParams {
+        CreateTokenParams {
+            name: "狐链fox".to_string(),
+            short_name: "狐链fox".to_string(),
+            desc: "a fox on chain".to_string(),
+            img_url: String::new(),
+            launch_time: Some(Utc::now().timestamp_millis() as u64),
+            label: TokenLabel::Meme,
+            web_url: Some("https://difipay.vercel.app".to_string()),
+            twitter_url: Some(String::new()),
+            telegram_url: None,
+            pre_sale: "0.2".to_string(),
+            raise_symbol: "BNB".to_string(),
+        }
+    }
+
+    #[test]
+    fn labels_parse_case_insensitively_and_serialize_as_api_names() {
+        assert_eq!("de-sci".parse::<TokenLabel>().unwrap(), TokenLabel::DeSci);
+        assert_eq!("AI".parse::<TokenLabel>().unwrap(), TokenLabel::Ai);
+        assert!("Memes".parse::<TokenLabel>().is_err());
+        assert_eq!(serde_json::to_string(&TokenLabel::DeSci).unwrap(), "\"De-Sci\"");
+    }
+
+    #[test]
+    fn valid_params_pass() {
+        validate_params(&params(), &default_raised_token()).unwrap();
+    }
+
+    #[test]
+    fn all_problems_are_reported_together() {
+        let mut p = params();
+        p.name = " ".to_string();
+        p.short_name = "FOX COIN".to_string();
+        p.twitter_url = Some("https://example.com/fox".to_string());
+        p.telegram_url = Some("t.me/fox".to_string());
+        p.pre_sale = "25".to_string();
+        p.launch_time = Some(Utc::now().timestamp() as u64); // 误传秒级时间戳
+
+        let errors = validate_params(&p, &default_raised_token()).unwrap_err();
+        assert_eq!(errors.0.len(), 6, "{}", errors);
+    }
+
+    #[test]
+    fn pre_sale_cap_comes_from_selected_raised_token() {
+        let cake = RaisedToken {
+            symbol: "CAKE".to_string(),
+            total_b_amount: "7500".to_string(),
+            ..default_raised_token()
+        };
+        let mut p = params();
+        p.raise_symbol = "cake".to_string();
+        p.pre_sale = "25".to_string();
+        validate_params(&p, &cake).unwrap();
+
+        p.pre_sale = "7500.1".to_string();
+        let errors = validate_params(&p, &cake).unwrap_err().to_string();
+        assert!(errors.contains("超过募集上限 7500 CAKE"), "{}", errors);
+
+        // 平台上限无法解析时报错，而不是跳过检查
+        let broken = RaisedToken { total_b_amount: "n/a".to_string(), ..cake };
+        let errors = validate_params(&p, &broken).unwrap_err().to_string();
+        assert!(errors.contains("totalBAmount"), "{}", errors);
+
+        p.pre_sale = "1".to_string();
+        let errors = validate_params(&p, &default_raised_token()).unwrap_err().to_string();
+        assert!(errors.contains("不一致"), "{}", errors);
+    }
+}
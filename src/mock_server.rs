@@ -0,0 +1,242 @@
+// 进程内的Four.meme API mock服务，仅用于测试
+// 实现 nonce/login/upload/create 四个接口和公开配置接口，返回与线上一致的 {code, msg, data} 信封：
+// - 登录时按 "You are sign in Meme {nonce}" 恢复签名地址，与verifyInfo.address不一致时拒绝
+// - 需要登录的接口校验 meme-web-access，可通过 expire_sessions 模拟access_token失效
+// 所有请求记录在MockState中，测试可以检查客户端实际发送的内容。
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use ethers::types::{Address, Signature};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+pub const MOCK_IMAGE_URL: &str = "https://static.four.meme/market/mock-token-image.png";
+
+#[derive(Debug, Clone)]
+pub struct LoginRecord {
+    pub address: String,
+    pub nonce: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Default)]
+pub struct MockState {
+    nonces: HashMap<String, String>, // 地址（小写） -> 最近一次下发的nonce
+    tokens: HashSet<String>,         // 有效的access_token
+    next_id: u64,
+    pub logins: Vec<LoginRecord>,
+    pub uploads: Vec<usize>, // 每次上传的请求体字节数
+    pub create_requests: Vec<Value>,
+}
+
+pub struct MockFourMeme {
+    pub base_url: String,
+    state: Arc<Mutex<MockState>>,
+    server: tokio::task::JoinHandle<()>,
+}
+
+impl MockFourMeme {
+    pub async fn start() -> Self {
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let app = Router::new()
+            .route("/meme-api/v1/private/user/nonce/generate", post(generate_nonce))
+            .route("/meme-api/v1/private/user/login/dex", post(login_dex))
+            .route("/meme-api/v1/private/token/upload", post(upload_token_image))
+            .route("/meme-api/v1/private/token/create", post(create_token))
+            .route("/meme-api/v1/public/config", get(public_config))
+            .with_state(state.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        Self {
+            base_url: format!("http://{}/meme-api", addr),
+            state,
+            server,
+        }
+    }
+
+    pub fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap()
+    }
+
+    // 让已下发的access_token全部失效，模拟会话过期
+    pub fn expire_sessions(&self) {
+        self.state().tokens.clear();
+    }
+}
+
+impl Drop for MockFourMeme {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+type Shared = State<Arc<Mutex<MockState>>>;
+
+fn ok(data: Value) -> (StatusCode, Json<Value>) {
+    (StatusCode::OK, Json(json!({ "code": "0", "msg": "success", "data": data })))
+}
+
+// 线上出错时HTTP状态码仍为200，code非0，data为null
+fn fail(code: &str, msg: &str) -> (StatusCode, Json<Value>) {
+    (StatusCode::OK, Json(json!({ "code": code, "msg": msg, "data": null })))
+}
+
+fn authorized(state: &MockState, headers: &HeaderMap) -> bool {
+    headers
+        .get("meme-web-access")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|token| state.tokens.contains(token))
+}
+
+async fn generate_nonce(State(state): Shared, Json(body): Json<Value>) -> (StatusCode, Json<Value>) {
+    let Some(address) = body["accountAddress"].as_str() else {
+        return fail("-1", "accountAddress is required");
+    };
+    if body["verifyType"] != "LOGIN" || body["networkCode"] != "BSC" {
+        return fail("-1", "invalid verifyType or networkCode");
+    }
+
+    let mut state = state.lock().unwrap();
+    state.next_id += 1;
+    let nonce = format!("mock-nonce-{:06}", state.next_id);
+    state.nonces.insert(address.to_lowercase(), nonce.clone());
+    ok(json!(nonce))
+}
+
+async fn login_dex(State(state): Shared, Json(body): Json<Value>) -> (StatusCode, Json<Value>) {
+    let verify_info = &body["verifyInfo"];
+    let (Some(address), Some(signature)) = (verify_info["address"].as_str(), verify_info["signature"].as_str()) else {
+        return fail("-1", "verifyInfo is required");
+    };
+
+    let mut state = state.lock().unwrap();
+    let Some(nonce) = state.nonces.remove(&address.to_lowercase()) else {
+        return fail("-1", "nonce not found");
+    };
+
+    let message = format!("You are sign in Meme {}", nonce);
+    let recovered = Signature::from_str(signature.trim_start_matches("0x"))
+        .ok()
+        .and_then(|sig| sig.recover(message.as_str()).ok());
+    let expected = Address::from_str(address).ok();
+    if recovered.is_none() || recovered != expected {
+        return fail("-1", "signature verification failed");
+    }
+
+    state.logins.push(LoginRecord {
+        address: address.to_string(),
+        nonce,
+        signature: signature.to_string(),
+    });
+    state.next_id += 1;
+    let access_token = format!("mock-access-token-{:06}", state.next_id);
+    state.tokens.insert(access_token.clone());
+    ok(json!(access_token))
+}
+
+async fn upload_token_image(State(state): Shared, headers: HeaderMap, body: Bytes) -> (StatusCode, Json<Value>) {
+    let mut state = state.lock().unwrap();
+    if !authorized(&state, &headers) {
+        return fail("403", "not login");
+    }
+    let is_multipart = headers
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("multipart/form-data"));
+    if !is_multipart || body.is_empty() {
+        return fail("-1", "file is required");
+    }
+
+    state.uploads.push(body.len());
+    ok(json!(MOCK_IMAGE_URL))
+}
+
+async fn create_token(State(state): Shared, headers: HeaderMap, Json(body): Json<Value>) -> (StatusCode, Json<Value>) {
+    let mut state = state.lock().unwrap();
+    if !authorized(&state, &headers) {
+        return fail("403", "not login");
+    }
+    for field in ["name", "shortName", "desc", "imgUrl", "label", "preSale"] {
+        if body[field].as_str().is_none_or(str::is_empty) {
+            return fail("-1", &format!("{} is required", field));
+        }
+    }
+
+    let pre_sale: f64 = body["preSale"].as_str().unwrap().parse().unwrap_or(0.0);
+    let launch_time = body["launchTime"].as_u64().unwrap_or_default();
+    state.create_requests.push(body);
+    state.next_id += 1;
+    let token_id = 100_000 + state.next_id;
+
+    ok(json!({
+        "tokenId": token_id,
+        "totalAmount": "1000000000",
+        "saleAmount": "800000000",
+        "template": 0,
+        "launchTime": launch_time,
+        "serverTime": chrono::Utc::now().timestamp_millis(),
+        // 前32字节是requestId（即tokenId），TokenManager替身据此发出TokenCreate事件
+        "createArg": format!("0x{:064x}{}", token_id, "ab".repeat(32)),
+        "signature": format!("0x{}", "cd".repeat(65)),
+        "bamount": pre_sale.to_string(),
+        "tamount": "0",
+    }))
+}
+
+// 公开配置：BNB和CAKE两种募集代币，CAKE的部分数值字段为数字
+async fn public_config() -> (StatusCode, Json<Value>) {
+    ok(json!([
+        {
+            "symbol": "BNB",
+            "nativeSymbol": "BNB",
+            "symbolAddress": "0xbb4cdb9cbd36b01bd1cbaebf2de08d9173bc095c",
+            "deployCost": "0",
+            "buyFee": "0.01",
+            "sellFee": "0.01",
+            "minTradeFee": "0",
+            "b0Amount": "8",
+            "totalBAmount": "24",
+            "totalAmount": "1000000000",
+            "logoUrl": "https://static.four.meme/market/bnb.png",
+            "tradeLevel": ["0.1", "0.5", "1"],
+            "status": "PUBLISH",
+            "buyTokenLink": "https://pancakeswap.finance/swap",
+            "reservedNumber": 10,
+            "saleRate": "0.8",
+            "networkCode": "BSC",
+            "platform": "MEME"
+        },
+        {
+            "symbol": "CAKE",
+            "nativeSymbol": "BNB",
+            "symbolAddress": "0x0e09fabb73bd3ade0a17ecc321fd13a19e81ce82",
+            "deployCost": 0.005,
+            "buyFee": 0.01,
+            "sellFee": 0.01,
+            "minTradeFee": 0,
+            "b0Amount": 2500,
+            "totalBAmount": 7500,
+            "totalAmount": 1000000000,
+            "logoUrl": "https://static.four.meme/market/cake.png",
+            "tradeLevel": ["10", "50", "100"],
+            "status": "PUBLISH",
+            "buyTokenLink": "https://pancakeswap.finance/swap",
+            "reservedNumber": 10,
+            "saleRate": 0.8,
+            "networkCode": "BSC",
+            "platform": "MEME"
+        }
+    ]))
+}
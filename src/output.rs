@@ -0,0 +1,158 @@
+// 命令输出格式
+// - text（默认）：结果和过程日志都是给人看的中文文本，写到stdout
+// - json：每个结果（卖出、创建、报价、错误等）以一行JSON写到stdout（多个结果即NDJSON），
+//   过程日志改写到stderr，stdout可以直接交给jq或其他脚本处理
+// 库和命令行中的过程日志统一使用 `logln!`，不要直接println!，否则会混进JSON输出。
+
+use serde::Serialize;
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" | "ndjson" => Ok(Self::Json),
+            other => Err(format!("未知的输出格式 '{}'，可选 text / json", other)),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Text => "text",
+            Self::Json => "json",
+        })
+    }
+}
+
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+// 进程级设置，命令行解析参数后调用一次
+pub fn set_format(format: OutputFormat) {
+    JSON_OUTPUT.store(format == OutputFormat::Json, Ordering::Relaxed);
+}
+
+pub fn format() -> OutputFormat {
+    if is_json() {
+        OutputFormat::Json
+    } else {
+        OutputFormat::Text
+    }
+}
+
+pub fn is_json() -> bool {
+    JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+// 打印一行过程日志：text写stdout，json写stderr
+pub fn log_line(args: fmt::Arguments) {
+    if is_json() {
+        eprintln!("{}", args);
+    } else {
+        println!("{}", args);
+    }
+}
+
+// 不换行的日志（倒计时等原地刷新的输出）
+pub fn log_inline(args: fmt::Arguments) {
+    if is_json() {
+        eprint!("{}", args);
+        let _ = std::io::stderr().flush();
+    } else {
+        print!("{}", args);
+        let _ = std::io::stdout().flush();
+    }
+}
+
+// 把结果序列化为一行JSON，kind标明结果类型；结构体的字段平铺在顶层，其他值放在value字段。
+// 地址和哈希为0x十六进制，U256金额与交易记录一样序列化为0x十六进制的wei
+pub fn result_line<T: Serialize + ?Sized>(kind: &str, value: &T) -> serde_json::Result<String> {
+    let line = match serde_json::to_value(value)? {
+        serde_json::Value::Object(fields) => {
+            let mut object = serde_json::Map::new();
+            object.insert("kind".to_string(), kind.into());
+            object.extend(fields);
+            serde_json::Value::Object(object)
+        }
+        other => serde_json::json!({ "kind": kind, "value": other }),
+    };
+    serde_json::to_string(&line)
+}
+
+// json模式下把结果写到stdout，text模式下什么都不做（文本结果由各命令自己打印）
+pub fn emit<T: Serialize + ?Sized>(kind: &str, value: &T) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    if is_json() {
+        let line = result_line(kind, value)?;
+        let mut stdout = std::io::stdout().lock();
+        writeln!(stdout, "{}", line)?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+// 命令失败时的结构化错误
+pub fn error_line(error: &dyn fmt::Display) -> String {
+    serde_json::json!({ "kind": "error", "error": error.to_string() }).to_string()
+}
+
+#[macro_export]
+macro_rules! logln {
+    () => {
+        $crate::output::log_line(format_args!(""))
+    };
+    ($($arg:tt)*) => {
+        $crate::output::log_line(format_args!($($arg)*))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Sample {
+        tx_hash: String,
+        amount: u64,
+    }
+
+    #[test]
+    fn result_line_flattens_struct_fields_after_kind() {
+        let line = result_line("sell", &Sample { tx_hash: "0xab".to_string(), amount: 3 }).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value, serde_json::json!({ "kind": "sell", "tx_hash": "0xab", "amount": 3 }));
+
+        let line = result_line("imported", &7u64).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value, serde_json::json!({ "kind": "imported", "value": 7 }));
+    }
+
+    #[test]
+    fn error_line_is_single_json_object() {
+        let line = error_line(&"GW: 数量未按GWEI对齐");
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["kind"], "error");
+        assert_eq!(value["error"], "GW: 数量未按GWEI对齐");
+        assert!(!line.contains('\n'));
+    }
+
+    #[test]
+    fn parses_output_format() {
+        assert_eq!("json".parse::<OutputFormat>().unwrap(), OutputFormat::Json);
+        assert_eq!("NDJSON".parse::<OutputFormat>().unwrap(), OutputFormat::Json);
+        assert_eq!("text".parse::<OutputFormat>().unwrap(), OutputFormat::Text);
+        assert!("yaml".parse::<OutputFormat>().is_err());
+    }
+}
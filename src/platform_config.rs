@@ -0,0 +1,214 @@
+// 平台募集代币配置
+// 从Four.meme公开配置接口获取当前的raisedToken列表（代币地址、费率、募集上限、状态等），
+// 缓存在 data/platform_config.json，有效期内不重复请求；
+// 接口不可用时依次退回过期缓存和内置的BNB配置。
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::api::{default_raised_token, FourMemeApiClient, RaisedToken};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedConfig {
+    fetched_at: i64, // 秒级时间戳
+    raised_tokens: Vec<RaisedToken>,
+}
+
+fn read_cache(path: &Path) -> Option<CachedConfig> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_cache(path: &Path, raised_tokens: &[RaisedToken]) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let cached = CachedConfig {
+        fetched_at: Utc::now().timestamp(),
+        raised_tokens: raised_tokens.to_vec(),
+    };
+    std::fs::write(path, serde_json::to_string_pretty(&cached)?)?;
+    Ok(())
+}
+
+// 获取募集代币列表：未过期的缓存 > 平台接口 > 过期缓存 > 内置BNB配置；cache_path为None时不使用缓存
+pub async fn load_raised_tokens(api: &FourMemeApiClient, cache_path: Option<&Path>, ttl_secs: i64) -> Vec<RaisedToken> {
+    let cached = cache_path.and_then(read_cache);
+    if let Some(cached) = &cached {
+        if Utc::now().timestamp() - cached.fetched_at < ttl_secs && !cached.raised_tokens.is_empty() {
+            return cached.raised_tokens.clone();
+        }
+    }
+
+    match api.get_raised_tokens().await {
+        Ok(raised_tokens) if !raised_tokens.is_empty() => {
+            if let Some(Err(e)) = cache_path.map(|path| write_cache(path, &raised_tokens)) {
+                logln!("  ⚠️  保存平台配置缓存失败: {}", e);
+            }
+            raised_tokens
+        }
+        result => {
+            let reason = match result {
+                Err(e) => e.to_string(),
+                Ok(_) => "配置中没有募集代币".to_string(),
+            };
+            match cached.filter(|c| !c.raised_tokens.is_empty()) {
+                Some(cached) => {
+                    logln!("  ⚠️  获取平台配置失败，使用过期缓存: {}", reason);
+                    cached.raised_tokens
+                }
+                None => {
+                    logln!("  ⚠️  获取平台配置失败，使用内置BNB配置: {}", reason);
+                    vec![default_raised_token()]
+                }
+            }
+        }
+    }
+}
+
+// 按符号选择募集代币，只允许平台已开放（PUBLISH）的代币
+pub fn select_raised_token(raised_tokens: &[RaisedToken], symbol: &str) -> std::result::Result<RaisedToken, Box<dyn std::error::Error>> {
+    let symbol = symbol.trim();
+    let available: Vec<&str> = raised_tokens
+        .iter()
+        .filter(|t| t.is_published())
+        .map(|t| t.symbol.as_str())
+        .collect();
+
+    match raised_tokens.iter().find(|t| t.symbol.eq_ignore_ascii_case(symbol)) {
+        Some(token) if token.is_published() => Ok(token.clone()),
+        Some(token) => Err(format!("募集代币 {} 当前不可用 (状态: {})，可选: {}", symbol, token.status, available.join("/")).into()),
+        None => Err(format!("平台不支持以 {} 募集，可选: {}", symbol, available.join("/")).into()),
+    }
+}
+
+pub fn print_raised_token(token: &RaisedToken) {
+    logln!(
+        "  募集代币: {} ({})，募集上限 {}，买入手续费 {}，发行费 {} {}",
+        token.symbol, token.symbol_address, token.total_b_amount, token.buy_fee, token.deploy_cost, token.native_symbol
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::MockFourMeme;
+
+    // 没有服务监听的端口，请求会立即失败
+    const DEAD_API: &str = "http://127.0.0.1:9/meme-api";
+
+    fn token(symbol: &str) -> RaisedToken {
+        RaisedToken { symbol: symbol.to_string(), ..default_raised_token() }
+    }
+
+    fn write_cached(path: &Path, fetched_at: i64, raised_tokens: Vec<RaisedToken>) {
+        let cached = CachedConfig { fetched_at, raised_tokens };
+        std::fs::write(path, serde_json::to_string(&cached).unwrap()).unwrap();
+    }
+
+    fn symbols(raised_tokens: &[RaisedToken]) -> Vec<&str> {
+        raised_tokens.iter().map(|t| t.symbol.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn fresh_cache_is_used_without_calling_api() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("platform_config.json");
+        write_cached(&path, Utc::now().timestamp(), vec![token("USD1")]);
+
+        let api = FourMemeApiClient::new().with_base_url(DEAD_API);
+        let loaded = load_raised_tokens(&api, Some(&path), 3600).await;
+        assert_eq!(symbols(&loaded), ["USD1"]);
+    }
+
+    #[tokio::test]
+    async fn expired_cache_is_refreshed_from_api() {
+        let mock = MockFourMeme::start().await;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("platform_config.json");
+        let stale_at = Utc::now().timestamp() - 7200;
+        write_cached(&path, stale_at, vec![token("USD1")]);
+
+        let api = FourMemeApiClient::new().with_base_url(&mock.base_url);
+        let loaded = load_raised_tokens(&api, Some(&path), 3600).await;
+        assert_eq!(symbols(&loaded), ["BNB", "CAKE"]);
+
+        let rewritten = read_cache(&path).unwrap();
+        assert!(rewritten.fetched_at > stale_at);
+        assert_eq!(symbols(&rewritten.raised_tokens), ["BNB", "CAKE"]);
+    }
+
+    #[tokio::test]
+    async fn missing_cache_is_filled_from_api() {
+        let mock = MockFourMeme::start().await;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data").join("platform_config.json");
+
+        let api = FourMemeApiClient::new().with_base_url(&mock.base_url);
+        let loaded = load_raised_tokens(&api, Some(&path), 3600).await;
+        assert_eq!(symbols(&loaded), ["BNB", "CAKE"]);
+        assert!(read_cache(&path).is_some());
+    }
+
+    #[tokio::test]
+    async fn expired_cache_is_used_when_api_is_down() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("platform_config.json");
+        let stale_at = Utc::now().timestamp() - 7200;
+        write_cached(&path, stale_at, vec![token("USD1")]);
+
+        let api = FourMemeApiClient::new().with_base_url(DEAD_API);
+        let loaded = load_raised_tokens(&api, Some(&path), 3600).await;
+        assert_eq!(symbols(&loaded), ["USD1"]);
+        // 过期缓存不会被改写
+        assert_eq!(read_cache(&path).unwrap().fetched_at, stale_at);
+    }
+
+    #[tokio::test]
+    async fn builtin_bnb_is_used_without_cache_or_api() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("platform_config.json");
+
+        let api = FourMemeApiClient::new().with_base_url(DEAD_API);
+        let loaded = load_raised_tokens(&api, Some(&path), 3600).await;
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].symbol, "BNB");
+        assert_eq!(loaded[0].symbol_address, default_raised_token().symbol_address);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn empty_cache_counts_as_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("platform_config.json");
+        write_cached(&path, Utc::now().timestamp(), Vec::new());
+
+        let api = FourMemeApiClient::new().with_base_url(DEAD_API);
+        let loaded = load_raised_tokens(&api, Some(&path), 3600).await;
+        assert_eq!(symbols(&loaded), ["BNB"]);
+    }
+
+    #[tokio::test]
+    async fn disabled_cache_always_asks_api() {
+        let mock = MockFourMeme::start().await;
+        let api = FourMemeApiClient::new().with_base_url(&mock.base_url);
+        assert_eq!(symbols(&load_raised_tokens(&api, None, 3600).await), ["BNB", "CAKE"]);
+
+        let down = FourMemeApiClient::new().with_base_url(DEAD_API);
+        assert_eq!(symbols(&load_raised_tokens(&down, None, 3600).await), ["BNB"]);
+    }
+
+    #[test]
+    fn select_only_published_tokens() {
+        let mut paused = token("USD1");
+        paused.status = "INIT".to_string();
+        let raised_tokens = vec![default_raised_token(), token("CAKE"), paused];
+
+        assert_eq!(select_raised_token(&raised_tokens, " cake ").unwrap().symbol, "CAKE");
+        let err = select_raised_token(&raised_tokens, "USD1").unwrap_err().to_string();
+        assert!(err.contains("当前不可用") && err.contains("BNB/CAKE"), "{}", err);
+        let err = select_raised_token(&raised_tokens, "ETH").unwrap_err().to_string();
+        assert!(err.contains("不支持"), "{}", err);
+    }
+}
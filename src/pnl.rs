@@ -0,0 +1,566 @@
+// 盈亏统计（PnL）
+// 基于交易记录按钱包和代币计算持仓成本、已实现盈亏和未实现盈亏：
+// - 持仓成本支持FIFO和加权平均两种方式，买入的平台手续费和gas计入成本
+// - 已实现盈亏 = 卖出到手金额 - 卖出gas - 对应持仓成本
+// - 未实现盈亏通过Helper3的trySell对当前持仓估值
+
+use ethers::{
+    prelude::*,
+    types::{Address, I256, U256},
+    utils::format_ether,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+
+use crate::journal::{TradeRecord, TradeSide};
+use crate::contracts::ITokenManagerHelper3;
+
+// 持仓成本计算方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CostBasisMethod {
+    Fifo,
+    Average,
+}
+
+impl std::str::FromStr for CostBasisMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "FIFO" => Ok(CostBasisMethod::Fifo),
+            "AVERAGE" | "AVG" => Ok(CostBasisMethod::Average),
+            other => Err(format!("不支持的成本计算方式: {} (可选 FIFO/AVERAGE)", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for CostBasisMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CostBasisMethod::Fifo => write!(f, "FIFO"),
+            CostBasisMethod::Average => write!(f, "AVERAGE"),
+        }
+    }
+}
+
+// 单个批次：数量和对应成本
+#[derive(Debug, Clone, Copy)]
+struct Lot {
+    amount: U256,
+    cost: U256,
+}
+
+// 持仓成本跟踪器
+struct CostTracker {
+    method: CostBasisMethod,
+    lots: VecDeque<Lot>,
+}
+
+impl CostTracker {
+    fn new(method: CostBasisMethod) -> Self {
+        Self {
+            method,
+            lots: VecDeque::new(),
+        }
+    }
+
+    fn buy(&mut self, amount: U256, cost: U256) {
+        match (self.method, self.lots.front_mut()) {
+            // 加权平均只保留一个合并批次
+            (CostBasisMethod::Average, Some(lot)) => {
+                lot.amount += amount;
+                lot.cost += cost;
+            }
+            _ => self.lots.push_back(Lot { amount, cost }),
+        }
+    }
+
+    // 卖出指定数量，返回(对应成本, 没有买入记录可匹配的数量)
+    fn sell(&mut self, amount: U256) -> (U256, U256) {
+        let mut remaining = amount;
+        let mut basis = U256::zero();
+
+        while !remaining.is_zero() {
+            let Some(lot) = self.lots.front_mut() else {
+                break;
+            };
+
+            let take = remaining.min(lot.amount);
+            let take_cost = if take == lot.amount {
+                lot.cost
+            } else {
+                lot.cost * take / lot.amount
+            };
+
+            basis += take_cost;
+            lot.amount -= take;
+            lot.cost -= take_cost;
+            remaining -= take;
+
+            if lot.amount.is_zero() {
+                self.lots.pop_front();
+            }
+        }
+
+        (basis, remaining)
+    }
+
+    fn position(&self) -> U256 {
+        self.lots.iter().fold(U256::zero(), |acc, lot| acc + lot.amount)
+    }
+
+    fn cost_basis(&self) -> U256 {
+        self.lots.iter().fold(U256::zero(), |acc, lot| acc + lot.cost)
+    }
+}
+
+// 单个钱包在单个代币上的盈亏
+#[derive(Debug, Clone)]
+pub struct PositionPnl {
+    pub wallet: Address,
+    pub token: Address,
+    pub bought: U256,         // 累计买入代币数量
+    pub sold: U256,           // 累计卖出代币数量
+    pub position: U256,       // 当前持仓数量
+    pub invested: U256,       // 买入总支出（含手续费和gas）
+    pub proceeds: U256,       // 卖出到手金额（已扣手续费，未扣gas）
+    pub platform_fees: U256,  // 平台手续费合计
+    pub gas_costs: U256,      // gas合计
+    pub cost_basis: U256,     // 当前持仓的剩余成本
+    pub realized_pnl: I256,   // 已实现盈亏
+    pub unmatched_sold: U256, // 没有买入记录可匹配的卖出数量（成本按0计算）
+    pub market_value: Option<U256>,   // trySell估值（已扣手续费）
+    pub unrealized_pnl: Option<I256>, // 未实现盈亏
+}
+
+impl PositionPnl {
+    pub fn total_pnl(&self) -> I256 {
+        self.realized_pnl + self.unrealized_pnl.unwrap_or_default()
+    }
+}
+
+// 单个钱包的汇总
+#[derive(Debug, Clone)]
+pub struct WalletPnl {
+    pub wallet: Address,
+    pub tokens: usize,
+    pub invested: U256,
+    pub proceeds: U256,
+    pub platform_fees: U256,
+    pub gas_costs: U256,
+    pub realized_pnl: I256,
+    pub market_value: U256,
+    pub unrealized_pnl: I256,
+}
+
+#[derive(Debug, Clone)]
+pub struct PnlReport {
+    pub method: CostBasisMethod,
+    pub positions: Vec<PositionPnl>,
+    pub wallets: Vec<WalletPnl>,
+}
+
+fn to_i256(value: U256) -> I256 {
+    I256::from_raw(value)
+}
+
+// 根据交易记录计算每个(钱包, 代币)的盈亏，不含未实现部分
+pub fn compute_positions(records: &[TradeRecord], method: CostBasisMethod) -> Vec<PositionPnl> {
+    let mut ordered: Vec<&TradeRecord> = records.iter().collect();
+    ordered.sort_by_key(|r| (r.block_number, r.log_index));
+
+    let mut trackers: BTreeMap<(Address, Address), (CostTracker, PositionPnl)> = BTreeMap::new();
+
+    for record in ordered {
+        let (tracker, pnl) = trackers
+            .entry((record.wallet, record.token))
+            .or_insert_with(|| {
+                (
+                    CostTracker::new(method),
+                    PositionPnl {
+                        wallet: record.wallet,
+                        token: record.token,
+                        bought: U256::zero(),
+                        sold: U256::zero(),
+                        position: U256::zero(),
+                        invested: U256::zero(),
+                        proceeds: U256::zero(),
+                        platform_fees: U256::zero(),
+                        gas_costs: U256::zero(),
+                        cost_basis: U256::zero(),
+                        realized_pnl: I256::zero(),
+                        unmatched_sold: U256::zero(),
+                        market_value: None,
+                        unrealized_pnl: None,
+                    },
+                )
+            });
+
+        pnl.platform_fees += record.platform_fee;
+        pnl.gas_costs += record.gas_cost;
+
+        match record.side {
+            TradeSide::Buy => {
+                let cost = record.net_funds() + record.gas_cost;
+                tracker.buy(record.token_amount, cost);
+                pnl.bought += record.token_amount;
+                pnl.invested += cost;
+            }
+            TradeSide::Sell => {
+                let received = record.net_funds();
+                let (basis, unmatched) = tracker.sell(record.token_amount);
+                pnl.sold += record.token_amount;
+                pnl.proceeds += received;
+                pnl.unmatched_sold += unmatched;
+                pnl.realized_pnl += to_i256(received) - to_i256(record.gas_cost) - to_i256(basis);
+            }
+        }
+
+        pnl.position = tracker.position();
+        pnl.cost_basis = tracker.cost_basis();
+    }
+
+    trackers.into_values().map(|(_, pnl)| pnl).collect()
+}
+
+// 通过trySell对当前持仓估值，计算未实现盈亏
+pub async fn mark_to_market<M: Middleware + 'static>(
+    client: Arc<M>,
+    token_manager_helper_address: Address,
+    positions: &mut [PositionPnl],
+) {
+    let helper = ITokenManagerHelper3::new(token_manager_helper_address, client);
+
+    for pnl in positions.iter_mut() {
+        if pnl.position.is_zero() {
+            pnl.market_value = Some(U256::zero());
+            pnl.unrealized_pnl = Some(I256::zero());
+            continue;
+        }
+
+        match helper.try_sell(pnl.token, pnl.position).call().await {
+            Ok((_, _, funds, fee)) => {
+                let value = funds.saturating_sub(fee);
+                pnl.market_value = Some(value);
+                pnl.unrealized_pnl = Some(to_i256(value) - to_i256(pnl.cost_basis));
+            }
+            Err(e) => {
+                // 已上PancakeSwap等情况trySell会失败，此时不估值
+                logln!("⚠️  代币 {:?} trySell估值失败: {}", pnl.token, e);
+            }
+        }
+    }
+}
+
+// 汇总生成报告
+pub fn build_report(method: CostBasisMethod, positions: Vec<PositionPnl>) -> PnlReport {
+    let mut wallets: BTreeMap<Address, WalletPnl> = BTreeMap::new();
+
+    for pnl in &positions {
+        let wallet = wallets.entry(pnl.wallet).or_insert_with(|| WalletPnl {
+            wallet: pnl.wallet,
+            tokens: 0,
+            invested: U256::zero(),
+            proceeds: U256::zero(),
+            platform_fees: U256::zero(),
+            gas_costs: U256::zero(),
+            realized_pnl: I256::zero(),
+            market_value: U256::zero(),
+            unrealized_pnl: I256::zero(),
+        });
+
+        wallet.tokens += 1;
+        wallet.invested += pnl.invested;
+        wallet.proceeds += pnl.proceeds;
+        wallet.platform_fees += pnl.platform_fees;
+        wallet.gas_costs += pnl.gas_costs;
+        wallet.realized_pnl += pnl.realized_pnl;
+        wallet.market_value += pnl.market_value.unwrap_or_default();
+        wallet.unrealized_pnl += pnl.unrealized_pnl.unwrap_or_default();
+    }
+
+    PnlReport {
+        method,
+        positions,
+        wallets: wallets.into_values().collect(),
+    }
+}
+
+fn format_signed_ether(value: I256) -> String {
+    if value.is_negative() {
+        format!("-{}", format_ether(value.unsigned_abs()))
+    } else {
+        format_ether(value.into_raw())
+    }
+}
+
+// 导出行：金额统一为BNB十进制字符串，代币数量为原始精度整数
+#[derive(Debug, Serialize)]
+struct PositionRow {
+    wallet: String,
+    token: String,
+    method: String,
+    bought: String,
+    sold: String,
+    position: String,
+    invested_bnb: String,
+    proceeds_bnb: String,
+    platform_fees_bnb: String,
+    gas_costs_bnb: String,
+    cost_basis_bnb: String,
+    realized_pnl_bnb: String,
+    market_value_bnb: String,
+    unrealized_pnl_bnb: String,
+    total_pnl_bnb: String,
+    unmatched_sold: String,
+}
+
+#[derive(Debug, Serialize)]
+struct WalletRow {
+    wallet: String,
+    tokens: usize,
+    invested_bnb: String,
+    proceeds_bnb: String,
+    platform_fees_bnb: String,
+    gas_costs_bnb: String,
+    realized_pnl_bnb: String,
+    market_value_bnb: String,
+    unrealized_pnl_bnb: String,
+    total_pnl_bnb: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportJson {
+    method: String,
+    generated_at: String,
+    positions: Vec<PositionRow>,
+    wallets: Vec<WalletRow>,
+}
+
+fn position_row(method: CostBasisMethod, pnl: &PositionPnl) -> PositionRow {
+    PositionRow {
+        wallet: format!("{:?}", pnl.wallet),
+        token: format!("{:?}", pnl.token),
+        method: method.to_string(),
+        bought: pnl.bought.to_string(),
+        sold: pnl.sold.to_string(),
+        position: pnl.position.to_string(),
+        invested_bnb: format_ether(pnl.invested),
+        proceeds_bnb: format_ether(pnl.proceeds),
+        platform_fees_bnb: format_ether(pnl.platform_fees),
+        gas_costs_bnb: format_ether(pnl.gas_costs),
+        cost_basis_bnb: format_ether(pnl.cost_basis),
+        realized_pnl_bnb: format_signed_ether(pnl.realized_pnl),
+        market_value_bnb: pnl.market_value.map(format_ether).unwrap_or_default(),
+        unrealized_pnl_bnb: pnl.unrealized_pnl.map(format_signed_ether).unwrap_or_default(),
+        total_pnl_bnb: format_signed_ether(pnl.total_pnl()),
+        unmatched_sold: pnl.unmatched_sold.to_string(),
+    }
+}
+
+fn wallet_row(wallet: &WalletPnl) -> WalletRow {
+    WalletRow {
+        wallet: format!("{:?}", wallet.wallet),
+        tokens: wallet.tokens,
+        invested_bnb: format_ether(wallet.invested),
+        proceeds_bnb: format_ether(wallet.proceeds),
+        platform_fees_bnb: format_ether(wallet.platform_fees),
+        gas_costs_bnb: format_ether(wallet.gas_costs),
+        realized_pnl_bnb: format_signed_ether(wallet.realized_pnl),
+        market_value_bnb: format_ether(wallet.market_value),
+        unrealized_pnl_bnb: format_signed_ether(wallet.unrealized_pnl),
+        total_pnl_bnb: format_signed_ether(wallet.realized_pnl + wallet.unrealized_pnl),
+    }
+}
+
+fn ensure_parent_dir(path: &str) -> std::io::Result<()> {
+    match std::path::Path::new(path).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => std::fs::create_dir_all(parent),
+        _ => Ok(()),
+    }
+}
+
+// 导出CSV（每行一个钱包+代币）
+pub fn export_csv(report: &PnlReport, path: &str) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    ensure_parent_dir(path)?;
+    let mut writer = csv::Writer::from_path(path)?;
+    for pnl in &report.positions {
+        writer.serialize(position_row(report.method, pnl))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// 报告的JSON形式（包含代币明细和钱包汇总），导出文件和--output json共用
+pub fn report_json(report: &PnlReport) -> ReportJson {
+    ReportJson {
+        method: report.method.to_string(),
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        positions: report.positions.iter().map(|p| position_row(report.method, p)).collect(),
+        wallets: report.wallets.iter().map(wallet_row).collect(),
+    }
+}
+
+// 导出JSON
+pub fn export_json(report: &PnlReport, path: &str) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    ensure_parent_dir(path)?;
+    std::fs::write(path, serde_json::to_string_pretty(&report_json(report))?)?;
+    Ok(())
+}
+
+// 打印报告
+pub fn print_report(report: &PnlReport) {
+    logln!("\n📊 盈亏报告 (成本计算方式: {})", report.method);
+
+    for pnl in &report.positions {
+        logln!("\n钱包: {:?}", pnl.wallet);
+        logln!("  代币: {:?}", pnl.token);
+        logln!("  买入数量: {} / 卖出数量: {} / 当前持仓: {}", pnl.bought, pnl.sold, pnl.position);
+        logln!("  买入总支出: {} BNB", format_ether(pnl.invested));
+        logln!("  卖出到手: {} BNB", format_ether(pnl.proceeds));
+        logln!("  平台手续费: {} BNB, gas: {} BNB", format_ether(pnl.platform_fees), format_ether(pnl.gas_costs));
+        logln!("  持仓成本: {} BNB", format_ether(pnl.cost_basis));
+        logln!("  已实现盈亏: {} BNB", format_signed_ether(pnl.realized_pnl));
+        match (pnl.market_value, pnl.unrealized_pnl) {
+            (Some(value), Some(unrealized)) => {
+                logln!("  持仓估值: {} BNB", format_ether(value));
+                logln!("  未实现盈亏: {} BNB", format_signed_ether(unrealized));
+            }
+            _ => logln!("  持仓估值: 无法获取"),
+        }
+        if !pnl.unmatched_sold.is_zero() {
+            logln!("  ⚠️  有 {} 个代币的卖出没有对应买入记录，成本按0计算", pnl.unmatched_sold);
+        }
+    }
+
+    logln!("\n📋 钱包汇总:");
+    for wallet in &report.wallets {
+        logln!(
+            "  {:?}: 代币数 {}, 已实现 {} BNB, 未实现 {} BNB, 合计 {} BNB",
+            wallet.wallet,
+            wallet.tokens,
+            format_signed_ether(wallet.realized_pnl),
+            format_signed_ether(wallet.unrealized_pnl),
+            format_signed_ether(wallet.realized_pnl + wallet.unrealized_pnl),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLET: Address = Address::repeat_byte(0x11);
+    const TOKEN: Address = Address::repeat_byte(0x22);
+
+    fn trade(side: TradeSide, wallet: Address, block: u64, amount: u64, funds: u64, fee: u64, gas: u64) -> TradeRecord {
+        TradeRecord {
+            side,
+            wallet,
+            token: TOKEN,
+            tx_hash: H256::from_low_u64_be(block),
+            log_index: 0,
+            block_number: block,
+            timestamp: 1_700_000_000 + block * 3,
+            token_amount: U256::from(amount),
+            funds: U256::from(funds),
+            platform_fee: U256::from(fee),
+            gas_cost: U256::from(gas),
+        }
+    }
+
+    // 两次买入后卖出150个，跨越两个批次；故意打乱顺序，按区块排序后计算
+    fn two_buys_then_partial_sell() -> Vec<TradeRecord> {
+        vec![
+            trade(TradeSide::Sell, WALLET, 3, 150, 3000, 30, 6),
+            trade(TradeSide::Buy, WALLET, 1, 100, 1000, 10, 5),
+            trade(TradeSide::Buy, WALLET, 2, 100, 2000, 20, 5),
+        ]
+    }
+
+    fn single(records: &[TradeRecord], method: CostBasisMethod) -> PositionPnl {
+        let mut positions = compute_positions(records, method);
+        assert_eq!(positions.len(), 1);
+        positions.pop().unwrap()
+    }
+
+    #[test]
+    fn fifo_sell_consumes_oldest_lot_first() {
+        let pnl = single(&two_buys_then_partial_sell(), CostBasisMethod::Fifo);
+
+        // 买入成本含手续费和gas：1015 + 2025；卖出到手 3000 - 30
+        assert_eq!(pnl.invested, U256::from(3040));
+        assert_eq!(pnl.proceeds, U256::from(2970));
+        assert_eq!(pnl.platform_fees, U256::from(60));
+        assert_eq!(pnl.gas_costs, U256::from(16));
+        // 卖出成本 = 第一批全部1015 + 第二批一半 2025 * 50 / 100 = 1012
+        assert_eq!(pnl.realized_pnl, I256::from(2970 - 6 - (1015 + 1012)));
+        assert_eq!(pnl.position, U256::from(50));
+        assert_eq!(pnl.cost_basis, U256::from(2025 - 1012));
+        assert_eq!(pnl.unmatched_sold, U256::zero());
+    }
+
+    #[test]
+    fn average_sell_uses_blended_cost() {
+        let pnl = single(&two_buys_then_partial_sell(), CostBasisMethod::Average);
+
+        // 均价成本 3040 / 200，卖出150个对应 3040 * 150 / 200 = 2280
+        assert_eq!(pnl.realized_pnl, I256::from(2970 - 6 - 2280));
+        assert_eq!(pnl.position, U256::from(50));
+        assert_eq!(pnl.cost_basis, U256::from(3040 - 2280));
+        assert_eq!(pnl.invested, U256::from(3040));
+        assert_eq!(pnl.proceeds, U256::from(2970));
+    }
+
+    #[test]
+    fn losing_sell_is_negative_after_fee_and_gas() {
+        let records = vec![
+            trade(TradeSide::Buy, WALLET, 1, 100, 1000, 10, 5),
+            trade(TradeSide::Sell, WALLET, 2, 100, 1000, 10, 5),
+        ];
+        for method in [CostBasisMethod::Fifo, CostBasisMethod::Average] {
+            let pnl = single(&records, method);
+            // 同价买卖，亏掉两次手续费和两次gas
+            assert_eq!(pnl.realized_pnl, I256::from(-30));
+            assert_eq!(pnl.position, U256::zero());
+            assert_eq!(pnl.cost_basis, U256::zero());
+        }
+    }
+
+    #[test]
+    fn sells_without_matching_buys_have_zero_cost() {
+        let other = Address::repeat_byte(0x33);
+        let records = vec![
+            // 没有任何买入记录
+            trade(TradeSide::Sell, other, 1, 40, 400, 4, 2),
+            // 卖出多于买入的部分
+            trade(TradeSide::Buy, WALLET, 2, 100, 1000, 10, 5),
+            trade(TradeSide::Sell, WALLET, 3, 130, 2600, 26, 4),
+        ];
+        let positions = compute_positions(&records, CostBasisMethod::Fifo);
+        assert_eq!(positions.len(), 2);
+
+        let wallet = positions.iter().find(|p| p.wallet == WALLET).unwrap();
+        assert_eq!(wallet.unmatched_sold, U256::from(30));
+        assert_eq!(wallet.realized_pnl, I256::from(2574 - 4 - 1015));
+        assert_eq!(wallet.position, U256::zero());
+
+        let other = positions.iter().find(|p| p.wallet == other).unwrap();
+        assert_eq!(other.unmatched_sold, U256::from(40));
+        assert_eq!(other.realized_pnl, I256::from(396 - 2));
+        assert_eq!(other.sold, U256::from(40));
+        assert_eq!(other.bought, U256::zero());
+
+        let report = build_report(CostBasisMethod::Fifo, positions);
+        assert_eq!(report.wallets.len(), 2);
+        assert_eq!(report.wallets.iter().map(|w| w.tokens).sum::<usize>(), 2);
+    }
+
+    #[test]
+    fn parses_cost_basis_method() {
+        assert_eq!("fifo".parse::<CostBasisMethod>().unwrap(), CostBasisMethod::Fifo);
+        assert_eq!("avg".parse::<CostBasisMethod>().unwrap(), CostBasisMethod::Average);
+        assert!("lifo".parse::<CostBasisMethod>().is_err());
+    }
+}
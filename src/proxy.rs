@@ -0,0 +1,345 @@
+// TokenTradeWithFee代理路由（fourmeme/1.sol）
+// 买卖通过我们部署的路由合约转发到TokenManager2，路由按feeRate（百分比，最高5%）收取手续费：
+// - 买入：msg.value的feeRate%转给feeRecipient，其余资金在TokenManager2买入，代币直接到调用者
+// - 卖出：调用者需先授权给TokenManager（不是路由），路由以feeRate*100（基点）调用sellToken
+// setFee只有部署者（owner）可以调用。
+
+use ethers::{
+    prelude::*,
+    types::{Address, H256, U256},
+    utils::format_ether,
+};
+use std::sync::Arc;
+use serde::Serialize;
+
+use crate::trading::SellResult;
+use crate::{journal, sell_fee, sender};
+use crate::config::{RuntimeConfig, PROXY_FEE_RATE_MAX};
+use crate::contracts::{FeeSetFilter, IERC20, ProxyTokenPurchaseFilter, ProxyTokenSoldFilter, TokenTradeWithFee};
+
+const PERCENT: u64 = 100;
+const BPS_DENOMINATOR: u64 = 10_000;
+
+#[derive(Debug, Serialize)]
+pub struct ProxyFeeConfig {
+    pub owner: Address,
+    pub fee_rate: U256, // 百分比
+    pub fee_recipient: Address,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProxyBuyResult {
+    pub tx_hash: H256,
+    pub block_number: Option<U64>,
+    pub bnb_spent: U256,
+    pub fee_paid: U256,
+    pub tokens_received: U256,
+    pub min_amount: U256,
+}
+
+// 路由的feeRate是百分比，sellTokenWithFee以feeRate*100作为基点传给sellToken
+pub fn fee_rate_bps(fee_rate: U256) -> U256 {
+    fee_rate * U256::from(PERCENT)
+}
+
+// 买入手续费：msg.value * feeRate / 100，与buyTokenWithFee一致
+pub fn buy_fee(funds: U256, fee_rate: U256) -> U256 {
+    funds * fee_rate / U256::from(PERCENT)
+}
+
+// 卖出手续费：TokenManager按基点从扣除平台手续费后的成交额中收取
+pub fn sell_fee(funds: U256, fee_rate: U256) -> U256 {
+    funds * fee_rate_bps(fee_rate) / U256::from(BPS_DENOMINATOR)
+}
+
+// ProxyTokenPurchase事件。注意合约把amountMsgValue（转给TokenManager的BNB）写进了tokenAmount字段，
+// 这里按实际含义解码为funds，代币数量需要看同一交易的TokenPurchase或余额变化
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyPurchase {
+    pub token: Address,
+    pub account: Address,
+    pub funds: U256,
+    pub fee: U256,
+}
+
+pub fn decode_purchase(log: &Log) -> Option<ProxyPurchase> {
+    let event = <ProxyTokenPurchaseFilter as EthEvent>::decode_log(&ethers::abi::RawLog::from(log.clone())).ok()?;
+    Some(ProxyPurchase {
+        token: event.token,
+        account: event.account,
+        funds: event.token_amount,
+        fee: event.fee_amount,
+    })
+}
+
+// ProxyTokenSold事件。fundsReceived字段的值是调用者传入的minFunds，不是实际到手金额，
+// 成交额需要看同一交易的TokenSale
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxySold {
+    pub token: Address,
+    pub amount: U256,
+    pub min_funds: U256,
+}
+
+pub fn decode_sold(log: &Log) -> Option<ProxySold> {
+    let event = <ProxyTokenSoldFilter as EthEvent>::decode_log(&ethers::abi::RawLog::from(log.clone())).ok()?;
+    Some(ProxySold {
+        token: event.token,
+        amount: event.amount,
+        min_funds: event.funds_received,
+    })
+}
+
+pub async fn fee_config<M: Middleware + 'static>(
+    client: Arc<M>,
+    router_address: Address,
+) -> std::result::Result<ProxyFeeConfig, Box<dyn std::error::Error>> {
+    let router = TokenTradeWithFee::new(router_address, client);
+    Ok(ProxyFeeConfig {
+        owner: router.owner().call().await?,
+        fee_rate: router.fee_rate().call().await?,
+        fee_recipient: router.fee_recipient().call().await?,
+    })
+}
+
+pub fn print_fee_config(router_address: Address, config: &ProxyFeeConfig) {
+    logln!("  路由合约: {:?}", router_address);
+    logln!("  owner: {:?}", config.owner);
+    logln!("  手续费率: {}%", config.fee_rate);
+    logln!("  手续费接收地址: {:?}", config.fee_recipient);
+}
+
+// 设置手续费（仅owner），发送前在本地检查权限和上限，避免白白消耗gas
+pub async fn set_fee<M: Middleware + 'static>(
+    client: Arc<M>,
+    router_address: Address,
+    fee_rate: u64,
+    fee_recipient: Address,
+) -> std::result::Result<H256, Box<dyn std::error::Error>> {
+    if fee_rate > PROXY_FEE_RATE_MAX {
+        return Err(format!("手续费率 {}% 超过合约上限 {}%", fee_rate, PROXY_FEE_RATE_MAX).into());
+    }
+    if fee_recipient.is_zero() {
+        return Err("手续费接收地址不能为零地址".into());
+    }
+    let router = TokenTradeWithFee::new(router_address, client.clone());
+    let owner = router.owner().call().await?;
+    if owner != sender(&*client)? {
+        return Err(format!("只有路由owner {:?} 可以设置手续费，当前钱包 {:?}", owner, sender(&*client)?).into());
+    }
+
+    let set_fee_tx = router.set_fee(U256::from(fee_rate), fee_recipient);
+    let pending = set_fee_tx.send().await?;
+    let tx_hash = pending.tx_hash();
+    logln!("✅ setFee交易已发送: {:?}", tx_hash);
+    let receipt = pending.await?.ok_or("setFee交易未被打包")?;
+    if receipt.status != Some(U64::one()) {
+        return Err(format!("setFee交易回滚: {:?}", tx_hash).into());
+    }
+    for log in &receipt.logs {
+        if let Ok(event) = parse_log::<FeeSetFilter>(log.clone()) {
+            logln!("✅ 手续费已更新: {}% -> {:?}", event.fee_rate, event.fee_recipient);
+        }
+    }
+    Ok(tx_hash)
+}
+
+// 通过路由买入：按扣除手续费后的资金预估数量，最少获得数量按滑点折算
+pub async fn buy<M: Middleware + 'static>(
+    client: Arc<M>,
+    router_address: Address,
+    token_address: Address,
+    funds: U256,
+    slippage_percent: u64,
+    config: &RuntimeConfig,
+) -> std::result::Result<ProxyBuyResult, Box<dyn std::error::Error>> {
+    let router = TokenTradeWithFee::new(router_address, client.clone());
+    let token = IERC20::new(token_address, client.clone());
+    let user_address = sender(&*client)?;
+
+    let fee_rate = router.fee_rate().call().await?;
+    let fee = buy_fee(funds, fee_rate);
+    let funds_after_fee = funds.saturating_sub(fee);
+    if funds_after_fee.is_zero() {
+        return Err("买入资金不足以支付路由手续费".into());
+    }
+
+    let (_, _, estimated_amount, _, _, _, _, _) = router.try_buy(token_address, U256::zero(), funds_after_fee).call().await?;
+    let min_amount = estimated_amount * U256::from(100 - slippage_percent.min(100)) / U256::from(100);
+    logln!(
+        "  路由买入: {} BNB (手续费 {}% = {} BNB)，预计获得 {}，最少 {} (滑点 {}%)",
+        format_ether(funds),
+        fee_rate,
+        format_ether(fee),
+        format_ether(estimated_amount),
+        format_ether(min_amount),
+        slippage_percent
+    );
+
+    let before_token_balance = token.balance_of(user_address).call().await?;
+
+    let buy_tx = router.buy_token_with_fee(token_address, min_amount).value(funds);
+    let pending = buy_tx.send().await?;
+    let tx_hash = pending.tx_hash();
+    logln!("✅ buyTokenWithFee交易已发送: {:?}", tx_hash);
+    let receipt = pending.await?.ok_or("buyTokenWithFee交易未被打包")?;
+    if receipt.status != Some(U64::one()) {
+        return Err(format!("buyTokenWithFee交易回滚: {:?}", tx_hash).into());
+    }
+    logln!("✅ buyTokenWithFee交易确认! 区块: {:?}", receipt.block_number);
+    journal::record_receipt(&client, config.trade_journal.as_deref(), &receipt).await;
+
+    // 实际手续费以路由事件为准
+    let fee_paid = receipt
+        .logs
+        .iter()
+        .filter(|log| log.address == router_address)
+        .find_map(decode_purchase)
+        .map(|event| event.fee)
+        .unwrap_or(fee);
+
+    let after_token_balance = token.balance_of(user_address).call().await?;
+    Ok(ProxyBuyResult {
+        tx_hash,
+        block_number: receipt.block_number,
+        bnb_spent: funds,
+        fee_paid,
+        tokens_received: after_token_balance.saturating_sub(before_token_balance),
+        min_amount,
+    })
+}
+
+// 通过路由卖出全部持仓：授权给TokenManager后调用sellTokenWithFee
+pub async fn sell<M: Middleware + 'static>(
+    client: Arc<M>,
+    router_address: Address,
+    token_address: Address,
+    slippage_percent: u64,
+    config: &RuntimeConfig,
+) -> std::result::Result<SellResult, Box<dyn std::error::Error>> {
+    let router = TokenTradeWithFee::new(router_address, client.clone());
+    let token = IERC20::new(token_address, client.clone());
+    let user_address = sender(&*client)?;
+
+    let amount = token.balance_of(user_address).call().await?;
+    if amount.is_zero() {
+        return Err("没有代币可以卖出!".into());
+    }
+
+    let (_, token_manager_address, ..) = router.get_token_info(token_address).call().await?;
+    let (_, _, funds, trade_fee) = router.try_sell(token_address, amount).call().await?;
+    let fee_rate = router.fee_rate().call().await?;
+    let net = funds.saturating_sub(trade_fee);
+    let router_fee = sell_fee(net, fee_rate);
+    let min_funds = net.saturating_sub(router_fee) * U256::from(100 - slippage_percent.min(100)) / U256::from(100);
+    logln!(
+        "  路由卖出: {}，预计获得 {} BNB (平台手续费 {} BNB，路由手续费 {}% ≈ {} BNB)，最少 {} BNB",
+        amount,
+        format_ether(net.saturating_sub(router_fee)),
+        format_ether(trade_fee),
+        fee_rate,
+        format_ether(router_fee),
+        format_ether(min_funds)
+    );
+
+    let mut approve_tx_hash = None;
+    let allowance = token.allowance(user_address, token_manager_address).call().await?;
+    if allowance < amount {
+        logln!("🔹 授权代币给TokenManager {:?}", token_manager_address);
+        let approve_tx = token.approve(token_manager_address, amount);
+        let pending = approve_tx.send().await?;
+        approve_tx_hash = Some(pending.tx_hash());
+        let receipt = pending.await?.ok_or("approve交易未被打包")?;
+        if receipt.status != Some(U64::one()) {
+            return Err(format!("approve交易回滚: {:?}", receipt.transaction_hash).into());
+        }
+        logln!("✅ approve交易确认! 区块: {:?}", receipt.block_number);
+    }
+
+    let before_bnb_balance = client.get_balance(user_address, None).await?;
+    let sell_tx = router.sell_token_with_fee(token_address, amount, min_funds);
+    let pending = sell_tx.send().await?;
+    let sell_tx_hash = pending.tx_hash();
+    logln!("✅ sellTokenWithFee交易已发送: {:?}", sell_tx_hash);
+    let receipt = pending.await?.ok_or("sellTokenWithFee交易未被打包")?;
+    if receipt.status != Some(U64::one()) {
+        return Err(format!("sellTokenWithFee交易回滚: {:?}", sell_tx_hash).into());
+    }
+    logln!("✅ sellTokenWithFee交易确认! 区块: {:?}", receipt.block_number);
+    journal::record_receipt(&client, config.trade_journal.as_deref(), &receipt).await;
+
+    // 到账BNB扣除本笔交易的gas
+    let after_bnb_balance = client.get_balance(user_address, None).await?;
+    let gas_cost = receipt.gas_used.unwrap_or_default() * receipt.effective_gas_price.unwrap_or_default();
+    let bnb_received = (after_bnb_balance + gas_cost).saturating_sub(before_bnb_balance);
+    let fees = sell_fee::sale_fees(&receipt, token_address, user_address, bnb_received);
+
+    Ok(SellResult {
+        token_sold: amount,
+        bnb_received,
+        sell_tx_hash,
+        block_number: receipt.block_number.map(|n| U256::from(n.as_u64())),
+        approve_tx_hash,
+        platform_fee: fees.map(|f| f.platform_fee),
+        third_party_fee: fees.map(|f| f.third_party_fee),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::Token;
+    use ethers::utils::parse_ether;
+
+    const TOKEN: Address = Address::repeat_byte(0x44);
+    const ACCOUNT: Address = Address::repeat_byte(0x11);
+
+    fn log(topics: Vec<H256>, data: Vec<Token>) -> Log {
+        Log {
+            address: Address::repeat_byte(0x99),
+            topics,
+            data: ethers::abi::encode(&data).into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn percent_fee_rate_is_sent_as_bps() {
+        assert_eq!(fee_rate_bps(U256::from(2)), U256::from(200));
+        assert_eq!(fee_rate_bps(U256::from(PROXY_FEE_RATE_MAX)), U256::from(500));
+
+        // 2%：买入按msg.value计算，卖出按成交额的200基点计算，结果一致
+        let funds = parse_ether(1u64).unwrap();
+        assert_eq!(buy_fee(funds, U256::from(2)), parse_ether(0.02).unwrap());
+        assert_eq!(sell_fee(funds, U256::from(2)), parse_ether(0.02).unwrap());
+        assert_eq!(sell_fee(U256::from(99), U256::from(3)), U256::from(2));
+        assert_eq!(sell_fee(funds, U256::zero()), U256::zero());
+    }
+
+    #[test]
+    fn purchase_token_amount_field_holds_msg_value() {
+        let msg_value = parse_ether(0.98).unwrap();
+        let fee = parse_ether(0.02).unwrap();
+        let purchase = log(
+            vec![ProxyTokenPurchaseFilter::signature(), H256::from(TOKEN), H256::from(ACCOUNT)],
+            vec![Token::Uint(msg_value), Token::Uint(fee)],
+        );
+        assert_eq!(
+            decode_purchase(&purchase),
+            Some(ProxyPurchase { token: TOKEN, account: ACCOUNT, funds: msg_value, fee })
+        );
+        assert_eq!(decode_sold(&purchase), None);
+    }
+
+    #[test]
+    fn sold_funds_received_field_holds_min_funds() {
+        let amount = parse_ether(1_000_000u64).unwrap();
+        let min_funds = parse_ether(0.9).unwrap();
+        let sold = log(
+            vec![ProxyTokenSoldFilter::signature(), H256::from(TOKEN)],
+            vec![Token::Uint(amount), Token::Uint(min_funds)],
+        );
+        assert_eq!(decode_sold(&sold), Some(ProxySold { token: TOKEN, amount, min_funds }));
+        assert_eq!(decode_purchase(&sold), None);
+    }
+}
@@ -0,0 +1,569 @@
+// 代理路由收入统计
+// 增量索引TokenTradeWithFee路由的ProxyTokenPurchase/ProxyTokenSold/FeeSet事件，保存在 data/router/<路由地址>/：
+// - 买入手续费直接取自ProxyTokenPurchase.feeAmount
+// - ProxyTokenSold不含卖家和手续费，卖家取交易发送者，手续费按同一交易中TokenSale的成交金额和当时的feeRate计算
+// - 有手续费的区块记录feeRecipient在该区块前后的余额（需要归档节点），用于和事件手续费对账
+// 报告按天、代币、用户汇总手续费和成交额。
+
+use chrono::DateTime;
+use ethers::{
+    prelude::*,
+    types::{Address, H256, U256},
+    utils::format_ether,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use crate::{history, proxy};
+use crate::contracts::{FeeSetFilter, ProxyTokenPurchaseFilter, ProxyTokenSoldFilter, TokenSaleFilter, TokenTradeWithFee};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RouterEventKind {
+    Purchase {
+        token: Address,
+        account: Address,
+        funds: U256, // 实际用于买入的BNB（事件的tokenAmount字段，值为amountMsgValue）
+        fee: U256,
+    },
+    Sold {
+        token: Address,
+        account: Address, // 交易发送者
+        amount: U256,
+        funds: U256, // TokenSale中的成交金额，找不到时为0
+        fee: U256,   // 按feeRate计算
+    },
+    FeeSet {
+        fee_rate: U256,
+        fee_recipient: Address,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouterEvent {
+    pub block_number: u64,
+    pub log_index: u64,
+    pub tx_hash: H256,
+    pub timestamp: u64,
+    pub fee_recipient: Address, // 事件发生时的手续费接收地址
+    #[serde(flatten)]
+    pub kind: RouterEventKind,
+}
+
+impl RouterEvent {
+    pub fn fee(&self) -> U256 {
+        match &self.kind {
+            RouterEventKind::Purchase { fee, .. } | RouterEventKind::Sold { fee, .. } => *fee,
+            RouterEventKind::FeeSet { .. } => U256::zero(),
+        }
+    }
+}
+
+// feeRecipient在某个区块前后的余额，None表示节点不支持历史状态查询
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecipientBalance {
+    pub block_number: u64,
+    pub timestamp: u64,
+    pub fee_recipient: Address,
+    pub before: Option<U256>,
+    pub after: Option<U256>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexState {
+    last_block: u64,
+    fee_rate: U256,
+    fee_recipient: Address,
+}
+
+// 单个路由的事件存储目录
+pub struct RouterStore {
+    dir: PathBuf,
+}
+
+impl RouterStore {
+    pub fn new(root: impl AsRef<Path>, router: Address) -> Self {
+        Self {
+            dir: root.as_ref().join(format!("{:?}", router)),
+        }
+    }
+
+    fn state_path(&self) -> PathBuf {
+        self.dir.join("state.json")
+    }
+
+    fn events_path(&self) -> PathBuf {
+        self.dir.join("events.jsonl")
+    }
+
+    fn balances_path(&self) -> PathBuf {
+        self.dir.join("recipient_balances.jsonl")
+    }
+
+    pub fn report_path(&self) -> PathBuf {
+        self.dir.join("revenue.json")
+    }
+
+    fn load_state(&self) -> std::result::Result<Option<IndexState>, Box<dyn std::error::Error>> {
+        let path = self.state_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&std::fs::read_to_string(path)?)?))
+    }
+
+    fn save_state(&self, state: &IndexState) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.state_path(), serde_json::to_string(state)?)?;
+        Ok(())
+    }
+
+    fn append<T: Serialize>(&self, path: PathBuf, items: &[T]) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(&self.dir)?;
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        for item in items {
+            writeln!(file, "{}", serde_json::to_string(item)?)?;
+        }
+        Ok(())
+    }
+
+    fn load<T: for<'de> Deserialize<'de>>(&self, path: PathBuf) -> std::result::Result<Vec<T>, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let mut items = Vec::new();
+        for line in BufReader::new(std::fs::File::open(path)?).lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                items.push(serde_json::from_str(&line)?);
+            }
+        }
+        Ok(items)
+    }
+
+    // 读取全部事件，按区块排序并去重（中断重跑可能重复写入同一页）
+    pub fn load_events(&self) -> std::result::Result<Vec<RouterEvent>, Box<dyn std::error::Error>> {
+        let mut events: Vec<RouterEvent> = self.load(self.events_path())?;
+        events.sort_by_key(|e| (e.block_number, e.log_index));
+        events.dedup_by_key(|e| (e.block_number, e.log_index));
+        Ok(events)
+    }
+
+    pub fn load_balances(&self) -> std::result::Result<Vec<RecipientBalance>, Box<dyn std::error::Error>> {
+        let mut balances: Vec<RecipientBalance> = self.load(self.balances_path())?;
+        balances.sort_by_key(|b| (b.block_number, b.fee_recipient));
+        balances.dedup_by_key(|b| (b.block_number, b.fee_recipient));
+        Ok(balances)
+    }
+}
+
+async fn block_timestamp<M: Middleware + 'static>(
+    client: &M,
+    block_times: &mut HashMap<u64, u64>,
+    block_number: u64,
+) -> std::result::Result<u64, Box<dyn std::error::Error>> {
+    if let Some(ts) = block_times.get(&block_number) {
+        return Ok(*ts);
+    }
+    let ts = client
+        .get_block(block_number)
+        .await?
+        .map(|block| block.timestamp.as_u64())
+        .unwrap_or_default();
+    block_times.insert(block_number, ts);
+    Ok(ts)
+}
+
+// 首次索引时读取起始区块前的手续费设置，节点不支持历史状态时退回当前设置
+async fn initial_fee_state<M: Middleware + 'static>(
+    client: &std::sync::Arc<M>,
+    router_address: Address,
+    before_block: u64,
+) -> std::result::Result<(U256, Address), Box<dyn std::error::Error>> {
+    let router = TokenTradeWithFee::new(router_address, client.clone());
+    let block = BlockId::from(before_block);
+    match (
+        router.fee_rate().block(block).call().await,
+        router.fee_recipient().block(block).call().await,
+    ) {
+        (Ok(rate), Ok(recipient)) => Ok((rate, recipient)),
+        _ => {
+            logln!("  ⚠️  无法读取区块 {} 的手续费设置（需要归档节点），使用当前设置", before_block);
+            Ok((router.fee_rate().call().await?, router.fee_recipient().call().await?))
+        }
+    }
+}
+
+// 增量索引路由事件，返回新增事件数
+pub async fn index_router<M: Middleware + 'static>(
+    client: &std::sync::Arc<M>,
+    router_address: Address,
+    from_block: Option<u64>,
+    block_chunk: u64,
+    store: &RouterStore,
+) -> std::result::Result<usize, Box<dyn std::error::Error>> {
+    let mut state = match (store.load_state()?, from_block) {
+        (Some(state), _) => state,
+        (None, Some(block)) => {
+            let (fee_rate, fee_recipient) = initial_fee_state(client, router_address, block.saturating_sub(1)).await?;
+            IndexState {
+                last_block: block.saturating_sub(1),
+                fee_rate,
+                fee_recipient,
+            }
+        }
+        (None, None) => return Err("首次索引需要指定起始区块（路由部署区块）".into()),
+    };
+    let start_block = state.last_block + 1;
+    let latest_block = client.get_block_number().await?.as_u64();
+    if start_block > latest_block {
+        return Ok(0);
+    }
+
+    let topics = [
+        ProxyTokenPurchaseFilter::signature(),
+        ProxyTokenSoldFilter::signature(),
+        FeeSetFilter::signature(),
+    ];
+    let mut block_times: HashMap<u64, u64> = HashMap::new();
+    let mut total = 0;
+    let chunk = block_chunk.max(1);
+
+    let mut page_start = start_block;
+    while page_start <= latest_block {
+        let page_end = (page_start + chunk - 1).min(latest_block);
+        let mut logs = history::get_logs_split(client, &[router_address], &topics, page_start, page_end).await?;
+        logs.sort_by_key(|log| (log.block_number, log.log_index));
+
+        let mut events = Vec::new();
+        for log in &logs {
+            let (Some(block_number), Some(tx_hash)) = (log.block_number, log.transaction_hash) else {
+                continue;
+            };
+            let block_number = block_number.as_u64();
+            let raw_log = ethers::abi::RawLog::from(log.clone());
+
+            let kind = if let Some(event) = proxy::decode_purchase(log) {
+                RouterEventKind::Purchase {
+                    token: event.token,
+                    account: event.account,
+                    funds: event.funds,
+                    fee: event.fee,
+                }
+            } else if let Some(event) = proxy::decode_sold(log) {
+                // 卖家和成交金额从同一交易的receipt中取
+                let receipt = client.get_transaction_receipt(tx_hash).await?.ok_or("无法获取卖出交易回执")?;
+                let funds = receipt
+                    .logs
+                    .iter()
+                    .filter_map(|l| parse_log::<TokenSaleFilter>(l.clone()).ok())
+                    .filter(|sale| sale.token == event.token)
+                    .fold(U256::zero(), |sum, sale| sum + sale.cost);
+                RouterEventKind::Sold {
+                    token: event.token,
+                    account: receipt.from,
+                    amount: event.amount,
+                    funds,
+                    fee: proxy::sell_fee(funds, state.fee_rate),
+                }
+            } else if let Ok(event) = <FeeSetFilter as EthEvent>::decode_log(&raw_log) {
+                RouterEventKind::FeeSet {
+                    fee_rate: event.fee_rate,
+                    fee_recipient: event.fee_recipient,
+                }
+            } else {
+                continue;
+            };
+
+            let event = RouterEvent {
+                block_number,
+                log_index: log.log_index.map(|i| i.as_u64()).unwrap_or_default(),
+                tx_hash,
+                timestamp: block_timestamp(client, &mut block_times, block_number).await?,
+                fee_recipient: state.fee_recipient,
+                kind,
+            };
+            if let RouterEventKind::FeeSet { fee_rate, fee_recipient } = &event.kind {
+                state.fee_rate = *fee_rate;
+                state.fee_recipient = *fee_recipient;
+            }
+            events.push(event);
+        }
+
+        // 有手续费的区块记录feeRecipient前后余额
+        let fee_blocks: BTreeSet<(u64, Address)> = events
+            .iter()
+            .filter(|e| !e.fee().is_zero())
+            .map(|e| (e.block_number, e.fee_recipient))
+            .collect();
+        let mut balances = Vec::new();
+        for (block_number, fee_recipient) in fee_blocks {
+            balances.push(RecipientBalance {
+                block_number,
+                timestamp: block_timestamp(client, &mut block_times, block_number).await?,
+                fee_recipient,
+                before: client.get_balance(fee_recipient, Some((block_number - 1).into())).await.ok(),
+                after: client.get_balance(fee_recipient, Some(block_number.into())).await.ok(),
+            });
+        }
+
+        // 先写事件再推进区块进度，中断后不会漏掉事件
+        store.append(store.events_path(), &events)?;
+        store.append(store.balances_path(), &balances)?;
+        state.last_block = page_end;
+        store.save_state(&state)?;
+        total += events.len();
+        logln!("  已索引区块 {}-{}: 路由事件 {} 个", page_start, page_end, events.len());
+
+        page_start = page_end + 1;
+    }
+
+    Ok(total)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RevenueRow {
+    pub key: String,
+    pub buys: u64,
+    pub sells: u64,
+    pub buy_volume: U256,  // 买入BNB
+    pub sell_volume: U256, // 卖出成交BNB
+    pub buy_fees: U256,
+    pub sell_fees: U256,
+}
+
+impl RevenueRow {
+    pub fn fees(&self) -> U256 {
+        self.buy_fees + self.sell_fees
+    }
+
+    fn add(&mut self, event: &RouterEvent) {
+        match &event.kind {
+            RouterEventKind::Purchase { funds, fee, .. } => {
+                self.buys += 1;
+                self.buy_volume += *funds;
+                self.buy_fees += *fee;
+            }
+            RouterEventKind::Sold { funds, fee, .. } => {
+                self.sells += 1;
+                self.sell_volume += *funds;
+                self.sell_fees += *fee;
+            }
+            RouterEventKind::FeeSet { .. } => {}
+        }
+    }
+}
+
+// 按天对账：事件手续费与feeRecipient在这些区块的实际到账
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReconcileRow {
+    pub day: String,
+    pub event_fees: U256,
+    pub received: U256,
+    pub unverified_blocks: u64, // 节点不支持历史余额查询的区块
+    pub mismatched_blocks: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RevenueReport {
+    pub total: RevenueRow,
+    pub by_day: Vec<RevenueRow>,
+    pub by_token: Vec<RevenueRow>,
+    pub by_user: Vec<RevenueRow>,
+    pub fee_changes: Vec<RouterEvent>,
+    pub reconciliation: Vec<ReconcileRow>,
+}
+
+fn day_of(timestamp: u64) -> String {
+    DateTime::from_timestamp(timestamp as i64, 0)
+        .map(|t| t.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+fn group_by(events: &[RouterEvent], key: impl Fn(&RouterEvent) -> Option<String>) -> Vec<RevenueRow> {
+    let mut rows: BTreeMap<String, RevenueRow> = BTreeMap::new();
+    for event in events {
+        if let Some(k) = key(event) {
+            rows.entry(k.clone())
+                .or_insert_with(|| RevenueRow { key: k, ..Default::default() })
+                .add(event);
+        }
+    }
+    rows.into_values().collect()
+}
+
+pub fn build_report(events: &[RouterEvent], balances: &[RecipientBalance]) -> RevenueReport {
+    let mut total = RevenueRow {
+        key: "total".to_string(),
+        ..Default::default()
+    };
+    events.iter().for_each(|e| total.add(e));
+
+    let trade_key = |event: &RouterEvent, by_token: bool| match &event.kind {
+        RouterEventKind::Purchase { token, account, .. } | RouterEventKind::Sold { token, account, .. } => {
+            Some(format!("{:?}", if by_token { token } else { account }))
+        }
+        RouterEventKind::FeeSet { .. } => None,
+    };
+
+    // 每个区块的事件手续费与余额变化比较，余额减少（接收地址同块转出）也视为不一致
+    let mut block_fees: BTreeMap<(u64, Address), U256> = BTreeMap::new();
+    for event in events.iter().filter(|e| !e.fee().is_zero()) {
+        *block_fees.entry((event.block_number, event.fee_recipient)).or_default() += event.fee();
+    }
+    let mut reconciliation: BTreeMap<String, ReconcileRow> = BTreeMap::new();
+    for balance in balances {
+        let day = day_of(balance.timestamp);
+        let row = reconciliation
+            .entry(day.clone())
+            .or_insert_with(|| ReconcileRow { day, ..Default::default() });
+        let event_fees = block_fees
+            .get(&(balance.block_number, balance.fee_recipient))
+            .copied()
+            .unwrap_or_default();
+        row.event_fees += event_fees;
+        match (balance.before, balance.after) {
+            (Some(before), Some(after)) => {
+                let received = after.saturating_sub(before);
+                row.received += received;
+                if after < before || received != event_fees {
+                    row.mismatched_blocks.push(balance.block_number);
+                }
+            }
+            _ => row.unverified_blocks += 1,
+        }
+    }
+
+    RevenueReport {
+        total,
+        by_day: group_by(events, |e| (!matches!(e.kind, RouterEventKind::FeeSet { .. })).then(|| day_of(e.timestamp))),
+        by_token: group_by(events, |e| trade_key(e, true)),
+        by_user: group_by(events, |e| trade_key(e, false)),
+        fee_changes: events
+            .iter()
+            .filter(|e| matches!(e.kind, RouterEventKind::FeeSet { .. }))
+            .cloned()
+            .collect(),
+        reconciliation: reconciliation.into_values().collect(),
+    }
+}
+
+pub fn export_json(report: &RevenueReport, path: impl AsRef<Path>) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(report)?)?;
+    Ok(())
+}
+
+fn print_rows(title: &str, rows: &[RevenueRow]) {
+    logln!("\n{}", title);
+    for row in rows {
+        logln!(
+            "  {}: 买入 {} 笔 {} BNB，卖出 {} 笔 {} BNB，手续费 {} BNB (买 {} / 卖 {})",
+            row.key,
+            row.buys,
+            format_ether(row.buy_volume),
+            row.sells,
+            format_ether(row.sell_volume),
+            format_ether(row.fees()),
+            format_ether(row.buy_fees),
+            format_ether(row.sell_fees)
+        );
+    }
+}
+
+pub fn print_report(report: &RevenueReport) {
+    logln!("\n📊 路由收入报告");
+    print_rows("合计", std::slice::from_ref(&report.total));
+    print_rows("按天", &report.by_day);
+    print_rows("按代币", &report.by_token);
+    print_rows("按用户", &report.by_user);
+
+    if !report.fee_changes.is_empty() {
+        logln!("\n手续费变更");
+        for change in &report.fee_changes {
+            if let RouterEventKind::FeeSet { fee_rate, fee_recipient } = &change.kind {
+                logln!("  {} 区块 {}: {}% -> {:?}", day_of(change.timestamp), change.block_number, fee_rate, fee_recipient);
+            }
+        }
+    }
+
+    logln!("\n对账（事件手续费 vs feeRecipient实际到账）");
+    for row in &report.reconciliation {
+        let status = if !row.mismatched_blocks.is_empty() {
+            format!("⚠️  不一致区块 {:?}", row.mismatched_blocks)
+        } else if row.unverified_blocks > 0 {
+            format!("⚠️  {} 个区块无法查询历史余额", row.unverified_blocks)
+        } else {
+            "✅ 一致".to_string()
+        };
+        logln!(
+            "  {}: 事件 {} BNB，到账 {} BNB {}",
+            row.day,
+            format_ether(row.event_fees),
+            format_ether(row.received),
+            status
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(block_number: u64, timestamp: u64, kind: RouterEventKind) -> RouterEvent {
+        RouterEvent {
+            block_number,
+            log_index: 0,
+            tx_hash: H256::from_low_u64_be(block_number),
+            timestamp,
+            fee_recipient: Address::repeat_byte(0xfe),
+            kind,
+        }
+    }
+
+    #[test]
+    fn report_groups_fees_and_reconciles_against_recipient_balance() {
+        let token = Address::repeat_byte(0x01);
+        let alice = Address::repeat_byte(0xa1);
+        let bob = Address::repeat_byte(0xb0);
+        let day1 = 1_760_000_000; // 2025-10-09
+        let day2 = day1 + 86_400;
+        let ether = |n: u64| U256::exp10(16) * n; // 0.01 BNB单位
+
+        let events = vec![
+            event(10, day1, RouterEventKind::Purchase { token, account: alice, funds: ether(99), fee: ether(1) }),
+            event(11, day1, RouterEventKind::Sold { token, account: bob, amount: U256::from(5), funds: ether(200), fee: ether(2) }),
+            event(12, day2, RouterEventKind::FeeSet { fee_rate: U256::from(3), fee_recipient: Address::repeat_byte(0xfe) }),
+            event(13, day2, RouterEventKind::Purchase { token, account: bob, funds: ether(97), fee: ether(3) }),
+        ];
+        let balance = |block_number, timestamp, before, after| RecipientBalance {
+            block_number,
+            timestamp,
+            fee_recipient: Address::repeat_byte(0xfe),
+            before,
+            after,
+        };
+        let balances = vec![
+            balance(10, day1, Some(ether(0)), Some(ether(1))),
+            balance(11, day1, Some(ether(1)), Some(ether(3))),
+            balance(13, day2, Some(ether(3)), Some(ether(5))), // 少到账0.01
+        ];
+
+        let report = build_report(&events, &balances);
+        assert_eq!(report.total.fees(), ether(6));
+        assert_eq!((report.total.buys, report.total.sells), (2, 1));
+        assert_eq!(report.by_day.len(), 2);
+        assert_eq!(report.by_token.len(), 1);
+        assert_eq!(report.by_user.iter().find(|r| r.key == format!("{:?}", bob)).unwrap().fees(), ether(5));
+        assert_eq!(report.fee_changes.len(), 1);
+
+        assert_eq!(report.reconciliation[0].received, ether(3));
+        assert!(report.reconciliation[0].mismatched_blocks.is_empty());
+        assert_eq!(report.reconciliation[1].mismatched_blocks, vec![13]);
+    }
+}
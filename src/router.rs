@@ -0,0 +1,248 @@
+// TokenTradeWithFee路由部署
+// 取代 fourmeme/test.js 中的Hardhat部署脚本：字节码优先读取编译产物（Hardhat/Foundry artifact或.bin文件），
+// 没有产物时调用本机solc编译 fourmeme/1.sol。部署前在本地校验参数，确认后回读合约状态核对，
+// 每次部署追加一行到 data/router_deployments.jsonl。
+
+use chrono::Utc;
+use ethers::{
+    abi::Abi,
+    prelude::*,
+    types::{Address, Bytes, H256, U256},
+    utils::keccak256,
+};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+
+use crate::config::PROXY_FEE_RATE_MAX;
+use crate::contracts::{TOKENTRADEWITHFEE_ABI, TokenTradeWithFee};
+
+const ROUTER_CONTRACT_NAME: &str = "TokenTradeWithFee";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouterDeployment {
+    pub chain_id: u64,
+    pub address: Address,
+    pub tx_hash: H256,
+    pub block_number: Option<u64>,
+    pub owner: Address,
+    pub token_manager_helper: Address,
+    pub fee_recipient: Address,
+    pub fee_rate: u64,
+    pub bytecode_hash: H256,
+    pub deployed_at: i64,
+}
+
+// 与合约构造函数中的require一致，提前拒绝必然回滚的部署
+pub fn validate_deploy_params(
+    token_manager_helper: Address,
+    fee_recipient: Address,
+    fee_rate: u64,
+) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    if token_manager_helper.is_zero() {
+        return Err("TokenManagerHelper地址不能为零地址".into());
+    }
+    if fee_recipient.is_zero() {
+        return Err("手续费接收地址不能为零地址".into());
+    }
+    if fee_rate > PROXY_FEE_RATE_MAX {
+        return Err(format!("手续费率 {}% 超过合约上限 {}%", fee_rate, PROXY_FEE_RATE_MAX).into());
+    }
+    Ok(())
+}
+
+// 读取编译产物中的部署字节码：
+// - Hardhat artifact: {"bytecode": "0x..."}
+// - Foundry artifact: {"bytecode": {"object": "0x..."}}
+// - 纯十六进制文件（solc --bin 输出）
+pub fn load_bytecode(path: impl AsRef<Path>) -> std::result::Result<Bytes, Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path).map_err(|e| format!("读取编译产物失败 {}: {}", path.display(), e))?;
+    let content = content.trim();
+
+    let hex_code = if content.starts_with('{') {
+        let artifact: serde_json::Value = serde_json::from_str(content)?;
+        let bytecode = &artifact["bytecode"];
+        bytecode
+            .as_str()
+            .or_else(|| bytecode["object"].as_str())
+            .ok_or_else(|| format!("编译产物中没有bytecode字段: {}", path.display()))?
+            .to_string()
+    } else {
+        content.to_string()
+    };
+    parse_bytecode(&hex_code)
+}
+
+// 用本机solc编译合约源码，OpenZeppelin和hardhat依赖从 node_modules 解析
+pub fn compile_with_solc(source: impl AsRef<Path>) -> std::result::Result<Bytes, Box<dyn std::error::Error>> {
+    let source = source.as_ref();
+    let output = Command::new("solc")
+        .args(["--combined-json", "bin", "--base-path", ".", "--include-path", "node_modules"])
+        .arg(source)
+        .output()
+        .map_err(|e| format!("未找到solc，请提供编译产物或安装solc: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("solc编译失败: {}", String::from_utf8_lossy(&output.stderr)).into());
+    }
+
+    let combined: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    let contracts = combined["contracts"].as_object().ok_or("solc输出中没有contracts")?;
+    let suffix = format!(":{}", ROUTER_CONTRACT_NAME);
+    let bin = contracts
+        .iter()
+        .find(|(name, _)| name.ends_with(&suffix))
+        .and_then(|(_, contract)| contract["bin"].as_str())
+        .ok_or_else(|| format!("solc输出中没有 {}", ROUTER_CONTRACT_NAME))?;
+    parse_bytecode(bin)
+}
+
+fn parse_bytecode(hex_code: &str) -> std::result::Result<Bytes, Box<dyn std::error::Error>> {
+    let hex_code = hex_code.trim().trim_start_matches("0x");
+    if hex_code.is_empty() {
+        return Err("字节码为空（合约可能是抽象合约或接口）".into());
+    }
+    if hex_code.contains("__$") {
+        return Err("字节码中有未链接的库引用".into());
+    }
+    Ok(hex::decode(hex_code)?.into())
+}
+
+// 按优先级获取字节码：编译产物存在时直接读取，否则用solc编译源码
+pub fn resolve_bytecode(
+    artifact_path: impl AsRef<Path>,
+    source_path: impl AsRef<Path>,
+) -> std::result::Result<Bytes, Box<dyn std::error::Error>> {
+    let artifact_path = artifact_path.as_ref();
+    if artifact_path.exists() {
+        logln!("  使用编译产物: {}", artifact_path.display());
+        return load_bytecode(artifact_path);
+    }
+    logln!("  未找到编译产物 {}，使用solc编译 {}", artifact_path.display(), source_path.as_ref().display());
+    compile_with_solc(source_path)
+}
+
+// 部署路由并等待确认，部署后回读owner/helper/手续费核对构造参数
+pub async fn deploy<M: Middleware + 'static>(
+    client: Arc<M>,
+    bytecode: Bytes,
+    token_manager_helper: Address,
+    fee_recipient: Address,
+    fee_rate: u64,
+) -> std::result::Result<RouterDeployment, Box<dyn std::error::Error>> {
+    validate_deploy_params(token_manager_helper, fee_recipient, fee_rate)?;
+
+    // 本地测试链上没有Helper3，只提示不阻止（构造函数只保存地址）
+    if client.get_code(token_manager_helper, None).await?.is_empty() {
+        logln!("  ⚠️  TokenManagerHelper {:?} 上没有合约代码，路由的买卖功能将不可用", token_manager_helper);
+    }
+
+    let bytecode_hash = H256::from(keccak256(&bytecode));
+    let abi: Abi = TOKENTRADEWITHFEE_ABI.clone();
+    let factory = ContractFactory::new(abi, bytecode, client.clone());
+    let deployer = factory.deploy((token_manager_helper, fee_recipient, U256::from(fee_rate)))?;
+    logln!("🔹 发送部署交易...");
+    let (contract, receipt) = deployer.send_with_receipt().await?;
+    if receipt.status != Some(U64::one()) {
+        return Err(format!("部署交易回滚: {:?}", receipt.transaction_hash).into());
+    }
+    logln!("✅ 部署交易确认! 区块: {:?}", receipt.block_number);
+
+    let router = TokenTradeWithFee::new(contract.address(), client.clone());
+    let owner = router.owner().call().await?;
+    let deployed_helper = router.token_manager_helper().call().await?;
+    let deployed_recipient = router.fee_recipient().call().await?;
+    let deployed_rate = router.fee_rate().call().await?;
+    if deployed_helper != token_manager_helper || deployed_recipient != fee_recipient || deployed_rate != U256::from(fee_rate) {
+        return Err(format!(
+            "部署后的合约状态与参数不一致: helper {:?}, feeRecipient {:?}, feeRate {}",
+            deployed_helper, deployed_recipient, deployed_rate
+        )
+        .into());
+    }
+
+    Ok(RouterDeployment {
+        chain_id: client.get_chainid().await?.as_u64(),
+        address: contract.address(),
+        tx_hash: receipt.transaction_hash,
+        block_number: receipt.block_number.map(|n| n.as_u64()),
+        owner,
+        token_manager_helper,
+        fee_recipient,
+        fee_rate,
+        bytecode_hash,
+        deployed_at: Utc::now().timestamp(),
+    })
+}
+
+// 追加一行部署记录（JSONL）
+pub fn record_deployment(path: impl AsRef<Path>, deployment: &RouterDeployment) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent() {
+        if !dir.as_os_str().is_empty() {
+            std::fs::create_dir_all(dir)?;
+        }
+    }
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(deployment)?)?;
+    Ok(())
+}
+
+// 读取全部部署记录，文件不存在时为空
+pub fn load_deployments(path: impl AsRef<Path>) -> std::result::Result<Vec<RouterDeployment>, Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    std::fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| serde_json::from_str(line).map_err(|e| format!("部署记录第{}行格式错误 {}: {}", i + 1, path.display(), e).into()))
+        .collect()
+}
+
+pub fn print_deployment(deployment: &RouterDeployment) {
+    logln!("  路由合约地址: {:?}", deployment.address);
+    logln!("  部署交易: {:?}", deployment.tx_hash);
+    logln!("  链ID: {}", deployment.chain_id);
+    logln!("  owner: {:?}", deployment.owner);
+    logln!("  TokenManagerHelper: {:?}", deployment.token_manager_helper);
+    logln!("  手续费: {}% -> {:?}", deployment.fee_rate, deployment.fee_recipient);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constructor_constraints_are_checked_locally() {
+        let helper = Address::repeat_byte(0x01);
+        let recipient = Address::repeat_byte(0x02);
+        validate_deploy_params(helper, recipient, PROXY_FEE_RATE_MAX).unwrap();
+        assert!(validate_deploy_params(helper, recipient, PROXY_FEE_RATE_MAX + 1).is_err());
+        assert!(validate_deploy_params(helper, Address::zero(), 1).is_err());
+        assert!(validate_deploy_params(Address::zero(), recipient, 1).is_err());
+    }
+
+    #[test]
+    fn bytecode_is_read_from_hardhat_foundry_and_bin_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let hardhat = dir.path().join("hardhat.json");
+        let foundry = dir.path().join("foundry.json");
+        let bin = dir.path().join("router.bin");
+        std::fs::write(&hardhat, r#"{"contractName":"TokenTradeWithFee","bytecode":"0x6080"}"#).unwrap();
+        std::fs::write(&foundry, r#"{"bytecode":{"object":"0x6080"}}"#).unwrap();
+        std::fs::write(&bin, "6080\n").unwrap();
+
+        for path in [&hardhat, &foundry, &bin] {
+            assert_eq!(load_bytecode(path).unwrap().to_vec(), vec![0x60, 0x80]);
+        }
+        std::fs::write(&hardhat, r#"{"bytecode":"0x"}"#).unwrap();
+        assert!(load_bytecode(&hardhat).is_err());
+    }
+
+    // 在anvil上部署并回读核对的测试见 tests/anvil.rs（deploys_router_and_verifies_state）
+}
@@ -0,0 +1,291 @@
+// 定时发布
+// 用未来的launchTime创建代币后，按服务器时间倒计时到开盘；
+// 开盘前核对链上getTokenInfo.launchTime与请求的发布时间，
+// 可选在开盘后的第一个区块买入（提前构造好交易，按最新区块时间戳判断发送时机）。
+
+use chrono::{DateTime, Utc};
+use ethers::{
+    prelude::*,
+    types::{Address, U256},
+    utils::format_ether,
+};
+use std::sync::Arc;
+use serde::Serialize;
+
+use crate::curve::{BondingCurve, CurveState};
+use crate::{journal, output, sender};
+use crate::config::{RuntimeConfig, BSC_BLOCK_TIME_SECS, LAUNCH_BUY_GAS_LIMIT};
+use crate::contracts::{ITokenManager2, ITokenManagerHelper3};
+
+// 开盘首笔买入最多尝试的区块数（交易落在开盘前的区块会回滚）
+const LAUNCH_BUY_MAX_ATTEMPTS: u32 = 3;
+// 链上与请求的发布时间允许的误差（秒）
+const LAUNCH_TIME_TOLERANCE_SECS: u64 = 1;
+// 毫秒时间戳的下限（2001-09-09），更小的整数多半是误填的秒级时间戳
+const MIN_LAUNCH_AT_MILLIS: u64 = 1_000_000_000_000;
+
+// 解析发布时间：RFC3339时间、毫秒时间戳，或相对当前时间的 +90s/+10m/+2h
+pub fn parse_launch_at(value: &str) -> std::result::Result<u64, Box<dyn std::error::Error>> {
+    let value = value.trim();
+    if let Some(relative) = value.strip_prefix('+') {
+        let (number, unit) = relative.split_at(relative.len().saturating_sub(1));
+        let amount: i64 = number.parse().map_err(|_| format!("无效的相对时间: {}", value))?;
+        let seconds = match unit {
+            "s" => amount,
+            "m" => amount * 60,
+            "h" => amount * 3600,
+            _ => return Err(format!("无效的相对时间单位: {} (可选 s/m/h)", value).into()),
+        };
+        return Ok((Utc::now() + chrono::Duration::seconds(seconds)).timestamp_millis() as u64);
+    }
+    if let Ok(millis) = value.parse::<u64>() {
+        if millis < MIN_LAUNCH_AT_MILLIS {
+            return Err(format!("发布时间 {} 不是毫秒时间戳（秒级时间戳请乘以1000）", value).into());
+        }
+        return Ok(millis);
+    }
+    let time = DateTime::parse_from_rfc3339(value).map_err(|e| format!("发布时间格式错误 '{}' ({})", value, e))?;
+    Ok(time.timestamp_millis() as u64)
+}
+
+// 以服务器时间为准的时钟，offset = 服务器时间 - 本地时间
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ServerClock {
+    pub offset_ms: i64,
+}
+
+impl ServerClock {
+    pub fn from_server_time(server_time_ms: u64) -> Self {
+        Self {
+            offset_ms: server_time_ms as i64 - Utc::now().timestamp_millis(),
+        }
+    }
+
+    pub fn now_ms(&self) -> i64 {
+        Utc::now().timestamp_millis() + self.offset_ms
+    }
+}
+
+fn format_millis(ms: i64) -> String {
+    DateTime::from_timestamp_millis(ms)
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| ms.to_string())
+}
+
+// 核对链上发布时间，返回链上launchTime（秒）
+pub async fn verify_launch_time<M: Middleware + 'static>(
+    client: Arc<M>,
+    token_manager_helper_address: Address,
+    token_address: Address,
+    requested_ms: u64,
+) -> std::result::Result<u64, Box<dyn std::error::Error>> {
+    let helper = ITokenManagerHelper3::new(token_manager_helper_address, client);
+    let info = helper.get_token_info(token_address).call().await?;
+    let onchain_secs = info.6.as_u64();
+    let requested_secs = requested_ms / 1000;
+
+    logln!("  请求的发布时间: {}", format_millis(requested_ms as i64));
+    logln!("  链上发布时间:   {}", format_millis(onchain_secs as i64 * 1000));
+    if onchain_secs.abs_diff(requested_secs) > LAUNCH_TIME_TOLERANCE_SECS {
+        logln!(
+            "  ⚠️  链上launchTime与请求相差 {} 秒，以链上为准",
+            onchain_secs as i64 - requested_secs as i64
+        );
+    }
+    Ok(onchain_secs)
+}
+
+// 按服务器时间倒计时，在开盘前lead_ms毫秒返回
+pub async fn countdown(clock: ServerClock, launch_secs: u64, lead_ms: i64) {
+    let target = launch_secs as i64 * 1000 - lead_ms;
+    let mut last_printed = i64::MAX;
+    loop {
+        let remaining = target - clock.now_ms();
+        if remaining <= 0 {
+            break;
+        }
+        let secs = remaining / 1000;
+        // 最后10秒每秒刷新，之前每10秒刷新
+        if secs != last_printed && (secs <= 10 || secs % 10 == 0) {
+            output::log_inline(format_args!("\r  ⏳ 距离开盘: {:02}:{:02}:{:02}   ", secs / 3600, secs % 3600 / 60, secs % 60));
+            last_printed = secs;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(remaining.clamp(1, 200) as u64)).await;
+    }
+    logln!("\r  🔔 开盘时间到                 ");
+}
+
+#[derive(Debug, Serialize)]
+pub struct LaunchBuyResult {
+    pub tx_hash: H256,
+    pub block_number: Option<U64>,
+    pub block_timestamp: u64,
+    pub funds: U256,
+    pub min_amount: U256,
+}
+
+// 在开盘后的第一个区块买入：下一个区块的时间戳预计不早于launchTime时立即发送，
+// gas上限固定（开盘前estimate_gas会回滚），落在开盘前的区块而回滚时在下一个区块重试
+pub async fn buy_at_launch<M: Middleware + 'static>(
+    client: Arc<M>,
+    token_manager_helper_address: Address,
+    token_address: Address,
+    launch_secs: u64,
+    funds: U256,
+    slippage_percent: u64,
+    config: &RuntimeConfig,
+) -> std::result::Result<LaunchBuyResult, Box<dyn std::error::Error>> {
+    let helper = ITokenManagerHelper3::new(token_manager_helper_address, client.clone());
+
+    // 开盘前的曲线状态即为开盘价，按本地曲线计算最少获得数量；TokenManager地址以getTokenInfo为准
+    let info = helper.get_token_info(token_address).call().await?;
+    if info.0 != U256::from(2) {
+        return Err(format!("代币由TokenManager V{}发行，开盘买入只支持V2", info.0).into());
+    }
+    let token_manager = ITokenManager2::new(info.1, client.clone());
+    // buyTokenAMAP附带BNB买入，ERC20募集的代币需要先授权并按募集代币计价，开盘买入不支持
+    if !info.2.is_zero() {
+        return Err(format!("代币以 {:?} 募集，开盘买入只支持BNB募集的代币", info.2).into());
+    }
+    let curve = BondingCurve::new(CurveState::from_token_info(&info))?;
+    let quote = curve.quote_buy(funds);
+    let min_amount = quote.token_amount * U256::from(100 - slippage_percent.min(100)) / U256::from(100);
+    logln!(
+        "  开盘买入: {} BNB，预计获得 {}，最少 {} (滑点 {}%)",
+        format_ether(funds),
+        format_ether(quote.token_amount),
+        format_ether(min_amount),
+        slippage_percent
+    );
+
+    let mut attempt = 0;
+    let mut last_sent_block = 0u64;
+    loop {
+        let latest = client
+            .get_block(BlockNumber::Latest)
+            .await?
+            .ok_or("无法获取最新区块")?;
+        let latest_number = latest.number.map(|n| n.as_u64()).unwrap_or_default();
+
+        if !ready_to_send(latest.timestamp.as_u64(), latest_number, last_sent_block, launch_secs) {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            continue;
+        }
+
+        attempt += 1;
+        last_sent_block = latest_number;
+        let buy_tx = token_manager
+            .buy_token_amap(token_address, sender(&*client)?, funds, min_amount)
+            .value(funds)
+            .gas(LAUNCH_BUY_GAS_LIMIT);
+        let pending = buy_tx.send().await?;
+        let tx_hash = pending.tx_hash();
+        logln!("  🚀 第{}次买入已发送 (最新区块 {}): {:?}", attempt, latest_number, tx_hash);
+
+        let receipt = pending.await?.ok_or("买入交易未被打包")?;
+        let block = client
+            .get_block(receipt.block_hash.ok_or("买入交易缺少区块哈希")?)
+            .await?
+            .ok_or("无法获取买入交易所在区块")?;
+        let block_timestamp = block.timestamp.as_u64();
+
+        if receipt.status == Some(U64::one()) {
+            logln!(
+                "  ✅ 开盘买入成功! 区块 {:?} (时间戳 {}, 开盘后 {} 秒)",
+                receipt.block_number,
+                block_timestamp,
+                block_timestamp.saturating_sub(launch_secs)
+            );
+            journal::record_receipt(&client, config.trade_journal.as_deref(), &receipt).await;
+            return Ok(LaunchBuyResult {
+                tx_hash,
+                block_number: receipt.block_number,
+                block_timestamp,
+                funds,
+                min_amount,
+            });
+        }
+
+        logln!("  ⚠️  买入交易回滚 (区块时间戳 {}，开盘 {})", block_timestamp, launch_secs);
+        if !should_retry(block_timestamp, launch_secs, attempt) {
+            return Err(format!("开盘买入失败: {:?}", tx_hash).into());
+        }
+    }
+}
+
+// 下一个区块的时间戳预计不早于launchTime，且这个区块还没发送过
+fn ready_to_send(latest_timestamp: u64, latest_number: u64, last_sent_block: u64, launch_secs: u64) -> bool {
+    latest_timestamp + BSC_BLOCK_TIME_SECS >= launch_secs && latest_number > last_sent_block
+}
+
+// 回滚后是否在下一个区块重试：只有落在开盘前的区块才重试；
+// 已开盘仍回滚，多半是滑点或余额问题，不再重试
+fn should_retry(block_timestamp: u64, launch_secs: u64, attempt: u32) -> bool {
+    block_timestamp < launch_secs && attempt < LAUNCH_BUY_MAX_ATTEMPTS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_absolute_launch_times() {
+        assert_eq!(parse_launch_at("1767225600000").unwrap(), 1_767_225_600_000);
+        assert_eq!(parse_launch_at(" 2026-01-01T00:00:00Z ").unwrap(), 1_767_225_600_000);
+        assert_eq!(parse_launch_at("2026-01-01T08:00:00+08:00").unwrap(), 1_767_225_600_000);
+        assert!(parse_launch_at("2026-01-01 00:00").is_err());
+    }
+
+    #[test]
+    fn rejects_second_timestamps() {
+        let err = parse_launch_at("1767225600").unwrap_err();
+        assert!(err.to_string().contains("毫秒时间戳"), "{}", err);
+        assert!(parse_launch_at("0").is_err());
+        assert_eq!(parse_launch_at("1000000000000").unwrap(), MIN_LAUNCH_AT_MILLIS);
+    }
+
+    #[test]
+    fn parses_relative_launch_times() {
+        for (value, secs) in [("+90s", 90), ("+10m", 600), ("+2h", 7200)] {
+            let before = Utc::now().timestamp_millis() as u64;
+            let parsed = parse_launch_at(value).unwrap();
+            let after = Utc::now().timestamp_millis() as u64;
+            assert!(parsed >= before + secs * 1000 && parsed <= after + secs * 1000, "{}", value);
+        }
+        assert!(parse_launch_at("+10d").is_err());
+        assert!(parse_launch_at("+m").is_err());
+    }
+
+    #[test]
+    fn server_clock_applies_offset() {
+        let local = Utc::now().timestamp_millis();
+        let ahead = ServerClock::from_server_time((local + 5_000) as u64);
+        assert!((ahead.offset_ms - 5_000).abs() < 1_000);
+        assert!((ahead.now_ms() - Utc::now().timestamp_millis() - ahead.offset_ms).abs() < 1_000);
+
+        let behind = ServerClock::from_server_time((local - 3_000) as u64);
+        assert!((behind.offset_ms + 3_000).abs() < 1_000);
+        assert!(behind.now_ms() < ahead.now_ms());
+    }
+
+    #[test]
+    fn sends_once_per_block_from_the_block_before_launch() {
+        let launch = 1_000;
+        // 下一个区块仍在开盘前
+        assert!(!ready_to_send(launch - BSC_BLOCK_TIME_SECS - 1, 10, 0, launch));
+        // 下一个区块预计正好开盘
+        assert!(ready_to_send(launch - BSC_BLOCK_TIME_SECS, 10, 0, launch));
+        // 同一个区块不重复发送
+        assert!(!ready_to_send(launch, 10, 10, launch));
+        assert!(ready_to_send(launch, 11, 10, launch));
+    }
+
+    #[test]
+    fn retries_only_reverts_before_launch() {
+        let launch = 1_000;
+        assert!(should_retry(launch - 1, launch, 1));
+        assert!(!should_retry(launch - 1, launch, LAUNCH_BUY_MAX_ATTEMPTS));
+        assert!(!should_retry(launch, launch, 1));
+        assert!(!should_retry(launch + 3, launch, 1));
+    }
+}
@@ -0,0 +1,253 @@
+// sellToken的第三方手续费参数
+// sellToken(origin, token, from, amount, minFunds, feeRate, feeRecipient) 允许在卖出时给第三方抽取手续费，
+// feeRate为基点（100 = 1%），合约上限5%，超过会以 "FR - FeeRate" 回滚。
+// 参数按优先级合并：命令行 > 环境变量 > 配置文件中该钱包的设置 > 配置文件default > 配置常量。
+// 实际扣除的手续费从卖出交易的TokenSale事件和到账金额反推，
+// 只适用于卖家直接出现在TokenSale中的场所（TokenManager V2、手续费路由）；
+// Helper3的ERC20报价路径和PancakeSwap无法反推，手续费留空。
+
+use ethers::{
+    prelude::*,
+    types::{Address, U256},
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::config::SELL_FEE_RATE_MAX_BPS;
+use crate::contracts::TokenSaleFilter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SellFeeConfig {
+    pub origin: U256,
+    pub fee_rate_bps: u64,
+    pub fee_recipient: Address,
+}
+
+// 配置文件中的一组参数，未填写的字段沿用上一层
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SellFeeOverride {
+    pub origin: Option<u64>,
+    pub fee_rate_bps: Option<u64>,
+    pub fee_recipient: Option<Address>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SellFeeFile {
+    #[serde(default)]
+    pub default: SellFeeOverride,
+    #[serde(default)]
+    pub wallets: HashMap<Address, SellFeeOverride>,
+}
+
+impl SellFeeOverride {
+    fn apply(&self, config: &mut SellFeeConfig) {
+        if let Some(origin) = self.origin {
+            config.origin = U256::from(origin);
+        }
+        if let Some(fee_rate_bps) = self.fee_rate_bps {
+            config.fee_rate_bps = fee_rate_bps;
+        }
+        if let Some(fee_recipient) = self.fee_recipient {
+            config.fee_recipient = fee_recipient;
+        }
+    }
+
+    // 从环境变量SELL_ORIGIN/SELL_FEE_RATE_BPS/SELL_FEE_RECIPIENT读取
+    pub fn from_env() -> std::result::Result<Self, Box<dyn std::error::Error>> {
+        fn var<T: std::str::FromStr>(name: &str) -> std::result::Result<Option<T>, Box<dyn std::error::Error>>
+        where
+            T::Err: std::fmt::Display,
+        {
+            match std::env::var(name) {
+                Ok(value) if !value.trim().is_empty() => value
+                    .trim()
+                    .parse()
+                    .map(Some)
+                    .map_err(|e| format!("环境变量{}无效 '{}': {}", name, value, e).into()),
+                _ => Ok(None),
+            }
+        }
+        Ok(SellFeeOverride {
+            origin: var("SELL_ORIGIN")?,
+            fee_rate_bps: var("SELL_FEE_RATE_BPS")?,
+            fee_recipient: var("SELL_FEE_RECIPIENT")?,
+        })
+    }
+}
+
+impl SellFeeConfig {
+    // 合并各层参数并校验
+    pub fn resolve(
+        base: SellFeeConfig,
+        config_path: impl AsRef<Path>,
+        wallet: Address,
+        env: &SellFeeOverride,
+        cli: &SellFeeOverride,
+    ) -> std::result::Result<Self, Box<dyn std::error::Error>> {
+        let mut config = base;
+        let config_path = config_path.as_ref();
+        if config_path.exists() {
+            let content = std::fs::read_to_string(config_path)?;
+            let file: SellFeeFile = serde_yaml::from_str(&content)
+                .map_err(|e| format!("手续费配置文件格式错误 {}: {}", config_path.display(), e))?;
+            file.default.apply(&mut config);
+            if let Some(wallet_override) = file.wallets.get(&wallet) {
+                wallet_override.apply(&mut config);
+            }
+        }
+        env.apply(&mut config);
+        cli.apply(&mut config);
+        config.validate()?;
+        Ok(config)
+    }
+
+    // 与合约的检查一致，提前拒绝必然回滚的卖出
+    pub fn validate(&self) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        if self.fee_rate_bps > SELL_FEE_RATE_MAX_BPS {
+            return Err(format!(
+                "手续费率 {} 基点超过合约上限 {} 基点（{}%）",
+                self.fee_rate_bps,
+                SELL_FEE_RATE_MAX_BPS,
+                SELL_FEE_RATE_MAX_BPS / 100
+            )
+            .into());
+        }
+        if self.fee_rate_bps > 0 && self.fee_recipient.is_zero() {
+            return Err("收取手续费时手续费接收地址不能为零地址".into());
+        }
+        Ok(())
+    }
+
+    pub fn print(&self) {
+        logln!(
+            "  卖出手续费参数: origin {}，feeRate {} 基点 ({:.2}%)，feeRecipient {:?}",
+            self.origin,
+            self.fee_rate_bps,
+            self.fee_rate_bps as f64 / 100.0,
+            self.fee_recipient
+        );
+    }
+}
+
+// 卖出交易中实际扣除的手续费
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SaleFees {
+    pub platform_fee: U256,    // TokenSale事件中的平台手续费
+    pub third_party_fee: U256, // 按feeRate给feeRecipient的手续费
+}
+
+// 第三方手续费 = 成交金额 - 平台手续费 - 卖家实际到账（不含gas）
+// 交易中没有该卖家的TokenSale事件时返回None
+pub fn sale_fees(receipt: &TransactionReceipt, token: Address, seller: Address, received: U256) -> Option<SaleFees> {
+    let events: Vec<TokenSaleFilter> = receipt
+        .logs
+        .iter()
+        .filter_map(|log| parse_log::<TokenSaleFilter>(log.clone()).ok())
+        .filter(|event| event.token == token && event.account == seller)
+        .collect();
+    if events.is_empty() {
+        return None;
+    }
+    let (cost, platform_fee) = events
+        .iter()
+        .fold((U256::zero(), U256::zero()), |(cost, fee), event| (cost + event.cost, fee + event.fee));
+    Some(SaleFees {
+        platform_fee,
+        third_party_fee: cost.saturating_sub(platform_fee).saturating_sub(received),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> SellFeeConfig {
+        SellFeeConfig {
+            origin: U256::zero(),
+            fee_rate_bps: 0,
+            fee_recipient: Address::repeat_byte(0xe1),
+        }
+    }
+
+    #[test]
+    fn wallet_settings_override_file_default_and_cli_overrides_everything() {
+        let wallet = Address::repeat_byte(0xaa);
+        let other = Address::repeat_byte(0xbb);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sell_fees.yaml");
+        std::fs::write(
+            &path,
+            format!(
+                "default:\n  fee_rate_bps: 100\n  fee_recipient: \"{:?}\"\nwallets:\n  \"{:?}\":\n    origin: 7\n    fee_rate_bps: 50\n",
+                Address::repeat_byte(0x11),
+                wallet
+            ),
+        )
+        .unwrap();
+
+        let none = SellFeeOverride::default();
+        let config = SellFeeConfig::resolve(base(), &path, wallet, &none, &none).unwrap();
+        assert_eq!(config.origin, U256::from(7));
+        assert_eq!(config.fee_rate_bps, 50);
+        assert_eq!(config.fee_recipient, Address::repeat_byte(0x11));
+
+        let config = SellFeeConfig::resolve(base(), &path, other, &none, &none).unwrap();
+        assert_eq!((config.origin, config.fee_rate_bps), (U256::zero(), 100));
+
+        let cli = SellFeeOverride { fee_rate_bps: Some(0), ..Default::default() };
+        let config = SellFeeConfig::resolve(base(), &path, wallet, &none, &cli).unwrap();
+        assert_eq!(config.fee_rate_bps, 0);
+    }
+
+    #[test]
+    fn fee_rate_above_contract_cap_is_rejected() {
+        let mut config = base();
+        config.fee_rate_bps = SELL_FEE_RATE_MAX_BPS;
+        config.validate().unwrap();
+        config.fee_rate_bps = SELL_FEE_RATE_MAX_BPS + 1;
+        assert!(config.validate().is_err());
+        config.fee_rate_bps = 100;
+        config.fee_recipient = Address::zero();
+        assert!(config.validate().is_err());
+    }
+
+    fn sale_receipt(account: Address, cost: u64, fee: u64) -> TransactionReceipt {
+        let token = Address::repeat_byte(0x44);
+        let data = ethers::abi::encode(&[
+            ethers::abi::Token::Address(token),
+            ethers::abi::Token::Address(account),
+            ethers::abi::Token::Uint(U256::zero()),
+            ethers::abi::Token::Uint(U256::from(1000)),
+            ethers::abi::Token::Uint(U256::from(cost)),
+            ethers::abi::Token::Uint(U256::from(fee)),
+            ethers::abi::Token::Uint(U256::zero()),
+            ethers::abi::Token::Uint(U256::zero()),
+        ]);
+        TransactionReceipt {
+            logs: vec![Log {
+                topics: vec![TokenSaleFilter::signature()],
+                data: data.into(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn sale_fees_are_only_derived_from_the_sellers_own_sale_event() {
+        let token = Address::repeat_byte(0x44);
+        let seller = Address::repeat_byte(0xaa);
+
+        let fees = sale_fees(&sale_receipt(seller, 1000, 10), token, seller, U256::from(980)).unwrap();
+        assert_eq!(fees, SaleFees { platform_fee: U256::from(10), third_party_fee: U256::from(10) });
+
+        // Helper3 ERC20报价路径：TokenSale的account是Helper3，到账经过兑换，无法反推
+        let helper = Address::repeat_byte(0xbb);
+        assert_eq!(sale_fees(&sale_receipt(helper, 1000, 10), token, seller, U256::from(980)), None);
+        // PancakeSwap：没有TokenSale事件
+        assert_eq!(sale_fees(&TransactionReceipt::default(), token, seller, U256::from(980)), None);
+    }
+}
@@ -0,0 +1,119 @@
+// Four.meme 登录会话缓存
+// access_token按钱包地址保存在 data/sessions/<地址>.json，内容用AES-256-GCM加密，
+// 密钥由钱包私钥派生（keccak256），只有同一把私钥才能解密。
+// 会话在过期前一直复用，API返回登录失效时由FourMemeApiClient重新登录并覆盖缓存。
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use chrono::{Duration, Utc};
+use ethers::{
+    signers::{LocalWallet, Signer},
+    types::Address,
+    utils::keccak256,
+};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const KEY_DOMAIN: &[u8] = b"fourmeme-session-v1";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub access_token: String,
+    pub issued_at: i64,  // 秒级时间戳
+    pub expires_at: i64, // 秒级时间戳
+}
+
+impl Session {
+    pub fn new(access_token: String, ttl: Duration) -> Self {
+        let now = Utc::now();
+        Self {
+            access_token,
+            issued_at: now.timestamp(),
+            expires_at: (now + ttl).timestamp(),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Utc::now().timestamp() >= self.expires_at
+    }
+}
+
+// 磁盘上的加密格式，地址明文保存便于排查
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedSession {
+    address: Address,
+    nonce: String,
+    ciphertext: String,
+}
+
+pub struct SessionStore {
+    dir: PathBuf,
+    address: Address,
+    cipher: Aes256Gcm,
+    ttl: Duration,
+}
+
+impl SessionStore {
+    pub fn new(dir: impl AsRef<Path>, wallet: &LocalWallet, ttl: Duration) -> Self {
+        let mut material = KEY_DOMAIN.to_vec();
+        material.extend_from_slice(&wallet.signer().to_bytes());
+        let key = keccak256(material);
+
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            address: wallet.address(),
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)),
+            ttl,
+        }
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.dir.join(format!("{:?}.json", self.address))
+    }
+
+    // 读取未过期的会话；文件不存在、已过期或无法解密（换了私钥）都返回None
+    pub fn load(&self) -> Option<Session> {
+        let content = std::fs::read_to_string(self.path()).ok()?;
+        let stored: EncryptedSession = serde_json::from_str(&content).ok()?;
+        if stored.address != self.address {
+            return None;
+        }
+
+        let nonce = hex::decode(stored.nonce).ok()?;
+        let ciphertext = hex::decode(stored.ciphertext).ok()?;
+        if nonce.len() != 12 {
+            return None;
+        }
+        let plaintext = self.cipher.decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref()).ok()?;
+        let session: Session = serde_json::from_slice(&plaintext).ok()?;
+
+        if session.is_expired() {
+            None
+        } else {
+            Some(session)
+        }
+    }
+
+    pub fn save(&self, access_token: &str) -> std::result::Result<Session, Box<dyn std::error::Error>> {
+        let session = Session::new(access_token.to_string(), self.ttl);
+        let plaintext = serde_json::to_vec(&session)?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_ref())
+            .map_err(|e| format!("加密会话失败: {}", e))?;
+
+        let stored = EncryptedSession {
+            address: self.address,
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        };
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.path(), serde_json::to_string_pretty(&stored)?)?;
+        Ok(session)
+    }
+
+    pub fn clear(&self) {
+        let _ = std::fs::remove_file(self.path());
+    }
+}
@@ -0,0 +1,264 @@
+// 代币图片预处理
+// 图片可以来自本地路径、内存字节或URL。上传前按文件头（magic bytes）识别真实格式，
+// 不信任扩展名；校验尺寸和大小，必要时缩放并重新编码为平台接受的PNG/JPEG。
+
+use image::{codecs::jpeg::JpegEncoder, imageops::FilterType, DynamicImage, ImageFormat};
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use crate::config::{TOKEN_IMAGE_MAX_BYTES, TOKEN_IMAGE_MAX_DIMENSION, TOKEN_IMAGE_MIN_DIMENSION};
+
+#[derive(Debug, Clone)]
+pub enum ImageSource {
+    Path(PathBuf),
+    Bytes { data: Vec<u8>, name: String },
+    Url(String),
+}
+
+impl ImageSource {
+    // http(s)开头按URL处理，其余按本地路径
+    pub fn parse(value: &str) -> Self {
+        if value.starts_with("http://") || value.starts_with("https://") {
+            ImageSource::Url(value.to_string())
+        } else {
+            ImageSource::Path(PathBuf::from(value))
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            ImageSource::Path(path) => path.display().to_string(),
+            ImageSource::Bytes { name, data } => format!("{} ({}字节)", name, data.len()),
+            ImageSource::Url(url) => url.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ImageLimits {
+    pub max_bytes: usize,
+    pub min_dimension: u32, // 宽高都不能小于该值
+    pub max_dimension: u32, // 超过时等比缩小
+}
+
+impl Default for ImageLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: TOKEN_IMAGE_MAX_BYTES,
+            min_dimension: TOKEN_IMAGE_MIN_DIMENSION,
+            max_dimension: TOKEN_IMAGE_MAX_DIMENSION,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SniffedFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+    Bmp,
+}
+
+impl SniffedFormat {
+    fn image_format(self) -> ImageFormat {
+        match self {
+            SniffedFormat::Png => ImageFormat::Png,
+            SniffedFormat::Jpeg => ImageFormat::Jpeg,
+            SniffedFormat::Gif => ImageFormat::Gif,
+            SniffedFormat::Webp => ImageFormat::WebP,
+            SniffedFormat::Bmp => ImageFormat::Bmp,
+        }
+    }
+
+    // 平台只接受PNG/JPEG，其余格式需要重新编码
+    fn accepted(self) -> bool {
+        matches!(self, SniffedFormat::Png | SniffedFormat::Jpeg)
+    }
+}
+
+// 根据文件头识别图片格式
+pub fn sniff_format(data: &[u8]) -> Option<SniffedFormat> {
+    if data.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some(SniffedFormat::Png)
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(SniffedFormat::Jpeg)
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some(SniffedFormat::Gif)
+    } else if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        Some(SniffedFormat::Webp)
+    } else if data.starts_with(b"BM") {
+        Some(SniffedFormat::Bmp)
+    } else {
+        None
+    }
+}
+
+// 上传用的图片
+#[derive(Debug, Clone)]
+pub struct PreparedImage {
+    pub data: Vec<u8>,
+    pub mime: &'static str,
+    pub extension: &'static str,
+    pub width: u32,
+    pub height: u32,
+    pub reencoded: bool,
+}
+
+// 校验并转换为平台可接受的图片：
+// 1. 格式不是PNG/JPEG时转为PNG
+// 2. 宽或高超过max_dimension时等比缩小
+// 3. 仍超过max_bytes时以递减的质量编码为JPEG，直到满足大小限制
+pub fn prepare_image(data: &[u8], limits: &ImageLimits) -> std::result::Result<PreparedImage, Box<dyn std::error::Error>> {
+    let format = sniff_format(data).ok_or("无法识别的图片格式（仅支持PNG/JPEG/GIF/WEBP/BMP）")?;
+    let image = image::load_from_memory_with_format(data, format.image_format())
+        .map_err(|e| format!("图片解码失败: {}", e))?;
+    let (width, height) = (image.width(), image.height());
+
+    if width < limits.min_dimension || height < limits.min_dimension {
+        return Err(format!(
+            "图片尺寸过小: {}x{}，宽高至少为 {}px",
+            width, height, limits.min_dimension
+        )
+        .into());
+    }
+
+    let oversized = width > limits.max_dimension || height > limits.max_dimension;
+    if format.accepted() && !oversized && data.len() <= limits.max_bytes {
+        let (mime, extension) = match format {
+            SniffedFormat::Png => ("image/png", "png"),
+            _ => ("image/jpeg", "jpg"),
+        };
+        return Ok(PreparedImage {
+            data: data.to_vec(),
+            mime,
+            extension,
+            width,
+            height,
+            reencoded: false,
+        });
+    }
+
+    let image = if oversized {
+        image.resize(limits.max_dimension, limits.max_dimension, FilterType::Lanczos3)
+    } else {
+        image
+    };
+
+    // 原本是JPEG的保持JPEG，其余先尝试PNG（保留透明度）
+    if format != SniffedFormat::Jpeg {
+        let png = encode_png(&image)?;
+        if png.len() <= limits.max_bytes {
+            return Ok(reencoded(png, "image/png", "png", &image));
+        }
+    }
+
+    for quality in [90u8, 80, 70, 60, 50] {
+        let jpeg = encode_jpeg(&image, quality)?;
+        if jpeg.len() <= limits.max_bytes {
+            return Ok(reencoded(jpeg, "image/jpeg", "jpg", &image));
+        }
+    }
+
+    Err(format!(
+        "图片过大: 压缩后仍超过 {} 字节，请换一张更小的图片",
+        limits.max_bytes
+    )
+    .into())
+}
+
+fn reencoded(data: Vec<u8>, mime: &'static str, extension: &'static str, image: &DynamicImage) -> PreparedImage {
+    PreparedImage {
+        data,
+        mime,
+        extension,
+        width: image.width(),
+        height: image.height(),
+        reencoded: true,
+    }
+}
+
+fn encode_png(image: &DynamicImage) -> std::result::Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut out = Cursor::new(Vec::new());
+    image.write_to(&mut out, ImageFormat::Png)?;
+    Ok(out.into_inner())
+}
+
+fn encode_jpeg(image: &DynamicImage, quality: u8) -> std::result::Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut out = Vec::new();
+    // JPEG不支持透明通道
+    image.to_rgb8().write_with_encoder(JpegEncoder::new_with_quality(&mut out, quality))?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage, Rgba, RgbaImage};
+
+    fn limits() -> ImageLimits {
+        ImageLimits {
+            max_bytes: 1024 * 1024,
+            min_dimension: 64,
+            max_dimension: 256,
+        }
+    }
+
+    fn encode(image: DynamicImage, format: ImageFormat) -> Vec<u8> {
+        let mut out = Cursor::new(Vec::new());
+        image.write_to(&mut out, format).unwrap();
+        out.into_inner()
+    }
+
+    #[test]
+    fn sniffs_format_from_magic_bytes() {
+        let image = DynamicImage::ImageRgb8(RgbImage::from_pixel(8, 8, Rgb([1, 2, 3])));
+        assert_eq!(sniff_format(&encode(image.clone(), ImageFormat::Png)), Some(SniffedFormat::Png));
+        assert_eq!(sniff_format(&encode(image.clone(), ImageFormat::Jpeg)), Some(SniffedFormat::Jpeg));
+        assert_eq!(sniff_format(&encode(image, ImageFormat::Gif)), Some(SniffedFormat::Gif));
+        assert_eq!(sniff_format(b"not an image"), None);
+    }
+
+    #[test]
+    fn jpeg_uses_image_jpeg_mime_and_is_kept_as_is() {
+        let data = encode(DynamicImage::ImageRgb8(RgbImage::from_pixel(128, 128, Rgb([200, 10, 10]))), ImageFormat::Jpeg);
+        let prepared = prepare_image(&data, &limits()).unwrap();
+        assert_eq!(prepared.mime, "image/jpeg");
+        assert_eq!(prepared.extension, "jpg");
+        assert!(!prepared.reencoded);
+        assert_eq!(prepared.data, data);
+    }
+
+    #[test]
+    fn oversized_gif_is_resized_to_png() {
+        let data = encode(DynamicImage::ImageRgba8(RgbaImage::from_pixel(512, 384, Rgba([0, 0, 0, 255]))), ImageFormat::Gif);
+        let prepared = prepare_image(&data, &limits()).unwrap();
+        assert!(prepared.reencoded);
+        assert_eq!(prepared.mime, "image/png");
+        assert_eq!((prepared.width, prepared.height), (256, 192));
+        assert_eq!(sniff_format(&prepared.data), Some(SniffedFormat::Png));
+    }
+
+    #[test]
+    fn too_small_image_is_rejected() {
+        let data = encode(DynamicImage::ImageRgb8(RgbImage::new(32, 32)), ImageFormat::Png);
+        let err = prepare_image(&data, &limits()).unwrap_err();
+        assert!(err.to_string().contains("32x32"));
+    }
+
+    #[test]
+    fn large_file_is_recompressed_under_limit() {
+        // 噪声图PNG压缩效果差，需要转为JPEG才能满足大小限制
+        let noise = RgbImage::from_fn(256, 256, |x, y| {
+            let v = (x.wrapping_mul(7919) ^ y.wrapping_mul(104729)).wrapping_mul(2654435761);
+            Rgb([(v >> 8) as u8, (v >> 16) as u8, (v >> 24) as u8])
+        });
+        let data = encode(DynamicImage::ImageRgb8(noise), ImageFormat::Png);
+        let tight = ImageLimits { max_bytes: 60 * 1024, ..limits() };
+        assert!(data.len() > tight.max_bytes);
+
+        let prepared = prepare_image(&data, &tight).unwrap();
+        assert_eq!(prepared.mime, "image/jpeg");
+        assert!(prepared.data.len() <= tight.max_bytes);
+    }
+}
@@ -0,0 +1,195 @@
+// 卖出流程
+// 查询持仓和预估结果、按需授权，然后在代币所在的交易场所（见venue.rs）卖出；
+// 对任意带签名钱包的Middleware通用，其他服务可以直接嵌入使用。
+
+use ethers::{
+    prelude::*,
+    types::{Address, H256, U256},
+    utils::format_ether,
+};
+use std::sync::Arc;
+use serde::Serialize;
+
+use crate::config::RuntimeConfig;
+use crate::contracts::{ITokenManagerHelper3, IERC20};
+use crate::sell_fee::{self, SellFeeConfig};
+use crate::sender;
+use crate::venue::{TokenManagerV2Venue, TradingVenue, VenueRouter};
+
+// 卖出代币的结果结构体
+#[derive(Debug, Serialize)]
+pub struct SellResult {
+    pub token_sold: U256,
+    pub bnb_received: U256,
+    pub sell_tx_hash: H256,
+    pub block_number: Option<U256>,
+    pub approve_tx_hash: Option<H256>,
+    pub platform_fee: Option<U256>,    // 平台手续费，无法从成交事件推算时为空
+    pub third_party_fee: Option<U256>, // 按feeRate实际给feeRecipient的手续费，无法推算时为空
+}
+
+// 完整的卖出代币流程方法
+#[tracing::instrument(name = "sell", skip_all, fields(token = ?token_address, wallet = tracing::field::Empty))]
+pub async fn execute_sell_process<M: Middleware + 'static>(
+    client: Arc<M>,
+    token_address: Address,
+    token_manager_helper_address: Address,
+    fee_config: &SellFeeConfig,
+    config: &RuntimeConfig,
+) -> std::result::Result<SellResult, Box<dyn std::error::Error>> {
+    // 创建合约实例
+    let token = IERC20::new(token_address, client.clone());
+    let token_manager_helper = ITokenManagerHelper3::new(token_manager_helper_address, client.clone());
+    let user_address = sender(&*client)?;
+    tracing::Span::current().record("wallet", tracing::field::debug(user_address));
+    
+    tracing::info!("开始卖出代币流程");
+    
+    // 查询卖出前的余额
+    let before_token_balance = token.balance_of(user_address).call().await?;
+    let before_bnb_balance = client.get_balance(user_address, None).await?;
+    
+    tracing::info!(token_balance = %before_token_balance, bnb_balance = %format_ether(before_bnb_balance), "卖出前余额");
+    
+    // 检查代币余额
+    if before_token_balance == U256::zero() {
+        return Err("没有代币可以卖出!".into());
+    }
+    
+    // 获取token信息和预估卖出结果
+    let token_info = token_manager_helper.get_token_info(token_address).call().await?;
+    let token_manager_address = token_info.1;
+    let quote = token_info.2;
+    let liquidity_added = token_info.11;
+    
+    tracing::debug!(token_manager = ?token_manager_address, quote = ?quote, liquidity_added, "代币信息");
+    
+    // 按代币信息选择交易场所（V1/V2/Helper3/PancakeSwap）
+    let venue = VenueRouter::new(client.clone(), token_manager_helper_address, *fee_config, config)?
+        .venue_from_info(token_info.0, token_manager_address, quote, liquidity_added)?;
+    let approval_target = venue.approval_target();
+    tracing::info!(venue = venue.name(), "交易场所");
+    
+    // 预估卖出结果
+    let sell_estimate = venue.quote_sell(token_address, before_token_balance).await?;
+    tracing::info!(
+        funds = %format_ether(sell_estimate.funds),
+        fee = %format_ether(sell_estimate.fee),
+        approval_target = ?approval_target,
+        "预估卖出结果"
+    );
+    
+    // 检查授权并在必要时进行approve
+    let current_allowance = token.allowance(user_address, approval_target).call().await?;
+    tracing::debug!(allowance = %current_allowance, required = %before_token_balance, "授权额度");
+    
+    let mut approve_tx_hash = None;
+    
+    if current_allowance < before_token_balance {
+        let approve_tx = token.approve(approval_target, before_token_balance);
+        let approve_pending = approve_tx.send().await?;
+        approve_tx_hash = Some(approve_pending.tx_hash());
+        tracing::info!(tx_hash = ?approve_pending.tx_hash(), "approve交易已发送");
+        
+        // 等待approve交易确认
+        let approve_receipt = approve_pending.await?.ok_or("approve交易未被打包")?;
+        if approve_receipt.status != Some(U64::one()) {
+            return Err(format!("approve交易回滚: {:?}", approve_receipt.transaction_hash).into());
+        }
+        tracing::info!(tx_hash = ?approve_receipt.transaction_hash, block = ?approve_receipt.block_number, "approve交易确认");
+        
+        // 检查新的授权额度
+        let new_allowance = token.allowance(user_address, approval_target).call().await?;
+        tracing::debug!(allowance = %new_allowance, "新的授权额度");
+    } else {
+        tracing::info!("授权已足够，直接执行卖出");
+    }
+    
+    // 使用纯粹的卖币方法执行卖出操作
+    let sell_result = sell_on_venue(
+        client.clone(),
+        &venue,
+        token_address,
+        before_token_balance,
+    ).await?;
+    
+    // 返回完整结果，包含approve信息
+    Ok(SellResult {
+        token_sold: sell_result.token_sold,
+        bnb_received: sell_result.bnb_received,
+        sell_tx_hash: sell_result.sell_tx_hash,
+        block_number: sell_result.block_number,
+        approve_tx_hash,
+        platform_fee: sell_result.platform_fee,
+        third_party_fee: sell_result.third_party_fee,
+    })
+}
+
+// 纯粹的卖币方法（不包含approve逻辑），直接调用TokenManager2的sellToken
+#[tracing::instrument(name = "sell", skip_all, fields(token = ?token_address))]
+pub async fn sell_tokens_only<M: Middleware + 'static>(
+    client: Arc<M>,
+    token_address: Address,
+    token_manager_address: Address,
+    token_manager_helper_address: Address,
+    token_amount: U256,
+    fee_config: &SellFeeConfig,
+    config: &RuntimeConfig,
+) -> std::result::Result<SellResult, Box<dyn std::error::Error>> {
+    let venue = TokenManagerV2Venue::new(
+        client.clone(),
+        token_manager_address,
+        token_manager_helper_address,
+        *fee_config,
+        config,
+    );
+    sell_on_venue(client, &venue, token_address, token_amount).await
+}
+
+// 在指定交易场所卖出（不包含approve逻辑），按余额变化和成交事件统计到账与手续费
+#[tracing::instrument(name = "sell.venue", skip_all, fields(venue = venue.name(), amount = %token_amount))]
+pub async fn sell_on_venue<M: Middleware + 'static, V: TradingVenue>(
+    client: Arc<M>,
+    venue: &V,
+    token_address: Address,
+    token_amount: U256,
+) -> std::result::Result<SellResult, Box<dyn std::error::Error>> {
+    let user_address = sender(&*client)?;
+    let min_funds = U256::zero();
+    
+    // 获取卖出前的BNB余额
+    let before_bnb_balance = client.get_balance(user_address, None).await?;
+    
+    // 执行卖出交易
+    tracing::info!("发送卖出交易");
+    let sell_receipt = venue.sell(token_address, token_amount, min_funds).await?;
+    let sell_tx_hash = sell_receipt.transaction_hash;
+    
+    // 获取卖出后的BNB余额
+    let after_bnb_balance = client.get_balance(user_address, None).await?;
+    
+    // 计算卖出结果
+    let token_sold = token_amount; // 假设全部卖出成功
+    let bnb_received = after_bnb_balance.saturating_sub(before_bnb_balance);
+    let gas_cost = sell_receipt.gas_used.unwrap_or_default() * sell_receipt.effective_gas_price.unwrap_or_default();
+    let fees = sell_fee::sale_fees(&sell_receipt, token_address, user_address, bnb_received + gas_cost);
+    
+    tracing::info!(
+        tx_hash = ?sell_tx_hash,
+        token_sold = %token_sold,
+        bnb_received = %format_ether(bnb_received),
+        platform_fee = ?fees.map(|f| format_ether(f.platform_fee)),
+        third_party_fee = ?fees.map(|f| format_ether(f.third_party_fee)),
+        "卖出完成"
+    );
+    
+    Ok(SellResult {
+        token_sold,
+        bnb_received,
+        sell_tx_hash,
+        block_number: sell_receipt.block_number.map(|n| U256::from(n.as_u64())),
+        approve_tx_hash: None, // 纯粹卖币方法不包含approve
+        platform_fee: fees.map(|f| f.platform_fee),
+        third_party_fee: fees.map(|f| f.third_party_fee),
+    })
+}
@@ -0,0 +1,444 @@
+// 流量录制与回放
+// 录制模式把每一次JSON-RPC调用和Four.meme HTTP请求按顺序写入JSONL（data/traffic/<时间>.jsonl），
+// 写入前脱敏：access_token、登录签名等凭证替换为 [REDACTED]，请求头不记录，RPC地址（可能带API key）不记录。
+// 回放模式不访问网络，按录制顺序返回记录的响应：
+// - RPC按 方法+参数 匹配下一条未使用的记录，参数不一致时（签名交易中的deadline等）退回同方法的下一条
+// - HTTP按 方法+接口 匹配下一条未使用的记录
+// 线上问题可以录制下来离线复现，再整理成回归测试。
+
+use async_trait::async_trait;
+use chrono::Utc;
+use ethers::providers::{Http, HttpClientError, JsonRpcClient, JsonRpcError};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::config::TRAFFIC_DIR;
+
+pub const REDACTED: &str = "[REDACTED]";
+
+// 出现在任意层级都会被脱敏的字段名（不区分大小写）
+const SECRET_KEYS: &[&str] = &[
+    "accesstoken",
+    "access_token",
+    "meme-web-access",
+    "authorization",
+    "cookie",
+    "privatekey",
+    "private_key",
+    "password",
+];
+
+// 登录接口：请求中的签名和响应中的access_token
+const LOGIN_ENDPOINT: &str = "login/dex";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Exchange {
+    Rpc {
+        method: String,
+        params: Value,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        result: Option<Value>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<RpcErrorRecord>,
+    },
+    Http {
+        method: String,
+        endpoint: String,
+        request: Value, // JSON请求体，GET和multipart上传为null
+        status: u16,
+        response: String, // 原始响应体
+    },
+}
+
+// 节点返回的JSON-RPC错误（如execution reverted），回放时原样返回
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcErrorRecord {
+    pub code: i64,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl From<&JsonRpcError> for RpcErrorRecord {
+    fn from(error: &JsonRpcError) -> Self {
+        Self {
+            code: error.code,
+            message: error.message.clone(),
+            data: error.data.clone(),
+        }
+    }
+}
+
+impl From<RpcErrorRecord> for JsonRpcError {
+    fn from(record: RpcErrorRecord) -> Self {
+        JsonRpcError {
+            code: record.code,
+            message: record.message,
+            data: record.data,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrafficEntry {
+    pub seq: u64,
+    pub timestamp: i64, // 毫秒
+    #[serde(flatten)]
+    pub exchange: Exchange,
+}
+
+#[derive(Debug)]
+enum Mode {
+    Record(Mutex<std::fs::File>),
+    Replay(Mutex<Vec<Option<Exchange>>>), // 已使用的记录置为None
+}
+
+#[derive(Debug)]
+pub struct Traffic {
+    path: PathBuf,
+    mode: Mode,
+    seq: AtomicU64,
+}
+
+impl Traffic {
+    // 录制到指定文件（覆盖已有内容）
+    pub fn record(path: impl AsRef<Path>) -> std::result::Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent() {
+            if !dir.as_os_str().is_empty() {
+                std::fs::create_dir_all(dir)?;
+            }
+        }
+        let file = std::fs::File::create(&path).map_err(|e| format!("无法创建录制文件 {}: {}", path.display(), e))?;
+        Ok(Self {
+            path,
+            mode: Mode::Record(Mutex::new(file)),
+            seq: AtomicU64::new(0),
+        })
+    }
+
+    pub fn replay(path: impl AsRef<Path>) -> std::result::Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref().to_path_buf();
+        let file = std::fs::File::open(&path).map_err(|e| format!("无法读取录制文件 {}: {}", path.display(), e))?;
+        let mut entries = Vec::new();
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: TrafficEntry = serde_json::from_str(&line)
+                .map_err(|e| format!("录制文件第{}行格式错误 {}: {}", i + 1, path.display(), e))?;
+            entries.push(Some(entry.exchange));
+        }
+        Ok(Self {
+            path,
+            mode: Mode::Replay(Mutex::new(entries)),
+            seq: AtomicU64::new(0),
+        })
+    }
+
+    // 默认录制路径：data/traffic/<时间>.jsonl
+    pub fn default_record_path() -> PathBuf {
+        Path::new(TRAFFIC_DIR).join(format!("{}.jsonl", Utc::now().format("%Y%m%d-%H%M%S")))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_replay(&self) -> bool {
+        matches!(self.mode, Mode::Replay(_))
+    }
+
+    // 录制模式下脱敏后追加一行，写入失败只提示不中断流程
+    pub fn log(&self, exchange: Exchange) {
+        let Mode::Record(file) = &self.mode else { return };
+        let entry = TrafficEntry {
+            seq: self.seq.fetch_add(1, Ordering::SeqCst),
+            timestamp: Utc::now().timestamp_millis(),
+            exchange: redact(exchange),
+        };
+        let result = serde_json::to_string(&entry)
+            .map_err(|e| e.to_string())
+            .and_then(|line| writeln!(file.lock().unwrap(), "{}", line).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            logln!("  ⚠️  写入录制文件失败 {}: {}", self.path.display(), e);
+        }
+    }
+
+    // 回放下一条RPC记录，返回记录的result或error
+    pub fn replay_rpc(&self, method: &str, params: &Value) -> Option<std::result::Result<Value, JsonRpcError>> {
+        let Mode::Replay(entries) = &self.mode else { return None };
+        let mut entries = entries.lock().unwrap();
+        let is_method = |exchange: &Exchange| matches!(exchange, Exchange::Rpc { method: m, .. } if m == method);
+        let exact = entries
+            .iter()
+            .position(|e| matches!(e, Some(exchange @ Exchange::Rpc { params: p, .. }) if is_method(exchange) && p == params));
+        let index = match exact {
+            Some(index) => index,
+            None => {
+                let index = entries.iter().position(|e| e.as_ref().is_some_and(is_method))?;
+                logln!("  ⚠️  回放: {} 的参数与记录不一致，按顺序使用下一条记录", method);
+                index
+            }
+        };
+        match entries[index].take() {
+            Some(Exchange::Rpc { error: Some(error), .. }) => Some(Err(error.into())),
+            Some(Exchange::Rpc { result, .. }) => Some(Ok(result.unwrap_or(Value::Null))),
+            _ => None,
+        }
+    }
+
+    // 回放下一条HTTP记录，返回(状态码, 响应体)
+    pub fn replay_http(&self, method: &str, endpoint: &str) -> Option<(u16, String)> {
+        let Mode::Replay(entries) = &self.mode else { return None };
+        let mut entries = entries.lock().unwrap();
+        let index = entries.iter().position(|e| {
+            matches!(e, Some(Exchange::Http { method: m, endpoint: ep, .. }) if m == method && ep == endpoint)
+        })?;
+        match entries[index].take() {
+            Some(Exchange::Http { status, response, .. }) => Some((status, response)),
+            _ => None,
+        }
+    }
+
+    // 回放结束后仍未使用的记录数，用于检查流程是否与录制时一致
+    pub fn remaining(&self) -> usize {
+        match &self.mode {
+            Mode::Replay(entries) => entries.lock().unwrap().iter().filter(|e| e.is_some()).count(),
+            Mode::Record(_) => 0,
+        }
+    }
+}
+
+// 进程内共享的录制/回放实例，由命令行在启动时设置，FourMemeApiClient::new()自动使用
+static CURRENT: OnceLock<Arc<Traffic>> = OnceLock::new();
+
+pub fn install(traffic: Traffic) -> Arc<Traffic> {
+    CURRENT.get_or_init(|| Arc::new(traffic)).clone()
+}
+
+pub fn current() -> Option<Arc<Traffic>> {
+    CURRENT.get().cloned()
+}
+
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if SECRET_KEYS.contains(&key.to_lowercase().as_str()) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+pub(crate) fn redact(mut exchange: Exchange) -> Exchange {
+    match &mut exchange {
+        Exchange::Rpc { params, result, .. } => {
+            redact_value(params);
+            if let Some(result) = result {
+                redact_value(result);
+            }
+        }
+        Exchange::Http { endpoint, request, response, .. } => {
+            redact_value(request);
+            let mut body: Option<Value> = serde_json::from_str(response).ok();
+            if let Some(body) = &mut body {
+                redact_value(body);
+            }
+            if endpoint == LOGIN_ENDPOINT {
+                if let Some(signature) = request.pointer_mut("/verifyInfo/signature") {
+                    *signature = Value::String(REDACTED.to_string());
+                }
+                if let Some(data) = body.as_mut().and_then(|b| b.get_mut("data")).filter(|d| !d.is_null()) {
+                    *data = Value::String(REDACTED.to_string());
+                }
+            }
+            if let Some(body) = body {
+                *response = body.to_string();
+            }
+        }
+    }
+    exchange
+}
+
+// 带录制/回放的JSON-RPC传输层：录制时转发给Http并记录，回放时直接返回记录的响应
+#[derive(Debug)]
+pub struct TrafficClient {
+    inner: Option<Http>,
+    traffic: Option<Arc<Traffic>>,
+}
+
+impl TrafficClient {
+    // traffic为None时等同于直接使用Http
+    pub fn new(inner: Http, traffic: Option<Arc<Traffic>>) -> Self {
+        Self { inner: Some(inner), traffic }
+    }
+
+    // 只回放，不需要RPC地址
+    pub fn replay_only(traffic: Arc<Traffic>) -> Self {
+        Self { inner: None, traffic: Some(traffic) }
+    }
+}
+
+fn replay_missing(method: &str) -> HttpClientError {
+    HttpClientError::JsonRpcError(JsonRpcError {
+        code: -32000,
+        message: format!("回放记录中没有 {} 的响应", method),
+        data: None,
+    })
+}
+
+#[async_trait]
+impl JsonRpcClient for TrafficClient {
+    type Error = HttpClientError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> std::result::Result<R, Self::Error>
+    where
+        T: std::fmt::Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = serde_json::to_value(&params).map_err(|err| HttpClientError::SerdeJson {
+            err,
+            text: format!("{:?}", params),
+        })?;
+
+        let result = match (&self.traffic, &self.inner) {
+            (Some(traffic), _) if traffic.is_replay() => match traffic.replay_rpc(method, &params) {
+                Some(result) => result.map_err(HttpClientError::JsonRpcError),
+                None => Err(replay_missing(method)),
+            },
+            (_, Some(inner)) => inner.request::<_, Value>(method, params.clone()).await,
+            (_, None) => Err(replay_missing(method)),
+        };
+
+        if let Some(traffic) = &self.traffic {
+            let error = match &result {
+                Err(HttpClientError::JsonRpcError(error)) => Some(RpcErrorRecord::from(error)),
+                _ => None,
+            };
+            // 网络错误没有响应，不记录
+            if result.is_ok() || error.is_some() {
+                traffic.log(Exchange::Rpc {
+                    method: method.to_string(),
+                    params,
+                    result: result.as_ref().ok().cloned(),
+                    error,
+                });
+            }
+        }
+
+        let value = result?;
+        serde_json::from_value(value.clone()).map_err(|err| HttpClientError::SerdeJson {
+            err,
+            text: value.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{default_raised_token, FourMemeApiClient};
+    use crate::mock_server::MockFourMeme;
+    use ethers::providers::{Middleware, Provider};
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers::types::U64;
+    use serde_json::json;
+
+    fn rpc(method: &str, params: Value, result: Value) -> Exchange {
+        Exchange::Rpc {
+            method: method.to_string(),
+            params,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn login_credentials_are_redacted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("traffic.jsonl");
+        let traffic = Traffic::record(&path).unwrap();
+        traffic.log(Exchange::Http {
+            method: "POST".to_string(),
+            endpoint: LOGIN_ENDPOINT.to_string(),
+            request: json!({"verifyInfo": {"address": "0xabc", "signature": "0xsecret"}, "accessToken": "t"}),
+            status: 200,
+            response: r#"{"code":"0","msg":"success","data":"access-token-123"}"#.to_string(),
+        });
+        drop(traffic);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("0xsecret") && !content.contains("access-token-123"));
+        assert!(content.contains("0xabc"));
+
+        let replay = Traffic::replay(&path).unwrap();
+        let (status, body) = replay.replay_http("POST", LOGIN_ENDPOINT).unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["data"], REDACTED);
+        assert!(replay.replay_http("POST", LOGIN_ENDPOINT).is_none());
+    }
+
+    #[tokio::test]
+    async fn rpc_replay_matches_params_then_falls_back_by_method() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("traffic.jsonl");
+        let traffic = Traffic::record(&path).unwrap();
+        traffic.log(rpc("eth_blockNumber", json!([]), json!("0x10")));
+        traffic.log(rpc("eth_getBalance", json!(["0x01", "latest"]), json!("0x1")));
+        traffic.log(rpc("eth_getBalance", json!(["0x02", "latest"]), json!("0x2")));
+        traffic.log(rpc("eth_blockNumber", json!([]), json!("0x11")));
+        drop(traffic);
+
+        let traffic = Arc::new(Traffic::replay(&path).unwrap());
+        let provider = Provider::new(TrafficClient::replay_only(traffic.clone()));
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(0x10));
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(0x11));
+        assert!(provider.get_block_number().await.is_err());
+
+        let balance: Value = provider.request("eth_getBalance", json!(["0x02", "latest"])).await.unwrap();
+        assert_eq!(balance, json!("0x2"));
+        let balance: Value = provider.request("eth_getBalance", json!(["0x03", "latest"])).await.unwrap();
+        assert_eq!(balance, json!("0x1"));
+        assert_eq!(traffic.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn recorded_api_session_replays_without_server() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("traffic.jsonl");
+        let wallet: LocalWallet = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318".parse().unwrap();
+
+        let mock = MockFourMeme::start().await;
+        let recorder = Arc::new(Traffic::record(&path).unwrap());
+        let mut api = FourMemeApiClient::new().with_base_url(&mock.base_url).with_traffic(recorder);
+        let nonce = api.get_nonce(&format!("{:?}", wallet.address())).await.unwrap();
+        api.login(&wallet, &nonce).await.unwrap();
+        let raised_tokens = api.get_raised_tokens().await.unwrap();
+        drop(mock);
+
+        // 服务已关闭，回放仍得到相同的结果
+        let replay = Arc::new(Traffic::replay(&path).unwrap());
+        let mut api = FourMemeApiClient::new().with_base_url("http://127.0.0.1:9").with_traffic(replay.clone());
+        assert_eq!(api.get_nonce(&format!("{:?}", wallet.address())).await.unwrap(), nonce);
+        assert_eq!(api.login(&wallet, &nonce).await.unwrap(), REDACTED);
+        let replayed = api.get_raised_tokens().await.unwrap();
+        assert_eq!(replayed.len(), raised_tokens.len());
+        assert_eq!(replayed[0].symbol, default_raised_token().symbol);
+        assert_eq!(replay.remaining(), 0);
+        assert!(api.get_raised_tokens().await.is_err());
+    }
+}
@@ -0,0 +1,662 @@
+// 交易场所（Venue）抽象
+// 同一个代币在不同阶段、不同版本下要调用不同的合约：
+// - TokenManager V1：2024-09-05之前创建的代币（purchaseTokenAMAP/saleToken）
+// - TokenManager V2：BNB计价的内盘代币（buyTokenAMAP/sellToken）
+// - Helper3：ERC20计价（quote不为0）的内盘代币，用buyWithEth/sellForEth直接以BNB买卖
+// - PancakeSwap V2：内盘结束（liquidityAdded）后的代币
+// VenueRouter按Helper3的getTokenInfo为每个代币选择场所，策略只需要面向TradingVenue编写。
+
+use chrono::Utc;
+use ethers::{
+    prelude::*,
+    types::{Address, U256},
+};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::config::{PANCAKE_ROUTER_V2_ADDRESS, PANCAKE_SWAP_DEADLINE_SECS, PANCAKE_V2_FEE_BPS, WBNB_ADDRESS, RuntimeConfig};
+use crate::contracts::{IPancakeRouter02, ITokenManager2, ITokenManagerHelper3, ITokenManagerV1};
+use crate::sell_fee::SellFeeConfig;
+use crate::{journal, sender};
+
+// 买入预估：用funds（BNB）能买到的代币数量
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuyQuote {
+    pub funds: U256,
+    pub amount: U256,
+    pub fee: U256, // 手续费（折算为BNB）
+}
+
+// 卖出预估：卖出amount个代币实际到手的BNB（已扣除手续费）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SellQuote {
+    pub amount: U256,
+    pub funds: U256,
+    pub fee: U256, // 手续费（折算为BNB）
+}
+
+// 交易场所，买卖都以BNB计价，成交后返回已确认的receipt
+// Box<dyn Error>不是Send，这里的future也不要求Send
+#[allow(async_fn_in_trait)]
+pub trait TradingVenue {
+    fn name(&self) -> &'static str;
+
+    // 卖出前需要授权代币的地址
+    fn approval_target(&self) -> Address;
+
+    async fn quote_buy(&self, token: Address, funds: U256) -> std::result::Result<BuyQuote, Box<dyn std::error::Error>>;
+
+    async fn quote_sell(&self, token: Address, amount: U256) -> std::result::Result<SellQuote, Box<dyn std::error::Error>>;
+
+    async fn buy(
+        &self,
+        token: Address,
+        funds: U256,
+        min_amount: U256,
+    ) -> std::result::Result<TransactionReceipt, Box<dyn std::error::Error>>;
+
+    async fn sell(
+        &self,
+        token: Address,
+        amount: U256,
+        min_funds: U256,
+    ) -> std::result::Result<TransactionReceipt, Box<dyn std::error::Error>>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VenueKind {
+    TokenManagerV1,
+    TokenManagerV2,
+    Helper3Erc20,
+    PancakeSwap,
+}
+
+// 按getTokenInfo的version/quote/liquidityAdded选择场所
+pub fn select_venue(
+    version: U256,
+    quote: Address,
+    liquidity_added: bool,
+) -> std::result::Result<VenueKind, Box<dyn std::error::Error>> {
+    if liquidity_added {
+        return Ok(VenueKind::PancakeSwap);
+    }
+    match version.as_u64() {
+        1 => Ok(VenueKind::TokenManagerV1),
+        2 if quote.is_zero() => Ok(VenueKind::TokenManagerV2),
+        2 => Ok(VenueKind::Helper3Erc20),
+        other => Err(format!("不支持的TokenManager版本: {}", other).into()),
+    }
+}
+
+// 等待交易确认，回滚时返回错误，确认后写入交易记录（journal为None时不记录）
+async fn confirm<M: Middleware + 'static>(
+    client: &M,
+    journal: Option<&Path>,
+    label: &str,
+    pending: PendingTransaction<'_, M::Provider>,
+) -> std::result::Result<TransactionReceipt, Box<dyn std::error::Error>> {
+    let tx_hash = pending.tx_hash();
+    tracing::info!(tx_hash = ?tx_hash, "{}交易已发送", label);
+    let receipt = pending.await?.ok_or_else(|| format!("{}交易未被打包", label))?;
+    if receipt.status != Some(U64::one()) {
+        return Err(format!("{}交易回滚: {:?}", label, tx_hash).into());
+    }
+    tracing::info!(tx_hash = ?tx_hash, block = ?receipt.block_number, "{}交易确认", label);
+    journal::record_receipt(client, journal, &receipt).await;
+    Ok(receipt)
+}
+
+// V1/V2内盘的预估都通过Helper3完成
+async fn helper_quote_buy<M: Middleware + 'static>(
+    helper: &ITokenManagerHelper3<M>,
+    token: Address,
+    funds: U256,
+) -> std::result::Result<BuyQuote, Box<dyn std::error::Error>> {
+    let (_, _, amount, _, fee, _, _, _) = helper.try_buy(token, U256::zero(), funds).call().await?;
+    Ok(BuyQuote { funds, amount, fee })
+}
+
+async fn helper_quote_sell<M: Middleware + 'static>(
+    helper: &ITokenManagerHelper3<M>,
+    token: Address,
+    amount: U256,
+) -> std::result::Result<SellQuote, Box<dyn std::error::Error>> {
+    let (_, _, funds, fee) = helper.try_sell(token, amount).call().await?;
+    Ok(SellQuote { amount, funds: funds.saturating_sub(fee), fee })
+}
+
+// 第三方手续费按基点从到手金额中扣除
+fn third_party_fee(net: U256, fee_config: &SellFeeConfig) -> U256 {
+    net * U256::from(fee_config.fee_rate_bps) / U256::from(10_000)
+}
+
+// TokenManager V1
+pub struct TokenManagerV1Venue<M> {
+    client: Arc<M>,
+    token_manager: Address,
+    helper: ITokenManagerHelper3<M>,
+    journal: Option<PathBuf>,
+}
+
+impl<M: Middleware + 'static> TokenManagerV1Venue<M> {
+    pub fn new(client: Arc<M>, token_manager: Address, helper: Address, config: &RuntimeConfig) -> Self {
+        let helper = ITokenManagerHelper3::new(helper, client.clone());
+        Self { client, token_manager, helper, journal: config.trade_journal.clone() }
+    }
+}
+
+impl<M: Middleware + 'static> TradingVenue for TokenManagerV1Venue<M> {
+    fn name(&self) -> &'static str {
+        "TokenManager V1"
+    }
+
+    fn approval_target(&self) -> Address {
+        self.token_manager
+    }
+
+    async fn quote_buy(&self, token: Address, funds: U256) -> std::result::Result<BuyQuote, Box<dyn std::error::Error>> {
+        helper_quote_buy(&self.helper, token, funds).await
+    }
+
+    async fn quote_sell(&self, token: Address, amount: U256) -> std::result::Result<SellQuote, Box<dyn std::error::Error>> {
+        helper_quote_sell(&self.helper, token, amount).await
+    }
+
+    async fn buy(
+        &self,
+        token: Address,
+        funds: U256,
+        min_amount: U256,
+    ) -> std::result::Result<TransactionReceipt, Box<dyn std::error::Error>> {
+        let token_manager = ITokenManagerV1::new(self.token_manager, self.client.clone());
+        let buy_tx = token_manager.purchase_token_amap(token, funds, min_amount).value(funds);
+        let pending = buy_tx.send().await?;
+        confirm(&*self.client, self.journal.as_deref(), "purchaseTokenAMAP", pending).await
+    }
+
+    async fn sell(
+        &self,
+        token: Address,
+        amount: U256,
+        min_funds: U256,
+    ) -> std::result::Result<TransactionReceipt, Box<dyn std::error::Error>> {
+        // V1的saleToken没有最少获得参数，只能在发送前按预估检查
+        if !min_funds.is_zero() {
+            let quote = self.quote_sell(token, amount).await?;
+            if quote.funds < min_funds {
+                return Err(format!("预估获得 {} 低于最少获得 {}，取消卖出", quote.funds, min_funds).into());
+            }
+        }
+        let token_manager = ITokenManagerV1::new(self.token_manager, self.client.clone());
+        let sell_tx = token_manager.sale_token(token, amount);
+        let pending = sell_tx.send().await?;
+        confirm(&*self.client, self.journal.as_deref(), "saleToken", pending).await
+    }
+}
+
+// TokenManager V2（BNB计价）
+pub struct TokenManagerV2Venue<M> {
+    client: Arc<M>,
+    token_manager: Address,
+    helper: ITokenManagerHelper3<M>,
+    fee_config: SellFeeConfig,
+    journal: Option<PathBuf>,
+}
+
+impl<M: Middleware + 'static> TokenManagerV2Venue<M> {
+    pub fn new(
+        client: Arc<M>,
+        token_manager: Address,
+        helper: Address,
+        fee_config: SellFeeConfig,
+        config: &RuntimeConfig,
+    ) -> Self {
+        let helper = ITokenManagerHelper3::new(helper, client.clone());
+        Self { client, token_manager, helper, fee_config, journal: config.trade_journal.clone() }
+    }
+}
+
+impl<M: Middleware + 'static> TradingVenue for TokenManagerV2Venue<M> {
+    fn name(&self) -> &'static str {
+        "TokenManager V2"
+    }
+
+    fn approval_target(&self) -> Address {
+        self.token_manager
+    }
+
+    async fn quote_buy(&self, token: Address, funds: U256) -> std::result::Result<BuyQuote, Box<dyn std::error::Error>> {
+        helper_quote_buy(&self.helper, token, funds).await
+    }
+
+    async fn quote_sell(&self, token: Address, amount: U256) -> std::result::Result<SellQuote, Box<dyn std::error::Error>> {
+        let quote = helper_quote_sell(&self.helper, token, amount).await?;
+        let extra_fee = third_party_fee(quote.funds, &self.fee_config);
+        Ok(SellQuote { amount, funds: quote.funds - extra_fee, fee: quote.fee + extra_fee })
+    }
+
+    async fn buy(
+        &self,
+        token: Address,
+        funds: U256,
+        min_amount: U256,
+    ) -> std::result::Result<TransactionReceipt, Box<dyn std::error::Error>> {
+        let token_manager = ITokenManager2::new(self.token_manager, self.client.clone());
+        let buy_tx = token_manager
+            .buy_token_amap(token, sender(&*self.client)?, funds, min_amount)
+            .value(funds);
+        let pending = buy_tx.send().await?;
+        confirm(&*self.client, self.journal.as_deref(), "buyTokenAMAP", pending).await
+    }
+
+    async fn sell(
+        &self,
+        token: Address,
+        amount: U256,
+        min_funds: U256,
+    ) -> std::result::Result<TransactionReceipt, Box<dyn std::error::Error>> {
+        // 超过合约上限的手续费率会回滚，发送前再检查一次
+        self.fee_config.validate()?;
+        self.fee_config.print();
+        let token_manager = ITokenManager2::new(self.token_manager, self.client.clone());
+        let sell_tx = token_manager.sell_token(
+            self.fee_config.origin,
+            token,
+            sender(&*self.client)?,
+            amount,
+            min_funds,
+            U256::from(self.fee_config.fee_rate_bps),
+            self.fee_config.fee_recipient,
+        );
+        let pending = sell_tx.send().await?;
+        confirm(&*self.client, self.journal.as_deref(), "sellToken", pending).await
+    }
+}
+
+// Helper3的ERC20计价路径：BNB与quote代币的兑换由Helper3在PancakeSwap完成，
+// 预估时同样按PancakeSwap的价格把BNB和quote代币互相折算
+pub struct Helper3Erc20Venue<M> {
+    helper: ITokenManagerHelper3<M>,
+    pancake: IPancakeRouter02<M>,
+    client: Arc<M>,
+    quote: Address,
+    wbnb: Address,
+    fee_config: SellFeeConfig,
+    journal: Option<PathBuf>,
+}
+
+impl<M: Middleware + 'static> Helper3Erc20Venue<M> {
+    pub fn new(
+        client: Arc<M>,
+        helper: Address,
+        quote: Address,
+        pancake_router: Address,
+        wbnb: Address,
+        fee_config: SellFeeConfig,
+        config: &RuntimeConfig,
+    ) -> Self {
+        Self {
+            helper: ITokenManagerHelper3::new(helper, client.clone()),
+            pancake: IPancakeRouter02::new(pancake_router, client.clone()),
+            client,
+            quote,
+            wbnb,
+            fee_config,
+            journal: config.trade_journal.clone(),
+        }
+    }
+
+    async fn amount_out(&self, amount_in: U256, path: Vec<Address>) -> std::result::Result<U256, Box<dyn std::error::Error>> {
+        if amount_in.is_zero() {
+            return Ok(U256::zero());
+        }
+        let amounts = self.pancake.get_amounts_out(amount_in, path).call().await?;
+        amounts.last().copied().ok_or_else(|| "getAmountsOut没有返回结果".into())
+    }
+}
+
+// 按比例把quote代币计价的手续费折算为BNB
+fn convert_fee(fee: U256, quote_amount: U256, bnb_amount: U256) -> U256 {
+    if quote_amount.is_zero() {
+        return U256::zero();
+    }
+    fee * bnb_amount / quote_amount
+}
+
+impl<M: Middleware + 'static> TradingVenue for Helper3Erc20Venue<M> {
+    fn name(&self) -> &'static str {
+        "Helper3 (ERC20计价)"
+    }
+
+    fn approval_target(&self) -> Address {
+        self.helper.address()
+    }
+
+    async fn quote_buy(&self, token: Address, funds: U256) -> std::result::Result<BuyQuote, Box<dyn std::error::Error>> {
+        let quote_funds = self.amount_out(funds, vec![self.wbnb, self.quote]).await?;
+        let quote = helper_quote_buy(&self.helper, token, quote_funds).await?;
+        Ok(BuyQuote { funds, amount: quote.amount, fee: convert_fee(quote.fee, quote_funds, funds) })
+    }
+
+    async fn quote_sell(&self, token: Address, amount: U256) -> std::result::Result<SellQuote, Box<dyn std::error::Error>> {
+        let quote = helper_quote_sell(&self.helper, token, amount).await?;
+        let extra_fee = third_party_fee(quote.funds, &self.fee_config);
+        let net_quote = quote.funds - extra_fee;
+        let funds = self.amount_out(net_quote, vec![self.quote, self.wbnb]).await?;
+        Ok(SellQuote { amount, funds, fee: convert_fee(quote.fee + extra_fee, net_quote, funds) })
+    }
+
+    async fn buy(
+        &self,
+        token: Address,
+        funds: U256,
+        min_amount: U256,
+    ) -> std::result::Result<TransactionReceipt, Box<dyn std::error::Error>> {
+        let buy_tx = self
+            .helper
+            .buy_with_eth(self.fee_config.origin, token, sender(&*self.client)?, funds, min_amount)
+            .value(funds);
+        let pending = buy_tx.send().await?;
+        confirm(&*self.client, self.journal.as_deref(), "buyWithEth", pending).await
+    }
+
+    async fn sell(
+        &self,
+        token: Address,
+        amount: U256,
+        min_funds: U256,
+    ) -> std::result::Result<TransactionReceipt, Box<dyn std::error::Error>> {
+        self.fee_config.validate()?;
+        self.fee_config.print();
+        let sell_tx = self.helper.sell_for_eth(
+            self.fee_config.origin,
+            token,
+            amount,
+            min_funds,
+            U256::from(self.fee_config.fee_rate_bps),
+            self.fee_config.fee_recipient,
+        );
+        let pending = sell_tx.send().await?;
+        confirm(&*self.client, self.journal.as_deref(), "sellForEth", pending).await
+    }
+}
+
+// PancakeSwap V2，ERC20计价的代币经quote代币中转
+pub struct PancakeSwapVenue<M> {
+    client: Arc<M>,
+    router: IPancakeRouter02<M>,
+    wbnb: Address,
+    quote: Address,
+    journal: Option<PathBuf>,
+}
+
+impl<M: Middleware + 'static> PancakeSwapVenue<M> {
+    pub fn new(client: Arc<M>, router: Address, wbnb: Address, quote: Address, config: &RuntimeConfig) -> Self {
+        let router = IPancakeRouter02::new(router, client.clone());
+        Self { client, router, wbnb, quote, journal: config.trade_journal.clone() }
+    }
+
+    // 买入路径，卖出时反向
+    fn buy_path(&self, token: Address) -> Vec<Address> {
+        swap_path(self.wbnb, self.quote, token)
+    }
+
+    fn deadline() -> U256 {
+        U256::from(Utc::now().timestamp() + PANCAKE_SWAP_DEADLINE_SECS)
+    }
+
+    async fn amount_out(&self, amount_in: U256, path: Vec<Address>) -> std::result::Result<U256, Box<dyn std::error::Error>> {
+        let amounts = self.router.get_amounts_out(amount_in, path).call().await?;
+        amounts.last().copied().ok_or_else(|| "getAmountsOut没有返回结果".into())
+    }
+}
+
+fn swap_path(wbnb: Address, quote: Address, token: Address) -> Vec<Address> {
+    if quote.is_zero() || quote == wbnb {
+        vec![wbnb, token]
+    } else {
+        vec![wbnb, quote, token]
+    }
+}
+
+// 每一跳按LP手续费估算
+fn pancake_fee(amount: U256, hops: usize) -> U256 {
+    amount * U256::from(PANCAKE_V2_FEE_BPS * hops as u64) / U256::from(10_000)
+}
+
+impl<M: Middleware + 'static> TradingVenue for PancakeSwapVenue<M> {
+    fn name(&self) -> &'static str {
+        "PancakeSwap V2"
+    }
+
+    fn approval_target(&self) -> Address {
+        self.router.address()
+    }
+
+    async fn quote_buy(&self, token: Address, funds: U256) -> std::result::Result<BuyQuote, Box<dyn std::error::Error>> {
+        let path = self.buy_path(token);
+        let hops = path.len() - 1;
+        let amount = self.amount_out(funds, path).await?;
+        Ok(BuyQuote { funds, amount, fee: pancake_fee(funds, hops) })
+    }
+
+    async fn quote_sell(&self, token: Address, amount: U256) -> std::result::Result<SellQuote, Box<dyn std::error::Error>> {
+        let mut path = self.buy_path(token);
+        path.reverse();
+        let hops = path.len() - 1;
+        let funds = self.amount_out(amount, path).await?;
+        Ok(SellQuote { amount, funds, fee: pancake_fee(funds, hops) })
+    }
+
+    async fn buy(
+        &self,
+        token: Address,
+        funds: U256,
+        min_amount: U256,
+    ) -> std::result::Result<TransactionReceipt, Box<dyn std::error::Error>> {
+        let buy_tx = self
+            .router
+            .swap_exact_eth_for_tokens_supporting_fee_on_transfer_tokens(
+                min_amount,
+                self.buy_path(token),
+                sender(&*self.client)?,
+                Self::deadline(),
+            )
+            .value(funds);
+        let pending = buy_tx.send().await?;
+        confirm(&*self.client, self.journal.as_deref(), "swapExactETHForTokens", pending).await
+    }
+
+    async fn sell(
+        &self,
+        token: Address,
+        amount: U256,
+        min_funds: U256,
+    ) -> std::result::Result<TransactionReceipt, Box<dyn std::error::Error>> {
+        let mut path = self.buy_path(token);
+        path.reverse();
+        let sell_tx = self.router.swap_exact_tokens_for_eth_supporting_fee_on_transfer_tokens(
+            amount,
+            min_funds,
+            path,
+            sender(&*self.client)?,
+            Self::deadline(),
+        );
+        let pending = sell_tx.send().await?;
+        confirm(&*self.client, self.journal.as_deref(), "swapExactTokensForETH", pending).await
+    }
+}
+
+// VenueRouter选出的场所
+pub enum Venue<M> {
+    TokenManagerV1(TokenManagerV1Venue<M>),
+    TokenManagerV2(TokenManagerV2Venue<M>),
+    Helper3Erc20(Helper3Erc20Venue<M>),
+    PancakeSwap(PancakeSwapVenue<M>),
+}
+
+impl<M: Middleware + 'static> TradingVenue for Venue<M> {
+    fn name(&self) -> &'static str {
+        match self {
+            Venue::TokenManagerV1(venue) => venue.name(),
+            Venue::TokenManagerV2(venue) => venue.name(),
+            Venue::Helper3Erc20(venue) => venue.name(),
+            Venue::PancakeSwap(venue) => venue.name(),
+        }
+    }
+
+    fn approval_target(&self) -> Address {
+        match self {
+            Venue::TokenManagerV1(venue) => venue.approval_target(),
+            Venue::TokenManagerV2(venue) => venue.approval_target(),
+            Venue::Helper3Erc20(venue) => venue.approval_target(),
+            Venue::PancakeSwap(venue) => venue.approval_target(),
+        }
+    }
+
+    async fn quote_buy(&self, token: Address, funds: U256) -> std::result::Result<BuyQuote, Box<dyn std::error::Error>> {
+        match self {
+            Venue::TokenManagerV1(venue) => venue.quote_buy(token, funds).await,
+            Venue::TokenManagerV2(venue) => venue.quote_buy(token, funds).await,
+            Venue::Helper3Erc20(venue) => venue.quote_buy(token, funds).await,
+            Venue::PancakeSwap(venue) => venue.quote_buy(token, funds).await,
+        }
+    }
+
+    async fn quote_sell(&self, token: Address, amount: U256) -> std::result::Result<SellQuote, Box<dyn std::error::Error>> {
+        match self {
+            Venue::TokenManagerV1(venue) => venue.quote_sell(token, amount).await,
+            Venue::TokenManagerV2(venue) => venue.quote_sell(token, amount).await,
+            Venue::Helper3Erc20(venue) => venue.quote_sell(token, amount).await,
+            Venue::PancakeSwap(venue) => venue.quote_sell(token, amount).await,
+        }
+    }
+
+    async fn buy(
+        &self,
+        token: Address,
+        funds: U256,
+        min_amount: U256,
+    ) -> std::result::Result<TransactionReceipt, Box<dyn std::error::Error>> {
+        match self {
+            Venue::TokenManagerV1(venue) => venue.buy(token, funds, min_amount).await,
+            Venue::TokenManagerV2(venue) => venue.buy(token, funds, min_amount).await,
+            Venue::Helper3Erc20(venue) => venue.buy(token, funds, min_amount).await,
+            Venue::PancakeSwap(venue) => venue.buy(token, funds, min_amount).await,
+        }
+    }
+
+    async fn sell(
+        &self,
+        token: Address,
+        amount: U256,
+        min_funds: U256,
+    ) -> std::result::Result<TransactionReceipt, Box<dyn std::error::Error>> {
+        match self {
+            Venue::TokenManagerV1(venue) => venue.sell(token, amount, min_funds).await,
+            Venue::TokenManagerV2(venue) => venue.sell(token, amount, min_funds).await,
+            Venue::Helper3Erc20(venue) => venue.sell(token, amount, min_funds).await,
+            Venue::PancakeSwap(venue) => venue.sell(token, amount, min_funds).await,
+        }
+    }
+}
+
+// 按代币选择交易场所
+pub struct VenueRouter<M> {
+    client: Arc<M>,
+    helper: ITokenManagerHelper3<M>,
+    pancake_router: Address,
+    wbnb: Address,
+    fee_config: SellFeeConfig,
+    config: RuntimeConfig,
+}
+
+impl<M: Middleware + 'static> VenueRouter<M> {
+    // PancakeSwap Router和WBNB使用配置中的BSC主网地址
+    pub fn new(
+        client: Arc<M>,
+        helper: Address,
+        fee_config: SellFeeConfig,
+        config: &RuntimeConfig,
+    ) -> std::result::Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            helper: ITokenManagerHelper3::new(helper, client.clone()),
+            client,
+            pancake_router: PANCAKE_ROUTER_V2_ADDRESS.parse()?,
+            wbnb: WBNB_ADDRESS.parse()?,
+            fee_config,
+            config: config.clone(),
+        })
+    }
+
+    // 替换PancakeSwap Router和WBNB地址，例如测试链或其他网络
+    pub fn with_pancake(mut self, pancake_router: Address, wbnb: Address) -> Self {
+        self.pancake_router = pancake_router;
+        self.wbnb = wbnb;
+        self
+    }
+
+    pub async fn venue_for(&self, token: Address) -> std::result::Result<Venue<M>, Box<dyn std::error::Error>> {
+        let (version, token_manager, quote, .., liquidity_added) = self.helper.get_token_info(token).call().await?;
+        self.venue_from_info(version, token_manager, quote, liquidity_added)
+    }
+
+    // 已经查询过getTokenInfo时直接按结果构造
+    pub fn venue_from_info(
+        &self,
+        version: U256,
+        token_manager: Address,
+        quote: Address,
+        liquidity_added: bool,
+    ) -> std::result::Result<Venue<M>, Box<dyn std::error::Error>> {
+        let client = self.client.clone();
+        let helper = self.helper.address();
+        Ok(match select_venue(version, quote, liquidity_added)? {
+            VenueKind::TokenManagerV1 => Venue::TokenManagerV1(TokenManagerV1Venue::new(client, token_manager, helper, &self.config)),
+            VenueKind::TokenManagerV2 => {
+                Venue::TokenManagerV2(TokenManagerV2Venue::new(client, token_manager, helper, self.fee_config, &self.config))
+            }
+            VenueKind::Helper3Erc20 => Venue::Helper3Erc20(Helper3Erc20Venue::new(
+                client,
+                helper,
+                quote,
+                self.pancake_router,
+                self.wbnb,
+                self.fee_config,
+                &self.config,
+            )),
+            VenueKind::PancakeSwap => Venue::PancakeSwap(PancakeSwapVenue::new(client, self.pancake_router, self.wbnb, quote, &self.config)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn venue_follows_token_info() {
+        let erc20_quote = Address::repeat_byte(0x55);
+        let cases = [
+            (1, Address::zero(), false, VenueKind::TokenManagerV1),
+            (2, Address::zero(), false, VenueKind::TokenManagerV2),
+            (2, erc20_quote, false, VenueKind::Helper3Erc20),
+            (1, Address::zero(), true, VenueKind::PancakeSwap),
+            (2, erc20_quote, true, VenueKind::PancakeSwap),
+        ];
+        for (version, quote, liquidity_added, expected) in cases {
+            assert_eq!(select_venue(U256::from(version), quote, liquidity_added).unwrap(), expected);
+        }
+        assert!(select_venue(U256::from(3), Address::zero(), false).is_err());
+    }
+
+    #[test]
+    fn erc20_quoted_tokens_route_through_quote_on_pancake() {
+        let wbnb = Address::repeat_byte(0xbb);
+        let quote = Address::repeat_byte(0x55);
+        let token = Address::repeat_byte(0x44);
+        assert_eq!(swap_path(wbnb, Address::zero(), token), vec![wbnb, token]);
+        assert_eq!(swap_path(wbnb, quote, token), vec![wbnb, quote, token]);
+        assert_eq!(pancake_fee(U256::from(10_000), 2), U256::from(PANCAKE_V2_FEE_BPS * 2));
+    }
+}
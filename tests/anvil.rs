@@ -0,0 +1,452 @@
+// 本地anvil集成测试
+// 部署 tests/contracts/MockFourMeme.sol 中的TokenManager2/Helper3/ERC20替身（事件与线上一致，并按线上错误码回滚），
+// 端到端运行卖出流程和创建代币流程的链上部分；路由测试部署 fourmeme/1.sol 中真实的TokenTradeWithFee，Four.meme API由进程内mock服务代替，不访问任何外部网络。
+// 默认标记为ignored，运行方式：FOURMEME_ANVIL_TESTS=1 cargo test --test anvil（见build.rs）。
+// 启用后需要本机安装anvil；solc依次取环境变量SOLC、PATH中的solc，都没有时通过svm安装固定版本，
+// 缺少anvil或无法取得solc时测试失败，不会静默通过。
+// 流程中的记录文件（data/...）都是相对路径，测试进程切换到临时目录运行，不会写入项目的data目录。
+
+#[allow(dead_code)]
+#[path = "../src/mock_server.rs"]
+mod mock_server;
+
+use ethers::{
+    abi::Abi,
+    prelude::*,
+    types::{Address, Bytes, U256},
+    utils::{parse_ether, Anvil, AnvilInstance},
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+use fourmeme_auto_sell::api::CreateTokenParams;
+use fourmeme_auto_sell::config::RuntimeConfig;
+use fourmeme_auto_sell::contracts::{ITokenManager2, TokenCreateFilter, TokenSaleFilter, IERC20};
+use fourmeme_auto_sell::creation::create_token_and_buy;
+use fourmeme_auto_sell::metadata::TokenLabel;
+use fourmeme_auto_sell::{proxy, router};
+use fourmeme_auto_sell::sell_fee::SellFeeConfig;
+use fourmeme_auto_sell::token_image::ImageSource;
+use fourmeme_auto_sell::trading::{execute_sell_process, sell_tokens_only};
+use fourmeme_auto_sell::venue::{TokenManagerV2Venue, TradingVenue};
+
+use mock_server::MockFourMeme;
+
+type Client = SignerMiddleware<Provider<Http>, LocalWallet>;
+
+// 本机没有solc时通过svm安装的版本（安装到~/.svm，之后复用）
+const SOLC_VERSION: &str = "0.8.24";
+
+// 与MockTokenManager2中的常量一致
+const RATE: u64 = 1_000_000;
+const FEE_BPS: u64 = 100;
+
+struct Chain {
+    _anvil: AnvilInstance,
+    _data: tempfile::TempDir,
+    runtime: RuntimeConfig, // 会话、断点、缓存和交易记录都写到本测试的临时目录
+    client: Arc<Client>,
+    wallet: LocalWallet,
+    fee_recipient: Address,
+    manager: Address,
+    helper: Address,
+}
+
+fn manifest_path(path: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(path)
+}
+
+// solc路径：环境变量SOLC > PATH中的solc > svm安装的SOLC_VERSION
+async fn solc_path() -> PathBuf {
+    if let Ok(path) = std::env::var("SOLC") {
+        return path.into();
+    }
+    if Command::new("solc").arg("--version").output().is_ok_and(|o| o.status.success()) {
+        return "solc".into();
+    }
+    let version = semver::Version::parse(SOLC_VERSION).unwrap();
+    if svm_lib::installed_versions().unwrap_or_default().contains(&version) {
+        return svm_lib::version_path(SOLC_VERSION).join(format!("solc-{}", SOLC_VERSION));
+    }
+    svm_lib::install(&version)
+        .await
+        .unwrap_or_else(|e| panic!("本机没有solc，通过svm安装solc {}失败: {}", SOLC_VERSION, e))
+}
+
+// 用solc编译合约，返回 合约名 -> (ABI, 字节码)
+fn compile(
+    solc: &Path,
+    source: &Path,
+    extra_args: &[&str],
+) -> std::result::Result<HashMap<String, (Abi, Bytes)>, Box<dyn std::error::Error>> {
+    let output = Command::new(solc)
+        .args(["--combined-json", "abi,bin", "--optimize"])
+        .args(extra_args)
+        .arg(source)
+        .output()
+        .map_err(|e| format!("无法运行solc {}: {}", solc.display(), e))?;
+    if !output.status.success() {
+        return Err(format!("solc编译失败: {}", String::from_utf8_lossy(&output.stderr)).into());
+    }
+
+    let combined: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    let contracts = combined["contracts"].as_object().ok_or("solc输出中没有contracts")?;
+    let mut compiled = HashMap::new();
+    for (name, contract) in contracts {
+        let name = name.rsplit(':').next().unwrap_or(name).to_string();
+        // 旧版solc的abi是JSON字符串，新版是数组
+        let abi: Abi = match contract["abi"].as_str() {
+            Some(abi) => serde_json::from_str(abi)?,
+            None => serde_json::from_value(contract["abi"].clone())?,
+        };
+        let bin = contract["bin"].as_str().ok_or("solc输出中没有bin")?;
+        compiled.insert(name, (abi, hex::decode(bin)?.into()));
+    }
+    Ok(compiled)
+}
+
+// mock合约只编译一次，所有测试共用
+async fn mocks() -> &'static HashMap<String, (Abi, Bytes)> {
+    static MOCKS: OnceCell<HashMap<String, (Abi, Bytes)>> = OnceCell::const_new();
+    MOCKS
+        .get_or_init(|| async {
+            let solc = solc_path().await;
+            compile(&solc, &manifest_path("tests/contracts/MockFourMeme.sol"), &["--via-ir"])
+                .unwrap_or_else(|e| panic!("编译mock合约失败: {}", e))
+        })
+        .await
+}
+
+async fn deploy<T: abi::Tokenize>(client: &Arc<Client>, mocks: &HashMap<String, (Abi, Bytes)>, name: &str, args: T) -> Address {
+    let (abi, bytecode) = mocks[name].clone();
+    let factory = ContractFactory::new(abi, bytecode, client.clone());
+    factory.deploy(args).unwrap().send().await.unwrap().address()
+}
+
+// 编译 fourmeme/1.sol 中的TokenTradeWithFee；node_modules中没有hardhat/OpenZeppelin时
+// 使用 tests/contracts/deps 中的替身（console只导入不调用，ReentrancyGuard行为一致）
+async fn router_contract() -> &'static (Abi, Bytes) {
+    static ROUTER: OnceCell<(Abi, Bytes)> = OnceCell::const_new();
+    ROUTER
+        .get_or_init(|| async {
+            let solc = solc_path().await;
+            let root = manifest_path("");
+            let mut args = vec!["--base-path".to_string(), root.display().to_string()];
+            for include in ["node_modules", "tests/contracts/deps"] {
+                let path = root.join(include);
+                if path.is_dir() {
+                    args.extend(["--include-path".to_string(), path.display().to_string()]);
+                }
+            }
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            let mut compiled = compile(&solc, &manifest_path(fourmeme_auto_sell::config::ROUTER_SOURCE_PATH), &args)
+                .unwrap_or_else(|e| panic!("编译TokenTradeWithFee失败: {}", e));
+            compiled.remove("TokenTradeWithFee").expect("编译结果中没有TokenTradeWithFee")
+        })
+        .await
+}
+
+// 启动anvil并部署mock合约，缺少anvil或solc时直接失败
+async fn setup() -> Chain {
+    assert!(
+        Command::new("anvil").arg("--version").output().is_ok_and(|o| o.status.success()),
+        "未安装anvil（https://book.getfoundry.sh/getting-started/installation）"
+    );
+    let mocks = mocks().await;
+    let data = tempfile::tempdir().unwrap();
+    let runtime = RuntimeConfig {
+        api_base_url: "http://127.0.0.1:9/meme-api".to_string(),
+        session_dir: Some(data.path().join("sessions")),
+        checkpoint_dir: data.path().join("creations"),
+        platform_config_cache: Some(data.path().join("platform_config.json")),
+        trade_journal: Some(data.path().join("trades.jsonl")),
+    };
+
+    let anvil = Anvil::new().spawn();
+    let wallet: LocalWallet = anvil.keys()[0].clone().into();
+    let wallet = wallet.with_chain_id(anvil.chain_id());
+    let provider = Provider::<Http>::try_from(anvil.endpoint()).unwrap();
+    let client = Arc::new(SignerMiddleware::new(provider, wallet.clone()));
+
+    let manager = deploy(&client, mocks, "MockTokenManager2", ()).await;
+    let helper = deploy(&client, mocks, "MockTokenManagerHelper3", manager).await;
+    Chain {
+        fee_recipient: anvil.addresses()[1],
+        _anvil: anvil,
+        _data: data,
+        runtime,
+        client,
+        wallet,
+        manager,
+        helper,
+    }
+}
+
+// 直接调用mock的createToken（不预购），返回新代币地址
+async fn create_mock_token(chain: &Chain) -> Address {
+    let token_manager = ITokenManager2::new(chain.manager, chain.client.clone());
+    let create_tx = token_manager.create_token(vec![0u8; 32].into(), vec![1u8; 65].into());
+    let receipt = create_tx.send().await.unwrap().await.unwrap().unwrap();
+    receipt
+        .logs
+        .iter()
+        .find_map(|log| parse_log::<TokenCreateFilter>(log.clone()).ok())
+        .map(|event| event.token)
+        .unwrap()
+}
+
+fn fee_config(chain: &Chain, fee_rate_bps: u64) -> SellFeeConfig {
+    SellFeeConfig {
+        origin: U256::zero(),
+        fee_rate_bps,
+        fee_recipient: chain.fee_recipient,
+    }
+}
+
+fn v2_venue(chain: &Chain, fee_rate_bps: u64) -> TokenManagerV2Venue<Client> {
+    TokenManagerV2Venue::new(chain.client.clone(), chain.manager, chain.helper, fee_config(chain, fee_rate_bps), &chain.runtime)
+}
+
+// 合约按固定价格成交：买入数量和卖出所得
+fn bought_amount(funds: U256) -> U256 {
+    let cost = funds - funds * U256::from(FEE_BPS) / U256::from(10_000);
+    cost * U256::from(RATE) / U256::exp10(9) * U256::exp10(9)
+}
+
+fn sale_cost_and_fee(amount: U256) -> (U256, U256) {
+    let cost = amount / U256::from(RATE);
+    (cost, cost * U256::from(FEE_BPS) / U256::from(10_000))
+}
+
+// 回滚原因可能以文本或ABI编码的十六进制出现在错误信息中
+fn reverted_with(err: &dyn std::error::Error, reason: &str) -> bool {
+    let message = err.to_string();
+    message.contains(reason) || message.contains(&hex::encode(reason))
+}
+
+#[tokio::test]
+#[cfg_attr(not(anvil_tests), ignore = "需要anvil: FOURMEME_ANVIL_TESTS=1 cargo test --test anvil")]
+async fn execute_sell_process_approves_and_sells_whole_balance() {
+    let chain = setup().await;
+    let token_address = create_mock_token(&chain).await;
+    let user = chain.wallet.address();
+    let token = IERC20::new(token_address, chain.client.clone());
+
+    let venue = v2_venue(&chain, 0);
+    venue.buy(token_address, parse_ether("1").unwrap(), U256::zero()).await.unwrap();
+    let amount = token.balance_of(user).call().await.unwrap();
+    assert_eq!(amount, bought_amount(parse_ether("1").unwrap()));
+
+    let recipient_before = chain.client.get_balance(chain.fee_recipient, None).await.unwrap();
+    let result = execute_sell_process(chain.client.clone(), token_address, chain.helper, &fee_config(&chain, 100), &chain.runtime)
+        .await
+        .unwrap();
+
+    let (cost, platform_fee) = sale_cost_and_fee(amount);
+    let third_party_fee = (cost - platform_fee) / U256::from(100);
+    assert!(result.approve_tx_hash.is_some());
+    assert_eq!(result.token_sold, amount);
+    assert_eq!(result.platform_fee, Some(platform_fee));
+    assert_eq!(result.third_party_fee, Some(third_party_fee));
+    assert_eq!(token.balance_of(user).call().await.unwrap(), U256::zero());
+    let recipient_after = chain.client.get_balance(chain.fee_recipient, None).await.unwrap();
+    assert_eq!(recipient_after - recipient_before, third_party_fee);
+
+    // 卖出交易中有真实签名的TokenSale事件
+    let receipt = chain.client.get_transaction_receipt(result.sell_tx_hash).await.unwrap().unwrap();
+    let sale = receipt
+        .logs
+        .iter()
+        .find_map(|log| parse_log::<TokenSaleFilter>(log.clone()).ok())
+        .unwrap();
+    assert_eq!((sale.token, sale.account, sale.amount), (token_address, user, amount));
+}
+
+#[tokio::test]
+#[cfg_attr(not(anvil_tests), ignore = "需要anvil: FOURMEME_ANVIL_TESTS=1 cargo test --test anvil")]
+async fn sell_tokens_only_enforces_gwei_precision() {
+    let chain = setup().await;
+    let token_address = create_mock_token(&chain).await;
+    let user = chain.wallet.address();
+    let token = IERC20::new(token_address, chain.client.clone());
+
+    v2_venue(&chain, 0).buy(token_address, parse_ether("0.5").unwrap(), U256::zero()).await.unwrap();
+    let amount = token.balance_of(user).call().await.unwrap();
+    let approve_tx = token.approve(chain.manager, amount);
+    approve_tx.send().await.unwrap().await.unwrap();
+
+    let err = sell_tokens_only(chain.client.clone(), token_address, chain.manager, chain.helper, amount - 1, &fee_config(&chain, 0), &chain.runtime)
+        .await
+        .unwrap_err();
+    assert!(reverted_with(&*err, "GW"), "unexpected error: {}", err);
+
+    let half = amount / U256::from(2) / U256::exp10(9) * U256::exp10(9);
+    let result = sell_tokens_only(chain.client.clone(), token_address, chain.manager, chain.helper, half, &fee_config(&chain, 0), &chain.runtime)
+        .await
+        .unwrap();
+    assert_eq!(result.platform_fee, Some(sale_cost_and_fee(half).1));
+    assert_eq!(result.third_party_fee, Some(U256::zero()));
+    assert_eq!(token.balance_of(user).call().await.unwrap(), amount - half);
+}
+
+#[tokio::test]
+#[cfg_attr(not(anvil_tests), ignore = "需要anvil: FOURMEME_ANVIL_TESTS=1 cargo test --test anvil")]
+async fn quotes_match_fills_and_slippage_reverts() {
+    let chain = setup().await;
+    let token_address = create_mock_token(&chain).await;
+    let token = IERC20::new(token_address, chain.client.clone());
+    let venue = v2_venue(&chain, 0);
+    let funds = parse_ether("0.3").unwrap();
+
+    let buy_quote = venue.quote_buy(token_address, funds).await.unwrap();
+    let err = venue.buy(token_address, funds, buy_quote.amount + 1).await.unwrap_err();
+    assert!(reverted_with(&*err, "Slippage"), "unexpected error: {}", err);
+    venue.buy(token_address, funds, buy_quote.amount).await.unwrap();
+    let amount = token.balance_of(chain.wallet.address()).call().await.unwrap();
+    assert_eq!(amount, buy_quote.amount);
+
+    let approve_tx = token.approve(venue.approval_target(), amount);
+    approve_tx.send().await.unwrap().await.unwrap();
+    let sell_quote = venue.quote_sell(token_address, amount).await.unwrap();
+    let err = venue.sell(token_address, amount, sell_quote.funds + 1).await.unwrap_err();
+    assert!(reverted_with(&*err, "Slippage"), "unexpected error: {}", err);
+    venue.sell(token_address, amount, sell_quote.funds).await.unwrap();
+    assert_eq!(token.balance_of(chain.wallet.address()).call().await.unwrap(), U256::zero());
+}
+
+#[tokio::test]
+#[cfg_attr(not(anvil_tests), ignore = "需要anvil: FOURMEME_ANVIL_TESTS=1 cargo test --test anvil")]
+async fn create_token_and_buy_runs_against_mock_api_and_chain() {
+    let chain = setup().await;
+    let mock = MockFourMeme::start().await;
+    let runtime = RuntimeConfig { api_base_url: mock.base_url.clone(), ..chain.runtime.clone() };
+
+    let params = CreateTokenParams {
+        name: "AnvilMeme".to_string(),
+        short_name: "ANVIL".to_string(),
+        desc: "anvil integration test token".to_string(),
+        img_url: String::new(),
+        launch_time: None,
+        label: TokenLabel::Meme,
+        web_url: None,
+        twitter_url: None,
+        telegram_url: None,
+        pre_sale: "0.2".to_string(),
+        raise_symbol: "BNB".to_string(),
+    };
+    let image = ImageSource::Path(manifest_path(fourmeme_auto_sell::config::CREATE_TOKEN_IMAGE_PATH));
+    let result = create_token_and_buy(
+        chain.client.clone(),
+        &chain.wallet,
+        chain.manager,
+        params,
+        &image,
+        &runtime,
+    )
+    .await
+    .unwrap();
+
+    assert_ne!(result.token_address, Address::zero());
+    assert_eq!(result.bnb_spent, parse_ether("0.2").unwrap());
+    assert_eq!(result.tokens_received, bought_amount(parse_ether("0.2").unwrap()));
+    assert!(result.approve_tx_hash.is_some());
+
+    let receipt = chain.client.get_transaction_receipt(result.create_tx_hash).await.unwrap().unwrap();
+    let created = receipt
+        .logs
+        .iter()
+        .find_map(|log| parse_log::<TokenCreateFilter>(log.clone()).ok())
+        .unwrap();
+    assert_eq!((created.creator, created.token), (chain.wallet.address(), result.token_address));
+    assert_eq!(created.request_id, U256::from(100_001));
+
+    let state = mock.state();
+    assert_eq!(state.logins.len(), 1);
+    assert_eq!(state.create_requests.len(), 1);
+    assert_eq!(state.create_requests[0]["preSale"], "0.2");
+
+    // 文件都写在配置的目录下：会话缓存、断点、平台配置缓存和预购产生的交易记录
+    assert!(runtime.session_dir.as_ref().unwrap().read_dir().unwrap().next().is_some());
+    assert!(runtime.checkpoint_dir.read_dir().unwrap().next().is_some());
+    assert!(runtime.platform_config_cache.as_ref().unwrap().exists());
+    assert!(runtime.trade_journal.as_ref().unwrap().exists());
+}
+
+#[tokio::test]
+#[cfg_attr(not(anvil_tests), ignore = "需要anvil: FOURMEME_ANVIL_TESTS=1 cargo test --test anvil")]
+async fn deploys_router_and_verifies_state() {
+    let chain = setup().await;
+    let (_, bytecode) = router_contract().await;
+
+    let err = router::deploy(chain.client.clone(), bytecode.clone(), chain.helper, chain.fee_recipient, 6)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("超过合约上限"), "unexpected error: {}", err);
+
+    let deployment = router::deploy(chain.client.clone(), bytecode.clone(), chain.helper, chain.fee_recipient, 2)
+        .await
+        .unwrap();
+    assert_eq!(deployment.owner, chain.wallet.address());
+    assert_eq!(deployment.chain_id, chain.client.get_chainid().await.unwrap().as_u64());
+    assert_eq!((deployment.token_manager_helper, deployment.fee_recipient, deployment.fee_rate), (chain.helper, chain.fee_recipient, 2));
+    assert!(!chain.client.get_code(deployment.address, None).await.unwrap().is_empty());
+
+    let path = Path::new("router_deployments.jsonl");
+    router::record_deployment(path, &deployment).unwrap();
+    let recorded: router::RouterDeployment =
+        serde_json::from_str(std::fs::read_to_string(path).unwrap().lines().last().unwrap()).unwrap();
+    assert_eq!((recorded.address, recorded.tx_hash), (deployment.address, deployment.tx_hash));
+}
+
+#[tokio::test]
+#[cfg_attr(not(anvil_tests), ignore = "需要anvil: FOURMEME_ANVIL_TESTS=1 cargo test --test anvil")]
+async fn proxy_router_buys_and_sells_with_fee() {
+    let chain = setup().await;
+    let (_, bytecode) = router_contract().await;
+    let router_address = router::deploy(chain.client.clone(), bytecode.clone(), chain.helper, chain.fee_recipient, 2)
+        .await
+        .unwrap()
+        .address;
+    let token_address = create_mock_token(&chain).await;
+    let user = chain.wallet.address();
+    let token = IERC20::new(token_address, chain.client.clone());
+
+    // 买入：2%手续费直接转给feeRecipient，其余资金在TokenManager买入
+    let funds = parse_ether("1").unwrap();
+    let fee = funds * U256::from(2) / U256::from(100);
+    let recipient_before = chain.client.get_balance(chain.fee_recipient, None).await.unwrap();
+    let buy = proxy::buy(chain.client.clone(), router_address, token_address, funds, 5, &chain.runtime).await.unwrap();
+    assert_eq!(buy.fee_paid, fee);
+    assert_eq!(buy.tokens_received, bought_amount(funds - fee));
+    let recipient_after_buy = chain.client.get_balance(chain.fee_recipient, None).await.unwrap();
+    assert_eq!(recipient_after_buy - recipient_before, fee);
+
+    // ProxyTokenPurchase的tokenAmount字段是转给TokenManager的BNB
+    let receipt = chain.client.get_transaction_receipt(buy.tx_hash).await.unwrap().unwrap();
+    let purchase = receipt.logs.iter().find_map(proxy::decode_purchase).unwrap();
+    assert_eq!((purchase.token, purchase.account, purchase.funds, purchase.fee), (token_address, user, funds - fee, fee));
+
+    // 卖出：路由以feeRate*100基点调用sellToken，手续费由TokenManager转给feeRecipient
+    let amount = token.balance_of(user).call().await.unwrap();
+    let sell = proxy::sell(chain.client.clone(), router_address, token_address, 5, &chain.runtime).await.unwrap();
+    let (cost, platform_fee) = sale_cost_and_fee(amount);
+    let router_fee = (cost - platform_fee) * proxy::fee_rate_bps(U256::from(2)) / U256::from(10_000);
+    assert_eq!(sell.token_sold, amount);
+    assert_eq!(sell.platform_fee, Some(platform_fee));
+    assert_eq!(sell.third_party_fee, Some(router_fee));
+    assert_eq!(token.balance_of(user).call().await.unwrap(), U256::zero());
+    let recipient_after_sell = chain.client.get_balance(chain.fee_recipient, None).await.unwrap();
+    assert_eq!(recipient_after_sell - recipient_after_buy, router_fee);
+
+    // ProxyTokenSold的fundsReceived字段是传入的minFunds，不是实际到手金额
+    let receipt = chain.client.get_transaction_receipt(sell.sell_tx_hash).await.unwrap().unwrap();
+    let sold = receipt.logs.iter().find_map(proxy::decode_sold).unwrap();
+    let net = cost - platform_fee;
+    let min_funds = (net - proxy::sell_fee(net, U256::from(2))) * U256::from(95) / U256::from(100);
+    assert_eq!((sold.token, sold.amount, sold.min_funds), (token_address, amount, min_funds));
+    assert_eq!(sell.bnb_received, net - router_fee);
+    assert!(sold.min_funds < sell.bnb_received);
+}
@@ -0,0 +1,194 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+// 集成测试用的Four.meme合约替身（只部署在本地anvil上）
+// - 事件签名与线上TokenManager2一致（TokenCreate/TokenPurchase/TokenSale）
+// - 固定价格：1 BNB = RATE 个代币，平台手续费1%
+// - 按线上错误码回滚：GW（未按GWEI对齐）、FR（手续费率超过5%）、ZA、SO、Slippage、More BNB
+
+contract MockToken {
+    string public name;
+    string public symbol;
+    uint8 public constant decimals = 18;
+    uint256 public totalSupply;
+    address public immutable minter;
+
+    mapping(address => uint256) public balanceOf;
+    mapping(address => mapping(address => uint256)) public allowance;
+
+    event Transfer(address indexed from, address indexed to, uint256 value);
+    event Approval(address indexed owner, address indexed spender, uint256 value);
+
+    constructor(string memory _name, string memory _symbol) {
+        name = _name;
+        symbol = _symbol;
+        minter = msg.sender;
+    }
+
+    function mint(address to, uint256 amount) external {
+        require(msg.sender == minter, "Only minter");
+        totalSupply += amount;
+        balanceOf[to] += amount;
+        emit Transfer(address(0), to, amount);
+    }
+
+    function approve(address spender, uint256 amount) external returns (bool) {
+        allowance[msg.sender][spender] = amount;
+        emit Approval(msg.sender, spender, amount);
+        return true;
+    }
+
+    function transfer(address to, uint256 amount) external returns (bool) {
+        _transfer(msg.sender, to, amount);
+        return true;
+    }
+
+    function transferFrom(address from, address to, uint256 amount) external returns (bool) {
+        uint256 allowed = allowance[from][msg.sender];
+        require(allowed >= amount, "Insufficient allowance");
+        allowance[from][msg.sender] = allowed - amount;
+        _transfer(from, to, amount);
+        return true;
+    }
+
+    function _transfer(address from, address to, uint256 amount) internal {
+        require(balanceOf[from] >= amount, "Insufficient balance");
+        balanceOf[from] -= amount;
+        balanceOf[to] += amount;
+        emit Transfer(from, to, amount);
+    }
+}
+
+contract MockTokenManager2 {
+    uint256 public constant RATE = 1_000_000;
+    uint256 public constant FEE_BPS = 100;
+    uint256 public constant MAX_FEE_RATE = 500;
+    uint256 public constant TOTAL_SUPPLY = 1_000_000_000 ether;
+    uint256 public constant GWEI = 1e9;
+
+    mapping(address => bool) public tokens;
+
+    event TokenCreate(address creator, address token, uint256 requestId, string name, string symbol, uint256 totalSupply, uint256 launchTime, uint256 launchFee);
+    event TokenPurchase(address token, address account, uint256 price, uint256 amount, uint256 cost, uint256 fee, uint256 offers, uint256 funds);
+    event TokenSale(address token, address account, uint256 price, uint256 amount, uint256 cost, uint256 fee, uint256 offers, uint256 funds);
+
+    receive() external payable {}
+
+    // 不校验签名内容，createArg的前32字节作为requestId；msg.value全部作为预购资金
+    function createToken(bytes calldata createArg, bytes calldata sign) external payable returns (address token) {
+        require(sign.length > 0, "Invalid signature");
+        uint256 requestId = createArg.length >= 32 ? uint256(bytes32(createArg[0:32])) : 0;
+
+        MockToken created = new MockToken("MockMeme", "MOCK");
+        created.mint(address(this), TOTAL_SUPPLY);
+        token = address(created);
+        tokens[token] = true;
+        emit TokenCreate(msg.sender, token, requestId, "MockMeme", "MOCK", TOTAL_SUPPLY, block.timestamp, 0);
+
+        if (msg.value > 0) {
+            _buy(token, msg.sender, msg.value, 0);
+        }
+    }
+
+    function buyTokenAMAP(address token, address to, uint256 funds, uint256 minAmount) external payable {
+        require(msg.value >= funds, "More BNB");
+        _buy(token, to, funds, minAmount);
+    }
+
+    function sellToken(
+        uint256,
+        address token,
+        address from,
+        uint256 amount,
+        uint256 minFunds,
+        uint256 feeRate,
+        address feeRecipient
+    ) external {
+        require(tokens[token], "Unknown token");
+        require(amount % GWEI == 0, "GW");
+        require(feeRate <= MAX_FEE_RATE, "FR");
+        require(from == msg.sender || from == tx.origin, "Invalid from");
+
+        (uint256 cost, uint256 fee) = quoteSell(amount);
+        require(cost > fee, "SO");
+        uint256 thirdPartyFee = (cost - fee) * feeRate / 10000;
+        uint256 funds = cost - fee - thirdPartyFee;
+        require(funds >= minFunds, "Slippage");
+
+        MockToken(token).transferFrom(from, address(this), amount);
+        emit TokenSale(token, from, price(), amount, cost, fee, offers(token), funds);
+        _pay(from, funds);
+        if (thirdPartyFee > 0) {
+            _pay(feeRecipient, thirdPartyFee);
+        }
+    }
+
+    function quoteBuy(uint256 funds) public pure returns (uint256 amount, uint256 cost, uint256 fee) {
+        fee = funds * FEE_BPS / 10000;
+        cost = funds - fee;
+        amount = cost * RATE / GWEI * GWEI;
+    }
+
+    function quoteSell(uint256 amount) public pure returns (uint256 cost, uint256 fee) {
+        cost = amount / RATE;
+        fee = cost * FEE_BPS / 10000;
+    }
+
+    function price() public pure returns (uint256) {
+        return 1 ether / RATE;
+    }
+
+    function offers(address token) public view returns (uint256) {
+        return MockToken(token).balanceOf(address(this));
+    }
+
+    function _buy(address token, address to, uint256 funds, uint256 minAmount) internal {
+        require(tokens[token], "Unknown token");
+        require(to != address(0), "ZA");
+        require(funds % GWEI == 0, "GW");
+
+        (uint256 amount, uint256 cost, uint256 fee) = quoteBuy(funds);
+        require(amount >= minAmount, "Slippage");
+        MockToken(token).transfer(to, amount);
+        emit TokenPurchase(token, to, price(), amount, cost, fee, offers(token), funds);
+    }
+
+    function _pay(address to, uint256 value) internal {
+        (bool success, ) = to.call{value: value}("");
+        require(success, "Transfer failed");
+    }
+}
+
+contract MockTokenManagerHelper3 {
+    MockTokenManager2 public immutable manager;
+
+    constructor(address payable _manager) {
+        manager = MockTokenManager2(_manager);
+    }
+
+    // 所有代币都是V2、BNB计价、未上PancakeSwap
+    function getTokenInfo(address token)
+        external
+        view
+        returns (uint256, address, address, uint256, uint256, uint256, uint256, uint256, uint256, uint256, uint256, bool)
+    {
+        uint256 offers = manager.offers(token);
+        return (2, address(manager), address(0), manager.price(), manager.FEE_BPS(), 0, 0, offers, manager.TOTAL_SUPPLY(), 0, 0, false);
+    }
+
+    // 只支持按资金预估（amount为0）
+    function tryBuy(address, uint256 amount, uint256 funds)
+        external
+        view
+        returns (address, address, uint256, uint256, uint256, uint256, uint256, uint256)
+    {
+        require(amount == 0, "Only funds mode");
+        (uint256 estimatedAmount, uint256 cost, uint256 fee) = manager.quoteBuy(funds);
+        return (address(manager), address(0), estimatedAmount, cost, fee, funds, 0, funds);
+    }
+
+    function trySell(address, uint256 amount) external view returns (address, address, uint256, uint256) {
+        (uint256 cost, uint256 fee) = manager.quoteSell(amount);
+        return (address(manager), address(0), cost, fee);
+    }
+}
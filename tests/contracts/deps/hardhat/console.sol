@@ -0,0 +1,5 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+// 测试用替身：fourmeme/1.sol 只导入不调用hardhat的console，node_modules中没有hardhat时使用
+library console {}